@@ -1,11 +1,10 @@
-use p_arse::{any, eoi, CharExt, Parser, TupleExt};
+use p_arse::{eoi, take_while1, Parser, TupleExt};
 
 fn main() {
     let nl = '\n';
 
     let header = {
-        let header_content =
-            (nl.not_ahead(), any()).r0().more().maps(|s| s.to_string());
+        let header_content = take_while1(|c| c != nl).maps(|s| s.to_string());
         let header_tag = ">";
         let header = (header_tag, header_content, nl).r2().r0();
 
@@ -13,19 +12,11 @@ fn main() {
     };
 
     let sequence = {
-        let parse_sequence = |(first, tail): (String, Vec<String>)| {
-            let mut sequence = first;
-            tail.into_iter()
-                .for_each(|subsequence| sequence.push_str(&subsequence));
-
-            sequence
-        };
-
-        let sequence_char = ('A'.to('Z')).or('*').or('-');
-        let subsequence = sequence_char.more().maps(|s| s.to_string());
-        let sequence = (subsequence, (nl, subsequence).r0().zore(), nl.zore())
-            .r2()
-            .map(parse_sequence);
+        let line =
+            take_while1(|c| c.is_ascii_uppercase() || c == '*' || c == '-');
+        // A single `String` per sequence, with the lines joined.
+        let lines = (line, (nl, line).zore()).maps(|s| s.replace(nl, ""));
+        let sequence = (lines, nl.zore()).r1();
 
         sequence
     };
@@ -1,6 +1,6 @@
 // use std::iter::FromIterator;
 
-use p_arse::{CharExt, Parser, TupleExt};
+use p_arse::{numbers::hex, Parser, TupleExt};
 
 #[derive(Debug)]
 struct Color {
@@ -10,11 +10,9 @@ struct Color {
 }
 
 fn main() {
-    let parse_hex_dd = |s: &str| u8::from_str_radix(s, 16).unwrap();
     let construct_color = |(r, g, b)| Color { r, g, b };
 
-    let hex_d = ('0'.to('9')).or('a'.to('f'));
-    let hex_dd = (hex_d, hex_d).maps(parse_hex_dd);
+    let hex_dd = hex::<u8>().digits(2, 2);
     let color = ("#", hex_dd, hex_dd, hex_dd).r0().map(construct_color);
 
     let (color, _tail) = color.p_arse("#defec8").unwrap();
@@ -3,12 +3,13 @@
 use std::{collections::HashMap, iter::once};
 
 use p_arse::{
-    any,
-    function::{Rec, RecursiveFunction},
-    rec,
-    CharExt,
+    function::Rule,
+    lexeme::ws,
+    numbers::float,
+    rule,
+    seq,
+    strings::json_string,
     Parser,
-    TupleExt,
 };
 
 #[derive(Debug)]
@@ -26,72 +27,31 @@ type Object = HashMap<String, Json>;
 
 use Json::*;
 
-#[derive(Debug)]
-struct Error;
-
-type Result<T> = std::result::Result<T, Error>;
-
 fn main() {
     let null = "null";
 
     let boolean = ("true".map(|_| true)).or("false".map(|_| false));
 
-    let number = {
-        let parse_number = |s: &str| s.parse::<f64>().map_err(|_| Error);
-
-        let non_zero = ('1'.to('9'), '0'.to('9').zore()).ignore();
-        let zero = '0'.ignore();
-        let whole = non_zero.or(zero);
-        let fractional = ('.', '0'.to('9').more());
-        let exponential = ('e'.or('E'), '+'.or('-').opt(), whole);
-        let number =
-            (whole, fractional.opt(), exponential.opt()).maps(parse_number);
+    let number = float::<f64>();
 
-        number
-    };
-
-    let string = {
-        let special = '\\'
-            .or('/')
-            .or('"')
-            .or('b'.map(|_| '\x08'))
-            .or('n'.map(|_| '\n'))
-            .or('f'.map(|_| '\x0C'))
-            .or('r'.map(|_| '\r'))
-            .or('t'.map(|_| '\t'));
-
-        let escape_sequence = ('\\', special).r0();
-        let not_escape_or_ending = ('"'.or('\\').not_ahead(), any()).r0();
-        let string = (
-            '"',
-            not_escape_or_ending
-                .or(escape_sequence)
-                .zore()
-                .maps(|s| s.to_string()),
-            '"',
-        )
-            .r2()
-            .r0();
-
-        string
-    };
+    let string = json_string();
 
-    let ws = ' '.or('\n').or('\t').zore().ignore();
+    let ws = ws();
 
     // Waiting for #86921 to be resolved so that this function can be replaced
     // with a closure with its arg types and lifetimes inferred.
     fn array<'a>(
-        value: RecursiveFunction<'a, Result<Json>>,
+        value: Rule<'a, Json>,
         ws: impl Parser<Output = ()> + 'a,
-    ) -> impl Parser<Output = Result<Array>> + 'a {
+    ) -> impl Parser<Output = Array> + 'a {
         let collect_elements =
-            |(first, rest)| once(first).chain(rest).collect::<Result<Array>>();
+            |(first, rest)| once(first).chain(rest).collect::<Array>();
 
-        let element = (ws, value, ws).r2().r0();
-        let rest = (',', element).r0().zore();
+        let element = seq!(~ws, value, ~ws);
+        let rest = seq!(~',', element).zore();
         let elements = (element, rest).map(collect_elements);
-        let empty_array = ('[', ws, ']').map(|_| Ok(Array::new()));
-        let non_empty_array = ('[', elements, ']').r2().r0();
+        let empty_array = ('[', ws, ']').map(|_| Array::new());
+        let non_empty_array = seq!(~'[', elements, ~']');
         let array = empty_array.or(non_empty_array);
 
         array
@@ -101,40 +61,35 @@ fn main() {
     // with a closure with its arg types and lifetimes inferred.
     fn object<'a>(
         string: impl Parser<Output = String> + 'a,
-        value: RecursiveFunction<'a, Result<Json>>,
+        value: Rule<'a, Json>,
         ws: impl Parser<Output = ()> + 'a,
-    ) -> impl Parser<Output = Result<Object>> + 'a {
+    ) -> impl Parser<Output = Object> + 'a {
         let collect_members =
-            |(first, rest)| once(first).chain(rest).collect::<Result<Object>>();
-
-        let element = (ws, value, ws).r2().r0();
-        let member = (ws, string, ws, ':', element)
-            .r3()
-            .r2()
-            .r0()
-            .map(|(string, element)| Ok((string, element?)));
-        let rest = (',', member).r0().zore();
+            |(first, rest)| once(first).chain(rest).collect::<Object>();
+
+        let element = seq!(~ws, value, ~ws);
+        let member = seq!(~ws, string, ~ws, ~':', element);
+        let rest = seq!(~',', member).zore();
         let members = (member, rest).map(collect_members);
-        let empty_object = ('{', ws, '}').map(|_| Ok(Object::new()));
-        let non_empty_object = ('{', members, '}').r2().r0();
+        let empty_object = ('{', ws, '}').map(|_| Object::new());
+        let non_empty_object = seq!(~'{', members, ~'}');
         let object = empty_object.or(non_empty_object);
 
         object
     }
 
-    let value: &dyn Rec<_> = &|tail, value| {
+    let value = |value| {
         object(string, value, ws)
-            .map(|object| object.map(Object))
-            .or(array(value, ws).map(|array| array.map(Array)))
-            .or(number.map(|number| number.map(Num)))
-            .or(string.map(|string| Ok(Str(string))))
-            .or(boolean.map(|boolean| Ok(Bool(boolean))))
-            .or(null.map(|_| Ok(Null)))
-            .p_arse(tail)
+            .map(Object)
+            .or(array(value, ws).map(Array))
+            .or(number.map(Num))
+            .or(string.map(Str))
+            .or(boolean.map(Bool))
+            .or(null.map(|_| Null))
     };
-    let value = rec(value);
+    let value = rule("value", &value);
 
-    let json = (ws, value, ws).r2().r0();
+    let json = seq!(~ws, value, ~ws);
 
     let input = r#"
 	{
@@ -153,5 +108,5 @@ fn main() {
     }"#;
 
     let (json, _) = json.p_arse(input).unwrap();
-    dbg!(json.unwrap());
+    dbg!(json);
 }
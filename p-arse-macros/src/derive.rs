@@ -0,0 +1,521 @@
+//! `#[derive(Parse)]`.
+
+use std::iter::once;
+
+use proc_macro::{
+    Delimiter,
+    Group,
+    Ident,
+    Literal,
+    Spacing,
+    Span,
+    TokenStream,
+    TokenTree,
+};
+
+use crate::{
+    call,
+    code,
+    is_keyword,
+    is_punct,
+    punct,
+    separated,
+    tuple,
+    visibility_len,
+    Error,
+    Result,
+};
+
+#[derive(Default)]
+struct Attributes {
+    lit: Option<Literal>,
+    sep: Option<Literal>,
+    skip_ws: bool,
+}
+
+struct Field {
+    attributes: Attributes,
+    name: Option<Ident>,
+    ty: Vec<TokenTree>,
+}
+
+enum Fields {
+    Named(Vec<Field>),
+    Unnamed(Vec<Field>),
+    Unit,
+}
+
+struct Variant {
+    attributes: Attributes,
+    name: Ident,
+    fields: Fields,
+}
+
+// Parsing.
+
+fn parse_attributes(tokens: &[TokenTree], i: &mut usize) -> Result<Attributes> {
+    let mut attributes = Attributes::default();
+
+    while is_punct(tokens.get(*i), '#') {
+        let group = match tokens.get(*i + 1) {
+            Some(TokenTree::Group(group))
+                if group.delimiter() == Delimiter::Bracket =>
+            {
+                group
+            }
+            _ => break,
+        };
+        *i += 2;
+
+        let content: Vec<_> = group.stream().into_iter().collect();
+        if !is_keyword(content.first(), "p_arse") {
+            continue;
+        }
+
+        let arguments = match content.get(1) {
+            Some(TokenTree::Group(arguments))
+                if arguments.delimiter() == Delimiter::Parenthesis =>
+            {
+                arguments
+            }
+            _ => {
+                return Err(Error::new(
+                    "expected `#[p_arse(...)]`",
+                    group.span(),
+                ))
+            }
+        };
+        let arguments: Vec<_> = arguments.stream().into_iter().collect();
+
+        for argument in split(&arguments) {
+            parse_argument(argument, &mut attributes)?;
+        }
+    }
+
+    Ok(attributes)
+}
+
+fn parse_argument(
+    argument: &[TokenTree],
+    attributes: &mut Attributes,
+) -> Result<()> {
+    let key = match argument {
+        [TokenTree::Ident(key), ..] => key,
+        [token, ..] => {
+            return Err(Error::new("expected an argument", token.span()))
+        }
+        [] => return Ok(()),
+    };
+    let value = match argument {
+        [_] => None,
+        [_, TokenTree::Punct(eq), TokenTree::Literal(value)]
+            if eq.as_char() == '=' =>
+        {
+            Some(value.clone())
+        }
+        _ => {
+            return Err(Error::new(
+                format!("expected `{} = \"...\"`", key),
+                key.span(),
+            ))
+        }
+    };
+
+    match (key.to_string().as_str(), value) {
+        ("lit", Some(value)) => attributes.lit = Some(value),
+        ("sep", Some(value)) => attributes.sep = Some(value),
+        ("skip_ws", None) => attributes.skip_ws = true,
+        ("lit", None) | ("sep", None) => {
+            return Err(Error::new(
+                format!("expected `{} = \"...\"`", key),
+                key.span(),
+            ))
+        }
+        ("skip_ws", Some(_)) => {
+            return Err(Error::new("expected `skip_ws`", key.span()))
+        }
+        (unknown, _) => {
+            return Err(Error::new(
+                format!("unknown argument `{}`", unknown),
+                key.span(),
+            ))
+        }
+    }
+
+    Ok(())
+}
+
+// Splits the tokens on the commas outside of angle brackets.
+fn split(tokens: &[TokenTree]) -> Vec<&[TokenTree]> {
+    let mut parts = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            TokenTree::Punct(punct) if punct.as_char() == '<' => depth += 1,
+            // Not a part of `->`.
+            TokenTree::Punct(punct)
+                if punct.as_char() == '>'
+                    && !matches!(
+                        tokens.get(i.wrapping_sub(1)),
+                        Some(TokenTree::Punct(arrow))
+                            if arrow.as_char() == '-'
+                                && arrow.spacing() == Spacing::Joint
+                    ) =>
+            {
+                depth -= 1
+            }
+            TokenTree::Punct(punct) if punct.as_char() == ',' && depth == 0 => {
+                parts.push(&tokens[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    if start < tokens.len() {
+        parts.push(&tokens[start..]);
+    }
+
+    parts
+}
+
+fn parse_fields(token: Option<&TokenTree>) -> Result<Fields> {
+    let group = match token {
+        Some(TokenTree::Group(group))
+            if group.delimiter() != Delimiter::None =>
+        {
+            group
+        }
+        _ => return Ok(Fields::Unit),
+    };
+    let tokens: Vec<_> = group.stream().into_iter().collect();
+    let named = group.delimiter() == Delimiter::Brace;
+    let mut fields = vec![];
+
+    for tokens in split(&tokens) {
+        let mut i = 0;
+        let attributes = parse_attributes(tokens, &mut i)?;
+        i += visibility_len(tokens, i);
+
+        let name = match (named, tokens.get(i)) {
+            (true, Some(TokenTree::Ident(name))) => {
+                i += 2;
+
+                Some(name.clone())
+            }
+            (true, _) => {
+                return Err(Error::new("expected a field", group.span()))
+            }
+            (false, _) => None,
+        };
+
+        fields.push(Field {
+            attributes,
+            name,
+            ty: tokens[i..].to_vec(),
+        });
+    }
+
+    Ok(match named {
+        true => Fields::Named(fields),
+        false => Fields::Unnamed(fields),
+    })
+}
+
+fn parse_variants(group: &Group) -> Result<Vec<Variant>> {
+    let tokens: Vec<_> = group.stream().into_iter().collect();
+    let mut variants = vec![];
+
+    for tokens in split(&tokens) {
+        let mut i = 0;
+        let attributes = parse_attributes(tokens, &mut i)?;
+        let name = match tokens.get(i) {
+            Some(TokenTree::Ident(name)) => name.clone(),
+            _ => return Err(Error::new("expected a variant", group.span())),
+        };
+        let fields = parse_fields(tokens.get(i + 1))?;
+
+        variants.push(Variant {
+            attributes,
+            name,
+            fields,
+        });
+    }
+
+    Ok(variants)
+}
+
+// Generation.
+
+struct Sequence {
+    // The parsers and the names of their outputs.
+    elements: Vec<(TokenStream, Option<Ident>)>,
+    skip_ws: bool,
+}
+
+impl Sequence {
+    fn new(skip_ws: bool) -> Self {
+        Sequence {
+            elements: vec![],
+            skip_ws,
+        }
+    }
+
+    fn push(
+        &mut self,
+        parser: TokenStream,
+        output: Option<Ident>,
+        skip_ws: bool,
+    ) {
+        if self.skip_ws || skip_ws {
+            self.elements.push((code("::p_arse::lexeme::ws()"), None));
+        }
+        self.elements.push((parser, output));
+    }
+
+    fn push_lit(&mut self, lit: &Option<Literal>, skip_ws: bool) {
+        if let Some(lit) = lit {
+            self.push(TokenTree::Literal(lit.clone()).into(), None, skip_ws);
+        }
+    }
+
+    fn push_fields(&mut self, fields: &Fields) -> Result<()> {
+        let fields = match fields {
+            Fields::Named(fields) | Fields::Unnamed(fields) => fields,
+            Fields::Unit => return Ok(()),
+        };
+
+        for (i, field) in fields.iter().enumerate() {
+            let attributes = &field.attributes;
+            let skip_ws = self.skip_ws || attributes.skip_ws;
+            let is_vec = is_vec(&field.ty);
+
+            if let (Some(sep), false) = (&attributes.sep, is_vec) {
+                return Err(Error::new(
+                    "`sep` is only allowed on `Vec` fields",
+                    sep.span(),
+                ));
+            }
+
+            let parser = match (&attributes.sep, skip_ws && is_vec) {
+                (None, false) => {
+                    let mut parser = code("::p_arse::parse::parse::<");
+                    parser.extend(field.ty.iter().cloned());
+                    parser.extend(code(">()"));
+
+                    parser
+                }
+                (sep, _) => {
+                    let sep = match sep {
+                        Some(sep) => TokenTree::Literal(sep.clone()).into(),
+                        None => code("\"\""),
+                    };
+
+                    call(
+                        "::p_arse::parse::separated",
+                        vec![sep, code(&skip_ws.to_string())],
+                    )
+                }
+            };
+            let output = match &field.name {
+                Some(name) => name.clone(),
+                None => Ident::new(&format!("field_{}", i), Span::mixed_site()),
+            };
+
+            self.push_lit(&attributes.lit, attributes.skip_ws);
+            self.push(parser, Some(output), attributes.skip_ws);
+        }
+
+        Ok(())
+    }
+
+    // Maps the outputs of the parsers with the expression.
+    fn generate(
+        self,
+        expression: TokenStream,
+        span: Span,
+    ) -> Result<TokenStream> {
+        if self.elements.is_empty() {
+            return Err(Error::new(
+                "expected fields or `#[p_arse(lit = \"...\")]`",
+                span,
+            ));
+        }
+
+        let (mut parsers, mut patterns): (Vec<_>, Vec<_>) = self
+            .elements
+            .into_iter()
+            .map(|(parser, output)| {
+                let pattern = match output {
+                    Some(output) => TokenTree::Ident(output).into(),
+                    None => code("_"),
+                };
+
+                (parser, pattern)
+            })
+            .unzip();
+        let (parser, pattern) = match parsers.len() {
+            1 => (parsers.pop().unwrap(), patterns.pop().unwrap()),
+            _ => (tuple(parsers), tuple(patterns)),
+        };
+
+        let mut action = TokenStream::from(punct('|'));
+        action.extend(pattern);
+        action.extend(once(punct('|')));
+        action.extend(expression);
+
+        Ok(call("::p_arse::Parser::map", vec![parser, action]))
+    }
+}
+
+// The expression constructing the value from the outputs of the fields.
+fn constructor(path: TokenStream, fields: &Fields) -> TokenStream {
+    let (delimiter, fields) = match fields {
+        Fields::Named(fields) => (Delimiter::Brace, fields),
+        Fields::Unnamed(fields) => (Delimiter::Parenthesis, fields),
+        Fields::Unit => return path,
+    };
+    let outputs = fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let output = match &field.name {
+                Some(name) => name.clone(),
+                None => Ident::new(&format!("field_{}", i), Span::mixed_site()),
+            };
+
+            TokenTree::Ident(output).into()
+        })
+        .collect();
+
+    let mut constructor = path;
+    constructor.extend(once(TokenTree::Group(Group::new(
+        delimiter,
+        separated(outputs),
+    ))));
+
+    constructor
+}
+
+pub(crate) fn derive(input: TokenStream) -> Result<TokenStream> {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+    let mut i = 0;
+    let attributes = parse_attributes(&tokens, &mut i)?;
+    i += visibility_len(&tokens, i);
+
+    let (kind, name) = match (tokens.get(i), tokens.get(i + 1)) {
+        (Some(TokenTree::Ident(kind)), Some(TokenTree::Ident(name))) => {
+            (kind.to_string(), name.clone())
+        }
+        _ => {
+            return Err(Error::new(
+                "expected a struct or an enum",
+                Span::call_site(),
+            ))
+        }
+    };
+    i += 2;
+
+    if is_punct(tokens.get(i), '<') {
+        return Err(Error::new(
+            "generic types are not supported",
+            tokens[i].span(),
+        ));
+    }
+
+    let parser = match kind.as_str() {
+        "struct" => {
+            let fields = parse_fields(tokens.get(i))?;
+            let mut sequence = Sequence::new(attributes.skip_ws);
+            sequence.push_lit(&attributes.lit, false);
+            sequence.push_fields(&fields)?;
+
+            sequence
+                .generate(constructor(code("Self"), &fields), name.span())?
+        }
+        "enum" => {
+            let variants = match tokens.get(i) {
+                Some(TokenTree::Group(group)) => parse_variants(group)?,
+                _ => return Err(Error::new("expected variants", name.span())),
+            };
+            let choice = variants
+                .iter()
+                .map(|variant| {
+                    let skip_ws =
+                        attributes.skip_ws || variant.attributes.skip_ws;
+                    let mut sequence = Sequence::new(skip_ws);
+                    sequence.push_lit(&variant.attributes.lit, false);
+                    sequence.push_fields(&variant.fields)?;
+
+                    let mut path = code("Self::");
+                    path.extend(once(TokenTree::Ident(variant.name.clone())));
+                    let constructor = constructor(path, &variant.fields);
+
+                    sequence.generate(constructor, variant.name.span())
+                })
+                .collect::<Result<Vec<_>>>()?
+                .into_iter()
+                .reduce(|choice, variant| {
+                    call("::p_arse::Parser::or", vec![choice, variant])
+                })
+                .ok_or_else(|| Error::new("expected variants", name.span()))?;
+
+            match attributes.lit {
+                Some(lit) => {
+                    let value = Ident::new("value", Span::mixed_site());
+                    let mut sequence = Sequence::new(attributes.skip_ws);
+                    sequence.push_lit(&Some(lit), false);
+                    sequence.elements.push((choice, Some(value.clone())));
+
+                    sequence
+                        .generate(TokenTree::Ident(value).into(), name.span())?
+                }
+                None => choice,
+            }
+        }
+        _ => {
+            return Err(Error::new(
+                "expected a struct or an enum",
+                tokens[i - 2].span(),
+            ))
+        }
+    };
+
+    let mut reference = TokenStream::from(punct('&'));
+    reference.extend(once(TokenTree::Group(Group::new(
+        Delimiter::Parenthesis,
+        parser,
+    ))));
+    let body = call("::p_arse::Parser::p_arse", vec![reference, code("tail")]);
+
+    let mut implementation = code("impl ::p_arse::Parse for");
+    implementation.extend(once(TokenTree::Ident(name)));
+    let mut function =
+        code("fn p_arse<'a>(tail: &'a str) -> ::p_arse::Result<'a, Self>");
+    function.extend(once(TokenTree::Group(Group::new(Delimiter::Brace, body))));
+    implementation.extend(once(TokenTree::Group(Group::new(
+        Delimiter::Brace,
+        function,
+    ))));
+
+    Ok(implementation)
+}
+
+// Whether the type is a `Vec`, possibly with a path, e.g. `std::vec::Vec<T>`.
+// Aliases of `Vec` are not recognized.
+fn is_vec(ty: &[TokenTree]) -> bool {
+    let end = ty
+        .iter()
+        .position(|token| is_punct(Some(token), '<'))
+        .unwrap_or(ty.len());
+    let path = &ty[..end];
+    let is_path = path.iter().all(|token| match token {
+        TokenTree::Ident(_) => true,
+        TokenTree::Punct(punct) => punct.as_char() == ':',
+        _ => false,
+    });
+
+    is_path && is_keyword(path.last(), "Vec")
+}
@@ -0,0 +1,715 @@
+//! Macros for [`p-arse`](https://docs.rs/p-arse): [`peg!`], turning PEG rules
+//! into parsers, and [`derive(Parse)`](derive@Parse), turning AST types into
+//! parsers.
+
+mod derive;
+
+use std::iter::{once, FromIterator};
+
+use proc_macro::{
+    Delimiter,
+    Group,
+    Ident,
+    Literal,
+    Punct,
+    Spacing,
+    Span,
+    TokenStream,
+    TokenTree,
+};
+
+/// Generates a function for each PEG rule, and a `p_arse::function::Rule`
+/// parser for it as a constant named after the rule in upper case with a
+/// `_RULE` suffix, e.g. `NUMBER_RULE` for `number`. The rules can refer to each
+/// other recursively. Unlike the functions, the constants can be described,
+/// exported or linted as a grammar, see `p_arse::describe`.
+///
+/// | Syntax               | Meaning                                          |
+/// |----------------------|--------------------------------------------------|
+/// | `name <- e`          | rule outputting `()`                             |
+/// | `name -> T <- e`     | rule outputting `T`                              |
+/// | `pub name <- e`      | public rule                                      |
+/// | `"abc"`, `'a'`       | literal                                          |
+/// | `['a'-'z' '_']`      | character class                                  |
+/// | `.`                  | any character                                    |
+/// | `rule`               | another rule                                     |
+/// | `(e)`                | grouping                                         |
+/// | `e?`, `e*`, `e+`     | optional, zero or more, one or more              |
+/// | `&e`, `!e`           | positive and negative lookahead                  |
+/// | `$e`                 | the input matched by `e`, as a `String`          |
+/// | `e1 e2`              | sequence                                         |
+/// | `e1 / e2`            | prioritized choice                               |
+/// | `x:e1 e2 => { .. }`  | action on the labeled outputs of a sequence      |
+///
+/// The output of a sequence is a tuple of the outputs of its elements. The
+/// outputs of unlabeled elements of a sequence with an action, and of the
+/// whole expression of a rule without a type, are ignored, so in such places
+/// the alternatives of a choice don't need to have the same output.
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::Parser;
+/// use p_arse_macros::peg;
+///
+/// peg! {
+///     header -> String <- ">" text:$(!"\n" .)+ "\n" => { text }
+///     sum -> u32 <- first:number rest:("+" n:number => { n })* !. => {
+///         first + rest.iter().sum::<u32>()
+///     }
+///     number -> u32 <- digits:$['0'-'9']+ => { digits.parse().unwrap() }
+///     list <- "(" (list / ['a'-'z'])* ")"
+/// }
+///
+/// assert_eq!(header(">MCHU\n").unwrap().0, "MCHU");
+/// assert_eq!(sum("1+20+300").unwrap().0, 321);
+/// assert!(list("(a(b)(c(d)))").is_ok());
+/// assert!(LIST_RULE.p_arse("(a(b)").is_err());
+/// assert_eq!(
+///     p_arse::export::export(&SUM_RULE, p_arse::export::Notation::Peg),
+///     "sum    <- number ('+' number)* !.\nnumber <- [0-9]+\n",
+/// );
+/// ```
+#[proc_macro]
+pub fn peg(input: TokenStream) -> TokenStream {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+
+    match parse_rules(&tokens).and_then(check_constants) {
+        Ok(rules) => rules.iter().map(Rule::generate).collect(),
+        Err(error) => error.into_compile_error(),
+    }
+}
+
+/// Implements `p_arse::Parse` for a struct or an enum without generics. See
+/// the `p_arse::parse` module for the attributes.
+#[proc_macro_derive(Parse, attributes(p_arse))]
+pub fn derive_parse(input: TokenStream) -> TokenStream {
+    match derive::derive(input) {
+        Ok(implementation) => implementation,
+        Err(error) => error.into_compile_error(),
+    }
+}
+
+struct Error {
+    message: String,
+    span: Span,
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    fn new<S: Into<String>>(message: S, span: Span) -> Self {
+        Error {
+            message: message.into(),
+            span,
+        }
+    }
+
+    fn into_compile_error(self) -> TokenStream {
+        let message = TokenTree::Literal(Literal::string(&self.message));
+        let mut bang = Punct::new('!', Spacing::Alone);
+        bang.set_span(self.span);
+        let mut group = Group::new(Delimiter::Parenthesis, message.into());
+        group.set_span(self.span);
+
+        TokenStream::from_iter(vec![
+            TokenTree::Ident(Ident::new("compile_error", self.span)),
+            TokenTree::Punct(bang),
+            TokenTree::Group(group),
+            TokenTree::Punct(Punct::new(';', Spacing::Alone)),
+        ])
+    }
+}
+
+struct Rule {
+    visibility: Vec<TokenTree>,
+    name: Ident,
+    output: Option<Vec<TokenTree>>,
+    expr: Expr,
+}
+
+struct Element {
+    label: Option<Ident>,
+    expr: Expr,
+}
+
+enum Expr {
+    Choice(Vec<Expr>),
+    Sequence(Vec<Element>),
+    Action(Vec<Element>, Group),
+    And(Box<Expr>),
+    Not(Box<Expr>),
+    Optional(Box<Expr>),
+    ZeroOrMore(Box<Expr>),
+    OneOrMore(Box<Expr>),
+    Capture(Box<Expr>),
+    Literal(Literal),
+    Class(Vec<(Literal, Option<Literal>)>),
+    Any,
+    Rule(Ident),
+}
+
+// Parsing.
+
+fn is_punct(token: Option<&TokenTree>, c: char) -> bool {
+    matches!(token, Some(TokenTree::Punct(punct)) if punct.as_char() == c)
+}
+
+// Whether the tokens at `i` form a two-character operator, e.g. `<-`.
+fn is_operator(tokens: &[TokenTree], i: usize, operator: &str) -> bool {
+    let mut chars = operator.chars();
+    let (first, second) = (chars.next().unwrap(), chars.next().unwrap());
+
+    matches!(
+        tokens.get(i),
+        Some(TokenTree::Punct(punct))
+            if punct.as_char() == first && punct.spacing() == Spacing::Joint
+    ) && is_punct(tokens.get(i + 1), second)
+}
+
+fn is_keyword(token: Option<&TokenTree>, keyword: &str) -> bool {
+    matches!(token, Some(TokenTree::Ident(ident)) if ident.to_string() == keyword)
+}
+
+// The length of the visibility, e.g. `pub(crate)`, at `i`.
+fn visibility_len(tokens: &[TokenTree], i: usize) -> usize {
+    match (is_keyword(tokens.get(i), "pub"), tokens.get(i + 1)) {
+        (true, Some(TokenTree::Group(group)))
+            if group.delimiter() == Delimiter::Parenthesis =>
+        {
+            2
+        }
+        (true, _) => 1,
+        (false, _) => 0,
+    }
+}
+
+fn is_rule_start(tokens: &[TokenTree], i: usize) -> bool {
+    let i = i + visibility_len(tokens, i);
+
+    matches!(tokens.get(i), Some(TokenTree::Ident(_)))
+        && (is_operator(tokens, i + 1, "<-")
+            || is_operator(tokens, i + 1, "->"))
+}
+
+fn parse_rules(tokens: &[TokenTree]) -> Result<Vec<Rule>> {
+    let mut rules = vec![];
+    let mut i = 0;
+
+    while i < tokens.len() {
+        if !is_rule_start(tokens, i) {
+            return Err(Error::new(
+                "expected a rule, e.g. `name <- \"a\"`",
+                tokens[i].span(),
+            ));
+        }
+
+        let visibility = tokens[i..i + visibility_len(tokens, i)].to_vec();
+        i += visibility.len();
+
+        let name = match &tokens[i] {
+            TokenTree::Ident(name) => name.clone(),
+            _ => unreachable!(),
+        };
+        i += 1;
+
+        let output = if is_operator(tokens, i, "->") {
+            let start = i + 2;
+            i = start;
+
+            while !is_operator(tokens, i, "<-") {
+                if i >= tokens.len() {
+                    return Err(Error::new("expected `<-`", name.span()));
+                }
+                i += 1;
+            }
+
+            Some(tokens[start..i].to_vec())
+        } else {
+            None
+        };
+        i += 2;
+
+        let start = i;
+        while i < tokens.len() && !is_rule_start(tokens, i) {
+            i += 1;
+        }
+
+        let mut parser = ExprParser {
+            tokens: &tokens[start..i],
+            position: 0,
+            span: name.span(),
+        };
+        let expr = parser.choice()?;
+        parser.expect_end()?;
+
+        rules.push(Rule {
+            visibility,
+            name,
+            output,
+            expr,
+        });
+    }
+
+    Ok(rules)
+}
+
+// Checks that the constants of the rules don't collide with the rules or
+// with each other, e.g. the constant of `list` with the rule `LIST_RULE`.
+fn check_constants(rules: Vec<Rule>) -> Result<Vec<Rule>> {
+    for (i, rule) in rules.iter().enumerate() {
+        let name = constant(&rule.name).to_string();
+        let collision = rules.iter().enumerate().find(|(j, other)| {
+            other.name.to_string() == name
+                || (*j < i && constant(&other.name).to_string() == name)
+        });
+
+        if let Some((_, other)) = collision {
+            return Err(Error::new(
+                format!(
+                    "the constant `{}` of rule `{}` collides with rule `{}`",
+                    name, rule.name, other.name,
+                ),
+                rule.name.span(),
+            ));
+        }
+    }
+
+    Ok(rules)
+}
+
+struct ExprParser<'t> {
+    tokens: &'t [TokenTree],
+    position: usize,
+    // Used for errors at the end of the tokens.
+    span: Span,
+}
+
+impl ExprParser<'_> {
+    fn peek(&self) -> Option<&TokenTree> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&TokenTree> {
+        self.position += 1;
+
+        self.tokens.get(self.position - 1)
+    }
+
+    fn eat_punct(&mut self, c: char) -> bool {
+        let eaten = is_punct(self.peek(), c);
+        if eaten {
+            self.position += 1;
+        }
+
+        eaten
+    }
+
+    fn error<S: Into<String>>(&self, message: S) -> Error {
+        let span = self.peek().map_or(self.span, TokenTree::span);
+
+        Error::new(message, span)
+    }
+
+    fn expect_end(&self) -> Result<()> {
+        match self.peek() {
+            Some(_) => Err(self.error("unexpected token")),
+            None => Ok(()),
+        }
+    }
+
+    fn choice(&mut self) -> Result<Expr> {
+        let mut alternatives = vec![self.alternative()?];
+
+        while self.eat_punct('/') {
+            alternatives.push(self.alternative()?);
+        }
+
+        Ok(match alternatives.len() {
+            1 => alternatives.pop().unwrap(),
+            _ => Expr::Choice(alternatives),
+        })
+    }
+
+    fn alternative(&mut self) -> Result<Expr> {
+        let mut elements = vec![];
+
+        while self.peek().is_some()
+            && !is_punct(self.peek(), '/')
+            && !is_operator(self.tokens, self.position, "=>")
+        {
+            elements.push(self.element()?);
+        }
+
+        if elements.is_empty() {
+            return Err(self.error("expected an expression"));
+        }
+
+        if is_operator(self.tokens, self.position, "=>") {
+            self.position += 2;
+
+            return match self.next() {
+                Some(TokenTree::Group(block))
+                    if block.delimiter() == Delimiter::Brace =>
+                {
+                    Ok(Expr::Action(elements, block.clone()))
+                }
+                _ => {
+                    self.position -= 1;
+
+                    Err(self.error("expected a block after `=>`"))
+                }
+            };
+        }
+
+        Ok(match elements.len() {
+            1 => elements.pop().unwrap().expr,
+            _ => Expr::Sequence(elements),
+        })
+    }
+
+    fn element(&mut self) -> Result<Element> {
+        let label = match (self.peek(), self.tokens.get(self.position + 1)) {
+            (Some(TokenTree::Ident(label)), Some(TokenTree::Punct(colon)))
+                if colon.as_char() == ':'
+                    && !is_operator(self.tokens, self.position + 1, "::") =>
+            {
+                let label = label.clone();
+                self.position += 2;
+
+                Some(label)
+            }
+            _ => None,
+        };
+        let expr = self.prefix()?;
+
+        Ok(Element { label, expr })
+    }
+
+    fn prefix(&mut self) -> Result<Expr> {
+        if self.eat_punct('&') {
+            Ok(Expr::And(Box::new(self.prefix()?)))
+        } else if self.eat_punct('!') {
+            Ok(Expr::Not(Box::new(self.prefix()?)))
+        } else if self.eat_punct('$') {
+            Ok(Expr::Capture(Box::new(self.prefix()?)))
+        } else {
+            self.suffix()
+        }
+    }
+
+    fn suffix(&mut self) -> Result<Expr> {
+        let mut expr = self.primary()?;
+
+        loop {
+            expr = if self.eat_punct('?') {
+                Expr::Optional(Box::new(expr))
+            } else if self.eat_punct('*') {
+                Expr::ZeroOrMore(Box::new(expr))
+            } else if self.eat_punct('+') {
+                Expr::OneOrMore(Box::new(expr))
+            } else {
+                return Ok(expr);
+            };
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        let token = match self.peek() {
+            Some(token) => token.clone(),
+            None => return Err(self.error("expected an expression")),
+        };
+
+        let expr = match &token {
+            TokenTree::Ident(rule) => Expr::Rule(rule.clone()),
+            TokenTree::Literal(literal) => {
+                let text = literal.to_string();
+
+                if text.starts_with(&['"', '\'', 'r'][..]) {
+                    Expr::Literal(literal.clone())
+                } else {
+                    return Err(self.error("expected a string or a char"));
+                }
+            }
+            TokenTree::Punct(dot) if dot.as_char() == '.' => Expr::Any,
+            TokenTree::Group(group)
+                if group.delimiter() == Delimiter::Parenthesis =>
+            {
+                let tokens: Vec<_> = group.stream().into_iter().collect();
+                let mut parser = ExprParser {
+                    tokens: &tokens,
+                    position: 0,
+                    span: group.span(),
+                };
+                let expr = parser.choice()?;
+                parser.expect_end()?;
+
+                expr
+            }
+            TokenTree::Group(group)
+                if group.delimiter() == Delimiter::Bracket =>
+            {
+                let tokens: Vec<_> = group.stream().into_iter().collect();
+                let mut parser = ExprParser {
+                    tokens: &tokens,
+                    position: 0,
+                    span: group.span(),
+                };
+
+                parser.class()?
+            }
+            _ => return Err(self.error("expected an expression")),
+        };
+        self.position += 1;
+
+        Ok(expr)
+    }
+
+    fn class(&mut self) -> Result<Expr> {
+        let mut ranges = vec![];
+
+        while self.peek().is_some() {
+            let from = self.char()?;
+            let to = match self.eat_punct('-') {
+                true => Some(self.char()?),
+                false => None,
+            };
+
+            ranges.push((from, to));
+        }
+
+        if ranges.is_empty() {
+            return Err(self.error("expected a char"));
+        }
+
+        Ok(Expr::Class(ranges))
+    }
+
+    fn char(&mut self) -> Result<Literal> {
+        match self.peek() {
+            Some(TokenTree::Literal(literal))
+                if literal.to_string().starts_with('\'') =>
+            {
+                let literal = literal.clone();
+                self.position += 1;
+
+                Ok(literal)
+            }
+            _ => Err(self.error("expected a char")),
+        }
+    }
+}
+
+// Generation.
+
+fn code(code: &str) -> TokenStream {
+    code.parse().unwrap()
+}
+
+fn punct(c: char) -> TokenTree {
+    TokenTree::Punct(Punct::new(c, Spacing::Alone))
+}
+
+fn separated(items: Vec<TokenStream>) -> TokenStream {
+    let mut separated = TokenStream::new();
+
+    for (i, item) in items.into_iter().enumerate() {
+        if i > 0 {
+            separated.extend(once(punct(',')));
+        }
+        separated.extend(item);
+    }
+
+    separated
+}
+
+fn call(function: &str, arguments: Vec<TokenStream>) -> TokenStream {
+    let mut call = code(function);
+    let arguments = Group::new(Delimiter::Parenthesis, separated(arguments));
+    call.extend(once(TokenTree::Group(arguments)));
+
+    call
+}
+
+// Tuples longer than 6 are nested, since parsers are implemented for tuples
+// of up to 6 parsers.
+fn tuple(mut items: Vec<TokenStream>) -> TokenStream {
+    if items.len() > 6 {
+        let rest = items.split_off(5);
+        items.push(tuple(rest));
+    }
+
+    TokenTree::Group(Group::new(Delimiter::Parenthesis, separated(items)))
+        .into()
+}
+
+impl Expr {
+    // Generates the parser. If its output is not `used`, the alternatives of
+    // choices are ignored, so they don't need to have the same output.
+    fn generate(&self, used: bool) -> TokenStream {
+        match self {
+            Expr::Choice(alternatives) => alternatives
+                .iter()
+                .map(|alternative| match used {
+                    true => alternative.generate(used),
+                    false => call(
+                        "::p_arse::Parser::ignore",
+                        vec![alternative.generate(used)],
+                    ),
+                })
+                .reduce(|choice, alternative| {
+                    call("::p_arse::Parser::or", vec![choice, alternative])
+                })
+                .unwrap(),
+            Expr::Sequence(elements) => tuple(
+                elements
+                    .iter()
+                    .map(|element| element.expr.generate(used))
+                    .collect(),
+            ),
+            Expr::Action(elements, block) => {
+                let mut parsers: Vec<_> = elements
+                    .iter()
+                    .map(|element| {
+                        element.expr.generate(element.label.is_some())
+                    })
+                    .collect();
+                let mut patterns: Vec<_> = elements
+                    .iter()
+                    .map(|element| match &element.label {
+                        Some(label) => TokenTree::Ident(label.clone()).into(),
+                        None => code("_"),
+                    })
+                    .collect();
+                let (parser, pattern) = match parsers.len() {
+                    1 => (parsers.pop().unwrap(), patterns.pop().unwrap()),
+                    _ => (tuple(parsers), tuple(patterns)),
+                };
+                let mut action = TokenStream::from(punct('|'));
+                action.extend(pattern);
+                action
+                    .extend(vec![punct('|'), TokenTree::Group(block.clone())]);
+
+                call("::p_arse::Parser::map", vec![parser, action])
+            }
+            Expr::And(expr) => {
+                call("::p_arse::Parser::ahead", vec![expr.generate(false)])
+            }
+            Expr::Not(expr) => {
+                call("::p_arse::Parser::not_ahead", vec![expr.generate(false)])
+            }
+            Expr::Optional(expr) => {
+                call("::p_arse::Parser::opt", vec![expr.generate(used)])
+            }
+            Expr::ZeroOrMore(expr) => {
+                call("::p_arse::Parser::zore", vec![expr.generate(used)])
+            }
+            Expr::OneOrMore(expr) => {
+                call("::p_arse::Parser::more", vec![expr.generate(used)])
+            }
+            Expr::Capture(expr) => call(
+                "::p_arse::Parser::maps",
+                vec![
+                    expr.generate(false),
+                    code("|s: &str| ::std::string::ToString::to_string(s)"),
+                ],
+            ),
+            Expr::Literal(literal) => {
+                TokenTree::Literal(literal.clone()).into()
+            }
+            Expr::Class(ranges) => ranges
+                .iter()
+                .map(|(from, to)| {
+                    let from =
+                        TokenStream::from(TokenTree::Literal(from.clone()));
+
+                    match to {
+                        Some(to) => call(
+                            "::p_arse::CharExt::to",
+                            vec![from, TokenTree::Literal(to.clone()).into()],
+                        ),
+                        None => from,
+                    }
+                })
+                .reduce(|class, range| {
+                    call("::p_arse::Parser::or", vec![class, range])
+                })
+                .unwrap(),
+            Expr::Any => code("::p_arse::any()"),
+            Expr::Rule(rule) => TokenTree::Ident(constant(rule)).into(),
+        }
+    }
+}
+
+// The constant of the rule, e.g. `LIST_RULE` for `list`.
+fn constant(rule: &Ident) -> Ident {
+    let name = rule.to_string();
+    let name = name.trim_start_matches("r#").to_uppercase();
+
+    Ident::new(&format!("{}_RULE", name), rule.span())
+}
+
+impl Rule {
+    fn generate(&self) -> TokenStream {
+        // Doesn't collide with the names of the rules.
+        let input = TokenTree::Ident(Ident::new("input", Span::mixed_site()));
+        let name = Literal::string(&self.name.to_string());
+        let constant = constant(&self.name);
+
+        let (output, parser): (TokenStream, _) = match &self.output {
+            Some(output) => {
+                (output.iter().cloned().collect(), self.expr.generate(true))
+            }
+            None => (
+                code("()"),
+                call(
+                    "::p_arse::Parser::ignore",
+                    vec![self.expr.generate(false)],
+                ),
+            ),
+        };
+
+        let mut parameters = TokenStream::from(input.clone());
+        parameters.extend(code(": &'tail str"));
+
+        let mut reference = TokenStream::from(punct('&'));
+        reference.extend(once(TokenTree::Ident(constant.clone())));
+        let body =
+            call("::p_arse::Parser::p_arse", vec![reference, input.into()]);
+
+        // The body of the rule ignores the rule itself, the rules refer to
+        // each other through their constants.
+        let mut closure = code("&|_|");
+        closure.extend(parser);
+
+        let mut rule = self.visibility.iter().cloned().collect::<TokenStream>();
+        rule.extend(code("const"));
+        rule.extend(once(TokenTree::Ident(constant)));
+        rule.extend(code(": ::p_arse::function::Rule<'static,"));
+        rule.extend(output.clone());
+        rule.extend(code("> ="));
+        rule.extend(call(
+            "::p_arse::rule",
+            vec![TokenTree::Literal(name).into(), closure],
+        ));
+        rule.extend(once(punct(';')));
+
+        let mut function = code("#[allow(non_snake_case)]");
+        function.extend(self.visibility.iter().cloned());
+        function.extend(code("fn"));
+        function.extend(once(TokenTree::Ident(self.name.clone())));
+        function.extend(code("<'tail>"));
+        function.extend(once(TokenTree::Group(Group::new(
+            Delimiter::Parenthesis,
+            parameters,
+        ))));
+        function.extend(code("-> ::p_arse::Result<'tail,"));
+        function.extend(output);
+        function.extend(code(">"));
+        function
+            .extend(once(TokenTree::Group(Group::new(Delimiter::Brace, body))));
+        function.extend(rule);
+
+        function
+    }
+}
@@ -0,0 +1,115 @@
+use p_arse::{parse, Parse, Parser, Result};
+
+#[derive(Debug, PartialEq)]
+struct Name(String);
+
+impl Parse for Name {
+    fn p_arse<'a>(tail: &'a str) -> Result<'a, Self> {
+        let name = p_arse::take_while1(|c: char| c.is_ascii_lowercase());
+
+        name.maps(|s| Name(s.to_string())).p_arse(tail)
+    }
+}
+
+// An INI-like section, e.g. `[server] port = 80 hosts = [a, b]`.
+#[derive(Parse, Debug, PartialEq)]
+#[p_arse(lit = "[", skip_ws)]
+struct Section {
+    name: Name,
+    #[p_arse(lit = "]")]
+    entries: Vec<Entry>,
+}
+
+#[derive(Parse, Debug, PartialEq)]
+#[p_arse(skip_ws)]
+struct Entry {
+    key: Name,
+    #[p_arse(lit = "=")]
+    value: Value,
+}
+
+#[derive(Parse, Debug, PartialEq)]
+enum Value {
+    #[p_arse(lit = "true")]
+    True,
+    #[p_arse(lit = "false")]
+    False,
+    Number(i32),
+    #[p_arse(skip_ws)]
+    List(
+        #[p_arse(lit = "[", sep = ",")] Vec<Name>,
+        #[p_arse(lit = "]")] (),
+    ),
+}
+
+#[test]
+fn test_derive() {
+    let input = "[server]\n  port = -80\n  hosts = [ a , b ]\n  tls = true";
+    let (section, tail) = parse::<Section>().p_arse(input).unwrap();
+    assert_eq!(tail, "");
+    assert_eq!(section.name, Name("server".into()));
+    assert_eq!(
+        section
+            .entries
+            .iter()
+            .map(|entry| &entry.value)
+            .collect::<Vec<_>>(),
+        vec![
+            &Value::Number(-80),
+            &Value::List(vec![Name("a".into()), Name("b".into())], ()),
+            &Value::True,
+        ],
+    );
+
+    assert!(parse::<Section>().p_arse("server]").is_err());
+    assert!(parse::<Value>().p_arse("[a, b").is_err());
+}
+
+#[derive(Parse, Debug, PartialEq)]
+#[p_arse(lit = "#")]
+enum Color {
+    #[p_arse(lit = "rgb")]
+    Rgb(u8, #[p_arse(lit = ",")] u8, #[p_arse(lit = ",")] u8),
+    #[p_arse(lit = "gray")]
+    Gray { level: u8 },
+}
+
+#[derive(Parse, Debug, PartialEq)]
+struct Digits(char, char, char, char, char, char, char, char);
+
+#[test]
+fn test_derive_shapes() {
+    assert_eq!(
+        parse::<Color>().p_arse("#rgb1,2,3").unwrap().0,
+        Color::Rgb(1, 2, 3)
+    );
+    assert_eq!(
+        parse::<Color>().p_arse("#gray255").unwrap().0,
+        Color::Gray { level: 255 },
+    );
+    assert!(parse::<Color>().p_arse("#gray256").is_err());
+    assert!(parse::<Color>().p_arse("rgb1,2,3").is_err());
+
+    let (digits, tail) = parse::<Digits>().p_arse("123456789").unwrap();
+    assert_eq!(digits, Digits('1', '2', '3', '4', '5', '6', '7', '8'));
+    assert_eq!(tail, "9");
+}
+
+#[derive(Parse, Debug, PartialEq)]
+#[p_arse(skip_ws)]
+struct Names {
+    #[p_arse(lit = "(")]
+    names: std::vec::Vec<Name>,
+    #[p_arse(lit = ")")]
+    close: (),
+}
+
+#[test]
+fn test_derive_vec_path() {
+    let (names, tail) = parse::<Names>().p_arse("( a  b c )").unwrap();
+    assert_eq!(tail, "");
+    assert_eq!(
+        names.names,
+        vec![Name("a".into()), Name("b".into()), Name("c".into())],
+    );
+}
@@ -0,0 +1,103 @@
+use p_arse::{
+    export::{export, Notation},
+    fun,
+    Parser,
+};
+use p_arse_macros::peg;
+
+peg! {
+    // Arithmetic expressions.
+    expr -> f64 <- spacing value:sum !. => { value }
+    sum -> f64 <- first:product rest:(op:$("+" / "-") spacing p:product => {
+        (op, p)
+    })* => {
+        rest.into_iter().fold(first, |sum, (op, p)| match op.as_str() {
+            "+" => sum + p,
+            _ => sum - p,
+        })
+    }
+    product -> f64 <- first:value rest:(['*' '/'] spacing value)* => {
+        rest.into_iter().fold(first, |product, (op, _, v)| match op {
+            '*' => product * v,
+            _ => product / v,
+        })
+    }
+    value -> f64 <- number / '(' spacing s:sum ')' spacing => { s }
+    number -> f64 <- digits:$(['0'-'9']+ ("." ['0'-'9']+)?) spacing => {
+        digits.parse().unwrap()
+    }
+    spacing <- [' ' '\t' '\n']*
+}
+
+#[test]
+fn test_recursion_and_actions() {
+    assert_eq!(expr(" 1 + 2.5 * (3 - 4)\n").unwrap(), (-1.5, ""));
+    assert_eq!(expr("8 / 2 / 2").unwrap().0, 2.0);
+    assert!(expr("1 + * 2").is_err());
+    assert!(expr("(1").is_err());
+    assert_eq!(fun(&number).p_arse("42x").unwrap(), (42.0, "x"));
+}
+
+mod visibility {
+    p_arse_macros::peg! {
+        pub(crate) word -> String <- $['a'-'z']+
+        pub Quoted <- '"' (!'"' .)* '"'
+    }
+}
+
+#[test]
+fn test_visibility() {
+    assert_eq!(visibility::word("abc def").unwrap(), ("abc".into(), " def"));
+    assert_eq!(visibility::Quoted(r#""a b"c"#).unwrap(), ((), "c"));
+}
+
+peg! {
+    // Unused choices don't need the same output.
+    keyword <- ("if" / 'x' / ['0'-'9']+) !['a'-'z']
+    // Sequences longer than 6 are nested.
+    seven -> (char, char, char, char, char, (char, char)) <-
+        'a' 'b' 'c' 'd' 'e' 'f' 'g'
+    // Labels are only needed by actions.
+    pair -> (String, String) <- a:$. "=" b:$. &";" => { (a, b) }
+    // `input` is the name of the generated parameter as well.
+    input <- "in" put
+    put <- "put"
+}
+
+#[test]
+fn test_syntax() {
+    assert!(keyword("if").is_ok());
+    assert!(keyword("123 ").is_ok());
+    assert!(keyword("ifs").is_err());
+
+    let seven = seven("abcdefgh").unwrap();
+    assert_eq!(seven, (('a', 'b', 'c', 'd', 'e', ('f', 'g')), "h"));
+
+    let ((a, b), tail) = pair("x=y;").unwrap();
+    assert_eq!((a.as_str(), b.as_str(), tail), ("x", "y", ";"));
+    assert!(pair("x=y").is_err());
+
+    assert!(input("input").is_ok());
+}
+
+#[test]
+fn test_errors() {
+    let error = fun(&number).p_arse("x").unwrap_err();
+    assert_eq!(error.tail(), "x");
+}
+
+#[test]
+fn test_rules() {
+    assert_eq!(VALUE_RULE.p_arse("(1 + 2) * 3").unwrap(), (3.0, "* 3"));
+    assert_eq!(
+        export(&EXPR_RULE, Notation::Peg),
+        concat!(
+            "expr    <- spacing sum !.\n",
+            "spacing <- (' ' / '\\t' / '\\n')*\n",
+            "sum     <- product (('+' / '-') spacing product)*\n",
+            "product <- value (('*' / '/') spacing value)*\n",
+            "value   <- number / '(' spacing sum ')' spacing\n",
+            "number  <- [0-9]+ ('.' [0-9]+)? spacing\n",
+        ),
+    );
+}
@@ -0,0 +1,301 @@
+//! Case-insensitive literals.
+
+use crate::{
+    describe::GrammarNode,
+    literal::CharRange,
+    search::FirstSet,
+    sequence::Leaf,
+    Error,
+    Parser,
+    Result,
+};
+
+/// How characters are compared by [`NoCase`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Folding {
+    /// Only ASCII letters are compared case-insensitively.
+    Ascii,
+    /// Characters are compared using the simple (one to one) Unicode case
+    /// mappings, e.g. `'ſ'` matches `'s'` and `'Σ'` matches `'ς'`, but
+    /// `'ß'` doesn't match `"SS"`.
+    Unicode,
+}
+
+impl Folding {
+    // Maps all the case variants of a character to one of them.
+    fn fold(self, c: char) -> char {
+        match self {
+            Folding::Ascii => c.to_ascii_lowercase(),
+            Folding::Unicode => {
+                let upper = single(c.to_uppercase()).unwrap_or(c);
+
+                single(upper.to_lowercase()).unwrap_or(upper)
+            }
+        }
+    }
+
+    // The character and its case variants.
+    fn variants(self, c: char) -> [char; 4] {
+        match self {
+            Folding::Ascii => {
+                [c, c.to_ascii_lowercase(), c.to_ascii_uppercase(), c]
+            }
+            Folding::Unicode => [
+                c,
+                single(c.to_lowercase()).unwrap_or(c),
+                single(c.to_uppercase()).unwrap_or(c),
+                self.fold(c),
+            ],
+        }
+    }
+
+    // The ASCII characters accepted by `matches` and, for the Unicode folding,
+    // all the non-ASCII ones, since other characters may fold to the same
+    // character, e.g. the Kelvin sign to `'k'`.
+    fn first_set<F>(
+        self,
+        non_ascii: Option<(char, char)>,
+        matches: F,
+    ) -> FirstSet
+    where
+        F: Fn(char) -> bool,
+    {
+        let ascii = (0..128u8)
+            .map(char::from)
+            .filter(|&c| matches(c))
+            .map(|c| (c, c));
+        let non_ascii = match self {
+            Folding::Ascii => non_ascii,
+            Folding::Unicode => Some(('\u{80}', char::MAX)),
+        };
+
+        FirstSet::from_ranges(ascii.chain(non_ascii))
+    }
+}
+
+fn single<I: Iterator<Item = char>>(mut chars: I) -> Option<char> {
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+/// A literal which can be matched case-insensitively, i.e. [`&str`](str),
+/// [`char`] or [`CharRange`].
+pub trait Caseless: Copy {
+    /// The matched input.
+    type Output;
+
+    fn p_arse_caseless<'a>(
+        &self,
+        folding: Folding,
+        tail: &'a str,
+    ) -> Result<'a, Self::Output>;
+
+    fn first_set_caseless(&self, folding: Folding) -> Option<FirstSet>;
+
+    fn describe_caseless(&self) -> GrammarNode;
+}
+
+impl Caseless for &str {
+    type Output = String;
+
+    fn p_arse_caseless<'a>(
+        &self,
+        folding: Folding,
+        tail: &'a str,
+    ) -> Result<'a, Self::Output> {
+        let mut chars = tail.chars();
+
+        for expected in self.chars() {
+            match chars.next() {
+                Some(c) if folding.fold(c) == folding.fold(expected) => {}
+                _ => {
+                    return Err(Error::expecting(
+                        format!("string '{}' (case-insensitive)", self),
+                        tail,
+                    ))
+                }
+            }
+        }
+
+        let rest = chars.as_str();
+        let matched = &tail[..tail.len() - rest.len()];
+
+        Ok((matched.to_string(), rest))
+    }
+
+    fn first_set_caseless(&self, folding: Folding) -> Option<FirstSet> {
+        self.chars()
+            .next()
+            .map(|first| first.first_set_caseless(folding).unwrap())
+    }
+
+    fn describe_caseless(&self) -> GrammarNode {
+        self.describe()
+    }
+}
+
+impl Caseless for char {
+    type Output = char;
+
+    fn p_arse_caseless<'a>(
+        &self,
+        folding: Folding,
+        tail: &'a str,
+    ) -> Result<'a, Self::Output> {
+        let mut chars = tail.chars();
+
+        match chars.next() {
+            Some(c) if folding.fold(c) == folding.fold(*self) => {
+                Ok((c, chars.as_str()))
+            }
+            _ => Err(Error::expecting(
+                format!("char '{}' (case-insensitive)", self),
+                tail,
+            )),
+        }
+    }
+
+    fn first_set_caseless(&self, folding: Folding) -> Option<FirstSet> {
+        let folded = folding.fold(*self);
+        let non_ascii = (!self.is_ascii()).then_some((*self, *self));
+
+        Some(folding.first_set(non_ascii, |c| folding.fold(c) == folded))
+    }
+
+    fn describe_caseless(&self) -> GrammarNode {
+        self.describe()
+    }
+}
+
+impl Caseless for CharRange {
+    type Output = char;
+
+    fn p_arse_caseless<'a>(
+        &self,
+        folding: Folding,
+        tail: &'a str,
+    ) -> Result<'a, Self::Output> {
+        let (from, to) = self.bounds();
+        let mut chars = tail.chars();
+
+        match chars.next() {
+            Some(c)
+                if folding
+                    .variants(c)
+                    .iter()
+                    .any(|v| (from..=to).contains(v)) =>
+            {
+                Ok((c, chars.as_str()))
+            }
+            _ => Err(Error::expecting(
+                format!("char from '{}' to '{}' (case-insensitive)", from, to),
+                tail,
+            )),
+        }
+    }
+
+    fn first_set_caseless(&self, folding: Folding) -> Option<FirstSet> {
+        let (from, to) = self.bounds();
+        let non_ascii =
+            Some((from.max('\u{80}'), to)).filter(|(from, to)| from <= to);
+
+        Some(folding.first_set(non_ascii, |c| {
+            folding.variants(c).iter().any(|v| (from..=to).contains(v))
+        }))
+    }
+
+    fn describe_caseless(&self) -> GrammarNode {
+        self.describe()
+    }
+}
+
+/// A [`Parser`] matching a literal case-insensitively, returned by
+/// [`no_case`] and [`no_case_ascii`]. Outputs the matched input rather than
+/// the literal.
+#[derive(Copy, Clone)]
+pub struct NoCase<L>
+where
+    L: Caseless,
+{
+    literal: L,
+    folding: Folding,
+}
+
+/// Matches the literal ignoring the case, using the simple Unicode case
+/// mappings. See [`Folding::Unicode`].
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::{no_case, CharExt, Parser};
+///
+/// let select = no_case("select");
+/// assert_eq!(select.p_arse("SeLeCt *").unwrap(), ("SeLeCt".into(), " *"));
+/// assert!(select.p_arse("selekt *").is_err());
+///
+/// let sigma = no_case('σ');
+/// assert_eq!(sigma.p_arse("Σ").unwrap().0, 'Σ');
+///
+/// let hex_letter = no_case('a'.to('f'));
+/// assert!(hex_letter.p_arse("E").is_ok());
+/// ```
+pub fn no_case<L: Caseless>(literal: L) -> NoCase<L> {
+    NoCase {
+        literal,
+        folding: Folding::Unicode,
+    }
+}
+
+/// Matches the literal ignoring the case of ASCII letters only. See
+/// [`Folding::Ascii`].
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::{no_case_ascii, Parser};
+///
+/// let header = no_case_ascii("content-type");
+/// assert!(header.p_arse("Content-Type: text/plain").is_ok());
+///
+/// let ascii_k = no_case_ascii('k');
+/// assert!(ascii_k.p_arse("K").is_ok());
+/// assert!(ascii_k.p_arse("\u{212A}").is_err()); // Kelvin sign
+/// ```
+pub fn no_case_ascii<L: Caseless>(literal: L) -> NoCase<L> {
+    NoCase {
+        literal,
+        folding: Folding::Ascii,
+    }
+}
+
+impl<L> NoCase<L>
+where
+    L: Caseless,
+{
+    pub fn folding(&self) -> Folding {
+        self.folding
+    }
+}
+
+impl<L> Parser for NoCase<L>
+where
+    L: Caseless,
+{
+    type Output = L::Output;
+
+    fn first_set(&self) -> Option<FirstSet> {
+        self.literal.first_set_caseless(self.folding)
+    }
+
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::NoCase(Box::new(self.literal.describe_caseless()))
+    }
+
+    fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        self.literal.p_arse_caseless(self.folding, tail)
+    }
+}
+
+impl<L> Leaf for NoCase<L> where L: Caseless {}
@@ -0,0 +1,341 @@
+//! Character classes.
+
+use std::{
+    fmt,
+    ops::{BitAnd, BitOr, Not},
+};
+
+use crate::{
+    describe::GrammarNode,
+    literal::CharRange,
+    search::{FirstSet, FIRST_NON_ASCII},
+    sequence::Leaf,
+    Error,
+    Parser,
+    Result,
+};
+
+/// A set of characters built from characters, ranges and strings using the
+/// union (`|`), intersection (`&`) and negation (`!`) operators.
+///
+/// Lookups of ASCII characters use a bitset, the other characters are looked
+/// up in a sorted list of ranges. Since the class is not [`Copy`], the
+/// [`Parser`] is implemented for `&CharClass`. It matches a single character
+/// from the class.
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::{CharClass, CharExt, Parser, none_of};
+///
+/// let hex_d = CharClass::from('0'.to('9')) | 'a'.to('f') | 'A'.to('F');
+/// let hex_dd = (&hex_d, &hex_d).maps(|s| u8::from_str_radix(s, 16).unwrap());
+///
+/// assert_eq!(hex_dd.p_arse("c8").unwrap().0, 0xc8);
+/// assert!(hex_dd.p_arse("cx").is_err());
+///
+/// let unescaped = none_of("\"\\");
+/// let vowel = CharClass::from("aeiou");
+/// let consonant = CharClass::from('a'.to('z')) & !vowel;
+///
+/// assert!((&unescaped).p_arse("a").is_ok());
+/// assert!((&unescaped).p_arse("\\").is_err());
+/// assert!((&consonant).p_arse("b").is_ok());
+/// assert!((&consonant).p_arse("e").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharClass {
+    set: FirstSet,
+    description: String,
+}
+
+impl CharClass {
+    /// A class containing no characters.
+    pub fn empty() -> Self {
+        CharClass {
+            set: FirstSet::from_ranges(None),
+            description: "nothing".to_string(),
+        }
+    }
+
+    /// A class containing the characters from the (inclusive) ranges.
+    pub fn from_ranges<I>(ranges: I) -> Self
+    where
+        I: IntoIterator<Item = (char, char)>,
+    {
+        let set = FirstSet::from_ranges(ranges);
+        let description = describe_ranges(&set);
+
+        CharClass { set, description }
+    }
+
+    /// Replaces the description of the class used in errors.
+    pub fn described<S>(mut self, description: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.description = description.into();
+
+        self
+    }
+
+    /// The description of the class used in errors.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn contains(&self, c: char) -> bool {
+        self.set.contains(c)
+    }
+
+    /// The sorted, non-overlapping (inclusive) ranges of the class.
+    pub fn ranges(&self) -> Vec<(char, char)> {
+        self.set.ranges()
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        CharClass {
+            set: self.set.union(&other.set),
+            description: format!("{} or {}", self, other),
+        }
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = vec![];
+        let (mut i, mut j) = (0, 0);
+
+        let (ranges_0, ranges_1) = (&self.set.non_ascii, &other.set.non_ascii);
+
+        while i < ranges_0.len() && j < ranges_1.len() {
+            let (from_0, to_0) = ranges_0[i];
+            let (from_1, to_1) = ranges_1[j];
+            let (from, to) = (from_0.max(from_1), to_0.min(to_1));
+
+            if from <= to {
+                ranges.push((from, to));
+            }
+
+            if to_0 < to_1 {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        CharClass {
+            set: FirstSet {
+                ascii: self.set.ascii & other.set.ascii,
+                non_ascii: ranges,
+            },
+            description: format!("({}) and ({})", self, other),
+        }
+    }
+
+    pub fn negation(&self) -> Self {
+        let mut ranges = vec![];
+        let mut from = Some(FIRST_NON_ASCII);
+
+        for &(range_from, range_to) in &self.set.non_ascii {
+            if let Some(from) = from {
+                if from < range_from {
+                    ranges.push((from, previous_char(range_from)));
+                }
+            }
+            from = next_char(range_to);
+        }
+
+        if let Some(from) = from {
+            ranges.push((from, char::MAX));
+        }
+
+        CharClass {
+            set: FirstSet {
+                ascii: !self.set.ascii,
+                non_ascii: ranges,
+            },
+            description: format!("not ({})", self),
+        }
+    }
+
+    pub(crate) fn first_set(&self) -> FirstSet {
+        self.set.clone()
+    }
+}
+
+fn next_char(c: char) -> Option<char> {
+    match c {
+        char::MAX => None,
+        '\u{D7FF}' => Some('\u{E000}'),
+        _ => char::from_u32(c as u32 + 1),
+    }
+}
+
+fn previous_char(c: char) -> char {
+    match c {
+        '\u{E000}' => '\u{D7FF}',
+        _ => char::from_u32(c as u32 - 1).unwrap_or(c),
+    }
+}
+
+fn describe_ranges(set: &FirstSet) -> String {
+    let ranges = set
+        .ranges()
+        .into_iter()
+        .map(|(from, to)| {
+            if from == to {
+                format!("{:?}", from)
+            } else {
+                format!("{:?}-{:?}", from, to)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    format!("[{}]", ranges.join(" "))
+}
+
+impl fmt::Display for CharClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.description)
+    }
+}
+
+impl From<char> for CharClass {
+    fn from(c: char) -> Self {
+        CharClass::from_ranges(Some((c, c))).described(format!("{:?}", c))
+    }
+}
+
+impl From<CharRange> for CharClass {
+    fn from(range: CharRange) -> Self {
+        let (from, to) = range.bounds();
+
+        CharClass::from_ranges(Some((from, to)))
+            .described(format!("from {:?} to {:?}", from, to))
+    }
+}
+
+/// A class containing the characters of the string.
+impl From<&str> for CharClass {
+    fn from(chars: &str) -> Self {
+        one_of(chars)
+    }
+}
+
+/// Returns a [`CharClass`] containing the characters of the string.
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::{Parser, one_of};
+///
+/// let sign = one_of("+-");
+///
+/// assert!((&sign).p_arse("-1").is_ok());
+/// assert!((&sign).p_arse("1").is_err());
+/// ```
+pub fn one_of(chars: &str) -> CharClass {
+    CharClass::from_ranges(chars.chars().map(|c| (c, c)))
+        .described(format!("one of {:?}", chars))
+}
+
+/// Returns a [`CharClass`] containing all characters except those of the
+/// string.
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::{Parser, none_of};
+///
+/// let unquoted = none_of("\"");
+///
+/// assert!((&unquoted).p_arse("a").is_ok());
+/// assert!((&unquoted).p_arse("\"").is_err());
+/// ```
+pub fn none_of(chars: &str) -> CharClass {
+    one_of(chars)
+        .negation()
+        .described(format!("none of {:?}", chars))
+}
+
+impl<T> BitOr<T> for CharClass
+where
+    T: Into<CharClass>,
+{
+    type Output = CharClass;
+
+    fn bitor(self, other: T) -> Self::Output {
+        self.union(&other.into())
+    }
+}
+
+impl<T> BitAnd<T> for CharClass
+where
+    T: Into<CharClass>,
+{
+    type Output = CharClass;
+
+    fn bitand(self, other: T) -> Self::Output {
+        self.intersection(&other.into())
+    }
+}
+
+impl Not for CharClass {
+    type Output = CharClass;
+
+    fn not(self) -> Self::Output {
+        self.negation()
+    }
+}
+
+impl<T> BitOr<T> for CharRange
+where
+    T: Into<CharClass>,
+{
+    type Output = CharClass;
+
+    fn bitor(self, other: T) -> Self::Output {
+        CharClass::from(self).union(&other.into())
+    }
+}
+
+impl<T> BitAnd<T> for CharRange
+where
+    T: Into<CharClass>,
+{
+    type Output = CharClass;
+
+    fn bitand(self, other: T) -> Self::Output {
+        CharClass::from(self).intersection(&other.into())
+    }
+}
+
+impl Not for CharRange {
+    type Output = CharClass;
+
+    fn not(self) -> Self::Output {
+        CharClass::from(self).negation()
+    }
+}
+
+impl Parser for &CharClass {
+    type Output = char;
+
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::Class(self.ranges())
+    }
+
+    fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        let mut chars = tail.chars();
+
+        match chars.next() {
+            Some(first) if self.contains(first) => Ok((first, chars.as_str())),
+            _ => Err(Error::expecting(format!("char {}", self), tail)),
+        }
+    }
+
+    fn first_set(&self) -> Option<FirstSet> {
+        Some(CharClass::first_set(self))
+    }
+}
+
+impl Leaf for &CharClass {}
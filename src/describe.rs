@@ -0,0 +1,326 @@
+//! Introspection of parsers.
+//!
+//! [`Parser::describe`](crate::Parser::describe) reflects the structure of a parser into a
+//! [`GrammarNode`] tree, e.g. to document, lint or visualize the grammar.
+//! The outputs and the mapping functions are not a part of the tree. Parsers
+//! built from functions, like [`fun`](crate::fun) or
+//! [`.bind()`](crate::Parser::bind), are opaque.
+//!
+//! Recursive grammars built with [`rec`](crate::rec) are opaque too: the
+//! closure is code, not a parser, so neither its structure nor the recursion
+//! can be seen. [`.named()`](crate::Parser::named) on such a parser gives
+//! only a rule around an opaque node, with no references to itself. Build
+//! recursive grammars with [`rule`](crate::rule), or `peg!` from the
+//! `p-arse-macros` crate, instead: their closures build parsers, so the rules
+//! are described with references to themselves.
+//!
+//! # Examples
+//!
+//! ```
+//! use p_arse::{describe::GrammarNode, CharExt, Parser};
+//!
+//! let digit = '0'.to('9');
+//! let number = ('-'.opt(), digit.more()).named("number");
+//!
+//! assert_eq!(
+//!     number.describe(),
+//!     GrammarNode::Rule {
+//!         name: "number".into(),
+//!         node: Box::new(GrammarNode::Sequence(vec![
+//!             GrammarNode::Repeat {
+//!                 node: Box::new(GrammarNode::Literal("-".into())),
+//!                 min: 0,
+//!                 max: Some(1),
+//!             },
+//!             GrammarNode::Repeat {
+//!                 node: Box::new(GrammarNode::Class(vec![('0', '9')])),
+//!                 min: 1,
+//!                 max: None,
+//!             },
+//!         ])),
+//!     },
+//! );
+//! ```
+
+use std::cell::RefCell;
+
+/// The structure of a parser, returned by
+/// [`Parser::describe`](crate::Parser::describe).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GrammarNode {
+    /// The nodes, one after another.
+    Sequence(Vec<GrammarNode>),
+    /// The first of the nodes which matches.
+    Choice(Vec<GrammarNode>),
+    /// The node repeated at least `min` times and at most `max` times, if
+    /// there's a limit.
+    Repeat {
+        node: Box<GrammarNode>,
+        min: usize,
+        max: Option<usize>,
+    },
+    /// Checks if the node matches (or doesn't match, if it's not `positive`)
+    /// without consuming anything.
+    Lookahead {
+        node: Box<GrammarNode>,
+        positive: bool,
+    },
+    /// The string.
+    Literal(String),
+    /// The node, ignoring the case.
+    NoCase(Box<GrammarNode>),
+    /// A character from the inclusive ranges.
+    Class(Vec<(char, char)>),
+    /// Any character.
+    Any,
+    /// The end of the input.
+    End,
+    /// A rule named with [`.named()`](crate::Parser::named).
+    Rule {
+        name: String,
+        node: Box<GrammarNode>,
+    },
+    /// A reference to a rule described elsewhere.
+    Ref(String),
+    /// A parser of unknown structure, e.g. a function.
+    Opaque(String),
+}
+
+impl GrammarNode {
+    /// The nodes one after another. Nested sequences are flattened.
+    pub fn sequence<I>(nodes: I) -> Self
+    where
+        I: IntoIterator<Item = GrammarNode>,
+    {
+        let mut flat = vec![];
+
+        for node in nodes {
+            match node {
+                GrammarNode::Sequence(nodes) => flat.extend(nodes),
+                node => flat.push(node),
+            }
+        }
+
+        match flat.len() {
+            1 => flat.pop().unwrap(),
+            _ => GrammarNode::Sequence(flat),
+        }
+    }
+
+    /// The first of the nodes which matches. Nested choices are flattened.
+    pub fn choice<I>(nodes: I) -> Self
+    where
+        I: IntoIterator<Item = GrammarNode>,
+    {
+        let mut flat = vec![];
+
+        for node in nodes {
+            match node {
+                GrammarNode::Choice(nodes) => flat.extend(nodes),
+                node => flat.push(node),
+            }
+        }
+
+        match flat.len() {
+            1 => flat.pop().unwrap(),
+            _ => GrammarNode::Choice(flat),
+        }
+    }
+
+    pub fn repeat(node: GrammarNode, min: usize, max: Option<usize>) -> Self {
+        GrammarNode::Repeat {
+            node: Box::new(node),
+            min,
+            max,
+        }
+    }
+
+    pub fn lookahead(node: GrammarNode, positive: bool) -> Self {
+        GrammarNode::Lookahead {
+            node: Box::new(node),
+            positive,
+        }
+    }
+
+    /// The direct children of the node.
+    pub fn children(&self) -> Vec<&GrammarNode> {
+        match self {
+            GrammarNode::Sequence(nodes) | GrammarNode::Choice(nodes) => {
+                nodes.iter().collect()
+            }
+            GrammarNode::Repeat { node, .. }
+            | GrammarNode::Lookahead { node, .. }
+            | GrammarNode::NoCase(node)
+            | GrammarNode::Rule { node, .. } => vec![node],
+            _ => vec![],
+        }
+    }
+
+    /// The rules in the tree, in the order of their first appearance, with
+    /// the rules nested in them replaced by [`GrammarNode::Ref`]. If a name
+    /// is repeated, the first rule is used, see
+    /// [`Lint::DuplicateRule`](crate::lint::Lint::DuplicateRule).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use p_arse::{describe::GrammarNode, CharExt, Parser};
+    ///
+    /// let digit = '0'.to('9').named("digit");
+    /// let pair = (digit, ',', digit).named("pair");
+    ///
+    /// let rules = pair.describe().rules();
+    /// assert_eq!(rules[0].0, "pair");
+    /// assert_eq!(
+    ///     rules[0].1,
+    ///     GrammarNode::Sequence(vec![
+    ///         GrammarNode::Ref("digit".into()),
+    ///         GrammarNode::Literal(",".into()),
+    ///         GrammarNode::Ref("digit".into()),
+    ///     ]),
+    /// );
+    /// assert_eq!(rules[1].0, "digit");
+    /// assert_eq!(rules.len(), 2);
+    /// ```
+    pub fn rules(&self) -> Vec<(String, GrammarNode)> {
+        let mut rules: Vec<(String, GrammarNode)> = vec![];
+
+        for (name, node) in self.definitions() {
+            if !rules.iter().any(|(defined, _)| *defined == name) {
+                rules.push((name, node));
+            }
+        }
+
+        rules
+    }
+
+    // The rules in the tree, in the order of their appearances, with the
+    // repeated names as well.
+    fn definitions(&self) -> Vec<(String, GrammarNode)> {
+        let mut definitions = vec![];
+        self.collect_rules(&mut definitions);
+
+        definitions
+    }
+
+    // The rules, preceded by the node as the rule `start` unless it's a rule.
+    // If a rule is already named `start`, it's `start2`, `start3` and so on.
+    pub(crate) fn rules_with_start(&self) -> Vec<(String, GrammarNode)> {
+        self.with_start(self.rules())
+    }
+
+    // Like `rules_with_start`, with the repeated names as well.
+    pub(crate) fn definitions_with_start(&self) -> Vec<(String, GrammarNode)> {
+        self.with_start(self.definitions())
+    }
+
+    fn with_start(
+        &self,
+        mut rules: Vec<(String, GrammarNode)>,
+    ) -> Vec<(String, GrammarNode)> {
+        if !matches!(self, GrammarNode::Rule { .. }) {
+            let taken = |name: &str| rules.iter().any(|(rule, _)| rule == name);
+            let name = std::iter::once("start".to_string())
+                .chain((2..).map(|n| format!("start{}", n)))
+                .find(|name| !taken(name))
+                .unwrap();
+
+            rules.insert(0, (name, self.without_rules()));
+        }
+
+        rules
+    }
+
+    fn collect_rules(&self, rules: &mut Vec<(String, GrammarNode)>) {
+        if let GrammarNode::Rule { name, node } = self {
+            rules.push((name.clone(), node.without_rules()));
+        }
+
+        for child in self.children() {
+            child.collect_rules(rules);
+        }
+    }
+
+    // The node with the nested rules replaced by references.
+    pub(crate) fn without_rules(&self) -> GrammarNode {
+        let boxed = |node: &GrammarNode| Box::new(node.without_rules());
+
+        match self {
+            GrammarNode::Sequence(nodes) => GrammarNode::Sequence(
+                nodes.iter().map(GrammarNode::without_rules).collect(),
+            ),
+            GrammarNode::Choice(nodes) => GrammarNode::Choice(
+                nodes.iter().map(GrammarNode::without_rules).collect(),
+            ),
+            GrammarNode::Repeat { node, min, max } => GrammarNode::Repeat {
+                node: boxed(node),
+                min: *min,
+                max: *max,
+            },
+            GrammarNode::Lookahead { node, positive } => {
+                GrammarNode::Lookahead {
+                    node: boxed(node),
+                    positive: *positive,
+                }
+            }
+            GrammarNode::NoCase(node) => GrammarNode::NoCase(boxed(node)),
+            GrammarNode::Rule { name, .. } => GrammarNode::Ref(name.clone()),
+            node => node.clone(),
+        }
+    }
+}
+
+thread_local! {
+    // The names of the rules described so far by the outermost rule being
+    // described, or `None` outside of it.
+    static DESCRIBED: RefCell<Option<Vec<&'static str>>> =
+        const { RefCell::new(None) };
+}
+
+// Forgets the described rules when the outermost rule is described.
+struct Outermost;
+
+impl Drop for Outermost {
+    fn drop(&mut self) {
+        DESCRIBED.with(|described| described.borrow_mut().take());
+    }
+}
+
+// Describes a rule which can refer to itself, see
+// [`rule`](crate::function::rule). Its first appearance is a
+// [`GrammarNode::Rule`], the next ones, including the recursive ones, are
+// [`GrammarNode::Ref`]s, so the description is finite.
+pub(crate) fn rule<F>(name: &'static str, body: F) -> GrammarNode
+where
+    F: FnOnce() -> GrammarNode,
+{
+    // Whether it's the first appearance of the rule and the outermost rule.
+    let (first, outermost) = DESCRIBED.with(|described| {
+        let mut described = described.borrow_mut();
+
+        match &mut *described {
+            Some(names) if names.contains(&name) => (false, false),
+            Some(names) => {
+                names.push(name);
+
+                (true, false)
+            }
+            None => {
+                *described = Some(vec![name]);
+
+                (true, true)
+            }
+        }
+    });
+
+    if !first {
+        return GrammarNode::Ref(name.to_string());
+    }
+
+    let _outermost = if outermost { Some(Outermost) } else { None };
+
+    GrammarNode::Rule {
+        name: name.to_string(),
+        node: Box::new(body()),
+    }
+}
@@ -0,0 +1,486 @@
+//! Railroad diagrams of parsers.
+//!
+//! [`railroad`] draws the structure of a parser (see
+//! [`describe`](crate::describe)) as an HTML page with an SVG railroad
+//! diagram for every [`.named()`](Parser::named) rule. If the parser itself
+//! is not named, it's drawn as the rule `start` (or `start2` and so on, if a
+//! rule is already named so). The references to the rules link to their
+//! diagrams. The page and the diagrams are self-contained, they need no
+//! scripts or stylesheets.
+//!
+//! The literals and the classes are drawn as rounded boxes, the rules as
+//! square boxes and the opaque parsers as dashed boxes. The lookaheads and
+//! the case-insensitive parsers are framed with a caption. For the recursive
+//! parsers, see [`describe`](crate::describe).
+//!
+//! # Examples
+//!
+//! ```
+//! use p_arse::{diagram::railroad, CharExt, Parser};
+//!
+//! let digit = '0'.to('9').named("digit");
+//! let number = ('-'.opt(), digit.more()).named("number");
+//!
+//! let html = railroad(&number);
+//! assert!(html.starts_with("<!DOCTYPE html>"));
+//! assert_eq!(html.matches("<svg").count(), 2);
+//! assert!(html.contains(r##"<h2 id="number">number</h2>"##));
+//! assert!(html.contains(r##"<a href="#digit">"##));
+//! assert!(html.contains("<text x=\""));
+//! assert!(html.contains(">[0-9]</text>"));
+//! ```
+
+use std::fmt::Write;
+
+use crate::{
+    describe::GrammarNode,
+    grammar::{peg_class, peg_literal},
+    Parser,
+};
+
+// The dimensions, in pixels.
+const CHAR_WIDTH: u32 = 8;
+const BOX_HEIGHT: u32 = 22;
+const PADDING: u32 = 8;
+const GAP: u32 = 10;
+const ARC: u32 = 10;
+const CAPTION: u32 = 14;
+const MARGIN: u32 = 10;
+
+const STYLE: &str = "\
+path { stroke: #333; stroke-width: 1.5; fill: none; }
+rect { stroke: #333; stroke-width: 1.5; fill: #eef; }
+rect.opaque { fill: #fff; stroke-dasharray: 4 2; }
+rect.frame { fill: none; stroke: #999; stroke-dasharray: 4 3; }
+text { font: 13px monospace; text-anchor: middle; }
+text.caption { font-size: 11px; text-anchor: start; fill: #666; }";
+
+/// Draws the rules of the parser as an HTML page of railroad diagrams.
+pub fn railroad<P: Parser>(parser: &P) -> String {
+    parser.describe().railroad()
+}
+
+/// Draws the node as an SVG railroad diagram. The rules in the node are
+/// drawn as references.
+pub fn svg(node: &GrammarNode) -> String {
+    let item = Item::from(&node.without_rules());
+    let size = item.size();
+    let width = 2 * MARGIN + 2 * GAP + size.width;
+    let height = 2 * MARGIN + size.up + size.down;
+    let y = MARGIN + size.up;
+    let mut svg = String::new();
+
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" class="railroad" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = width,
+        h = height,
+    );
+    let _ = writeln!(svg, "<style>\n{}\n</style>", STYLE);
+
+    // The ends of the track are marked with bars.
+    let end = width - MARGIN;
+    let _ = writeln!(
+        svg,
+        r#"<path d="M{x} {top}v16M{x} {y}h{gap}M{e} {y}h{gap}M{e2} {top}v16"/>"#,
+        x = MARGIN,
+        y = y,
+        top = y - 8,
+        gap = GAP,
+        e = end - GAP,
+        e2 = end,
+    );
+    item.draw(&mut svg, MARGIN + GAP, y);
+    svg.push_str("</svg>\n");
+
+    svg
+}
+
+impl GrammarNode {
+    /// Draws the rules of the tree as an HTML page of railroad diagrams, see
+    /// [`railroad`].
+    pub fn railroad(&self) -> String {
+        let mut html = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>Grammar</title>\n</head>\n<body>\n",
+        );
+
+        for (name, node) in self.rules_with_start() {
+            let _ = writeln!(html, r#"<h2 id="{0}">{0}</h2>"#, escape(&name));
+            html.push_str(&svg(&node));
+        }
+        html.push_str("</body>\n</html>\n");
+
+        html
+    }
+}
+
+// The parts of the diagrams.
+enum Item {
+    // A literal, a class or a special character.
+    Terminal(String),
+    // A reference to a rule.
+    NonTerminal(String),
+    // An opaque parser.
+    Opaque(String),
+    // The track without anything on it.
+    Skip,
+    Sequence(Vec<Item>),
+    Choice(Vec<Item>),
+    // The item with a track back to its beginning, and a caption below, if
+    // the number of repetitions is limited.
+    Loop(Box<Item>, Option<String>),
+    // The item in a frame with a caption above.
+    Frame(Box<Item>, String),
+}
+
+// The size of an item, around the track it lies on.
+#[derive(Clone, Copy)]
+struct Size {
+    width: u32,
+    up: u32,
+    down: u32,
+}
+
+impl From<&GrammarNode> for Item {
+    fn from(node: &GrammarNode) -> Self {
+        let boxed = |node: &GrammarNode| Box::new(Item::from(node));
+
+        match node {
+            GrammarNode::Sequence(nodes) => match nodes.len() {
+                0 => Item::Skip,
+                1 => Item::from(&nodes[0]),
+                _ => Item::Sequence(nodes.iter().map(Item::from).collect()),
+            },
+            GrammarNode::Choice(nodes) => match nodes.len() {
+                0 => Item::Skip,
+                1 => Item::from(&nodes[0]),
+                _ => Item::Choice(nodes.iter().map(Item::from).collect()),
+            },
+            GrammarNode::Repeat { node, min, max } => match (*min, *max) {
+                (0, Some(0)) => Item::Skip,
+                (1, Some(1)) => Item::from(&**node),
+                (0, Some(1)) => {
+                    Item::Choice(vec![Item::Skip, Item::from(&**node)])
+                }
+                (0, None) => Item::Choice(vec![
+                    Item::Skip,
+                    Item::Loop(boxed(node), None),
+                ]),
+                (1, None) => Item::Loop(boxed(node), None),
+                (min, max) => {
+                    let times = match max {
+                        Some(max) if min == max => format!("{} times", min),
+                        Some(max) if min == 0 => {
+                            format!("at most {} times", max)
+                        }
+                        Some(max) => format!("{} to {} times", min, max),
+                        None => format!("at least {} times", min),
+                    };
+                    let repeat = Item::Loop(boxed(node), Some(times));
+
+                    // A loop is passed through at least once, so it needs a
+                    // way around it, like `e*`.
+                    match min {
+                        0 => Item::Choice(vec![Item::Skip, repeat]),
+                        _ => repeat,
+                    }
+                }
+            },
+            GrammarNode::Lookahead { node, positive } => {
+                let caption = match positive {
+                    true => "followed by",
+                    false => "not followed by",
+                };
+
+                Item::Frame(boxed(node), caption.to_string())
+            }
+            GrammarNode::Literal(literal) => {
+                Item::Terminal(peg_literal(literal))
+            }
+            GrammarNode::NoCase(node) => {
+                Item::Frame(boxed(node), "ignoring case".to_string())
+            }
+            GrammarNode::Class(ranges) => Item::Terminal(peg_class(ranges)),
+            GrammarNode::Any => Item::Terminal("any character".to_string()),
+            GrammarNode::End => Item::Terminal("end of input".to_string()),
+            GrammarNode::Rule { name, .. } | GrammarNode::Ref(name) => {
+                Item::NonTerminal(name.clone())
+            }
+            GrammarNode::Opaque(description) => {
+                Item::Opaque(description.clone())
+            }
+        }
+    }
+}
+
+impl Item {
+    fn size(&self) -> Size {
+        match self {
+            Item::Terminal(text)
+            | Item::NonTerminal(text)
+            | Item::Opaque(text) => Size {
+                width: text_width(text) + 2 * PADDING,
+                up: BOX_HEIGHT / 2,
+                down: BOX_HEIGHT / 2,
+            },
+            Item::Skip => Size {
+                width: 0,
+                up: 0,
+                down: 0,
+            },
+            Item::Sequence(items) => {
+                let sizes: Vec<_> = items.iter().map(Item::size).collect();
+
+                Size {
+                    width: sizes.iter().map(|size| size.width).sum::<u32>()
+                        + GAP * (sizes.len() as u32 - 1),
+                    up: sizes.iter().map(|size| size.up).max().unwrap_or(0),
+                    down: sizes.iter().map(|size| size.down).max().unwrap_or(0),
+                }
+            }
+            Item::Choice(items) => {
+                let sizes: Vec<_> = items.iter().map(Item::size).collect();
+                let offsets = branch_offsets(&sizes);
+                let last = sizes.len() - 1;
+
+                Size {
+                    width: sizes
+                        .iter()
+                        .map(|size| size.width)
+                        .max()
+                        .unwrap_or(0)
+                        + 4 * ARC,
+                    up: sizes[0].up,
+                    down: offsets[last] + sizes[last].down,
+                }
+            }
+            Item::Loop(item, caption) => {
+                let size = item.size();
+                let height = if caption.is_some() { CAPTION } else { 0 };
+
+                Size {
+                    width: loop_width(size, caption) + 2 * ARC,
+                    up: size.up,
+                    down: loop_offset(size) + height,
+                }
+            }
+            Item::Frame(item, caption) => {
+                let size = item.size();
+
+                Size {
+                    width: (size.width + 2 * PADDING)
+                        .max(text_width(caption) + PADDING),
+                    up: size.up + PADDING + CAPTION,
+                    down: size.down + PADDING,
+                }
+            }
+        }
+    }
+
+    // Draws the item with its track starting at `(x, y)`.
+    fn draw(&self, svg: &mut String, x: u32, y: u32) {
+        let size = self.size();
+
+        match self {
+            Item::Terminal(text) => {
+                draw_box(svg, x, y, size.width, text, r#"rx="11""#);
+            }
+            Item::NonTerminal(name) => {
+                let _ = write!(svg, r##"<a href="#{}">"##, escape(name));
+                draw_box(svg, x, y, size.width, name, "");
+                svg.push_str("</a>\n");
+            }
+            Item::Opaque(text) => {
+                draw_box(svg, x, y, size.width, text, r#"class="opaque""#);
+            }
+            Item::Skip => {}
+            Item::Sequence(items) => {
+                let mut x = x;
+
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        draw_line(svg, x, y, GAP);
+                        x += GAP;
+                    }
+                    item.draw(svg, x, y);
+                    x += item.size().width;
+                }
+            }
+            Item::Choice(items) => {
+                let sizes: Vec<_> = items.iter().map(Item::size).collect();
+                let offsets = branch_offsets(&sizes);
+                let inner = size.width - 4 * ARC;
+
+                for ((item, item_size), offset) in
+                    items.iter().zip(&sizes).zip(offsets)
+                {
+                    let track = inner - item_size.width;
+
+                    if offset == 0 {
+                        draw_line(svg, x, y, 2 * ARC);
+                    } else {
+                        let _ = writeln!(
+                            svg,
+                            r#"<path d="M{} {}a{a} {a} 0 0 1 {a} {a}v{}a{a} {a} 0 0 0 {a} {a}"/>"#,
+                            x,
+                            y,
+                            offset - 2 * ARC,
+                            a = ARC,
+                        );
+                    }
+                    item.draw(svg, x + 2 * ARC, y + offset);
+
+                    let end = x + 2 * ARC + item_size.width;
+                    if offset == 0 {
+                        draw_line(svg, end, y, track + 2 * ARC);
+                    } else {
+                        let _ = writeln!(
+                            svg,
+                            r#"<path d="M{} {}h{}a{a} {a} 0 0 0 {a} -{a}v-{}a{a} {a} 0 0 1 {a} -{a}"/>"#,
+                            end,
+                            y + offset,
+                            track,
+                            offset - 2 * ARC,
+                            a = ARC,
+                        );
+                    }
+                }
+            }
+            Item::Loop(item, caption) => {
+                let item_size = item.size();
+                let offset = loop_offset(item_size);
+                let inner = loop_width(item_size, caption);
+
+                draw_line(svg, x, y, ARC);
+                item.draw(svg, x + ARC, y);
+                draw_line(
+                    svg,
+                    x + ARC + item_size.width,
+                    y,
+                    inner - item_size.width + ARC,
+                );
+                let _ = writeln!(
+                    svg,
+                    r#"<path d="M{} {}a{a} {a} 0 0 1 {a} {a}v{v}a{a} {a} 0 0 1 -{a} {a}h-{}a{a} {a} 0 0 1 -{a} -{a}v-{v}a{a} {a} 0 0 1 {a} -{a}"/>"#,
+                    x + ARC + inner,
+                    y,
+                    inner,
+                    v = offset - 2 * ARC,
+                    a = ARC,
+                );
+
+                if let Some(caption) = caption {
+                    draw_caption(
+                        svg,
+                        x + ARC,
+                        y + offset + CAPTION - 3,
+                        caption,
+                    );
+                }
+            }
+            Item::Frame(item, caption) => {
+                let item_size = item.size();
+                let top = y - item_size.up - PADDING;
+
+                let _ = writeln!(
+                    svg,
+                    r#"<rect class="frame" x="{}" y="{}" width="{}" height="{}" rx="4"/>"#,
+                    x,
+                    top,
+                    size.width,
+                    item_size.up + item_size.down + 2 * PADDING,
+                );
+                draw_caption(svg, x, top - 4, caption);
+                draw_line(svg, x, y, PADDING);
+                item.draw(svg, x + PADDING, y);
+                draw_line(
+                    svg,
+                    x + PADDING + item_size.width,
+                    y,
+                    size.width - PADDING - item_size.width,
+                );
+            }
+        }
+    }
+}
+
+// The distances of the tracks of the branches from the track of the choice.
+fn branch_offsets(sizes: &[Size]) -> Vec<u32> {
+    let mut offsets = vec![0];
+
+    for pair in sizes.windows(2) {
+        let previous = offsets[offsets.len() - 1];
+        let distance = (pair[0].down + GAP + pair[1].up).max(2 * ARC);
+
+        offsets.push(previous + distance);
+    }
+
+    offsets
+}
+
+// The distance of the track back from the track of the loop.
+fn loop_offset(size: Size) -> u32 {
+    (size.down + GAP).max(2 * ARC)
+}
+
+// The width inside the loop, wide enough for the caption.
+fn loop_width(size: Size, caption: &Option<String>) -> u32 {
+    match caption {
+        Some(caption) => size.width.max(text_width(caption)),
+        None => size.width,
+    }
+}
+
+fn text_width(text: &str) -> u32 {
+    text.chars().count() as u32 * CHAR_WIDTH
+}
+
+fn draw_line(svg: &mut String, x: u32, y: u32, width: u32) {
+    if width > 0 {
+        let _ = writeln!(svg, r#"<path d="M{} {}h{}"/>"#, x, y, width);
+    }
+}
+
+fn draw_box(
+    svg: &mut String,
+    x: u32,
+    y: u32,
+    width: u32,
+    text: &str,
+    attributes: &str,
+) {
+    let _ = writeln!(
+        svg,
+        r#"<rect x="{}" y="{}" width="{}" height="{}" {}/>"#,
+        x,
+        y - BOX_HEIGHT / 2,
+        width,
+        BOX_HEIGHT,
+        attributes,
+    );
+    let _ = writeln!(
+        svg,
+        r#"<text x="{}" y="{}">{}</text>"#,
+        x + width / 2,
+        y + 4,
+        escape(text),
+    );
+}
+
+fn draw_caption(svg: &mut String, x: u32, y: u32, caption: &str) {
+    let _ = writeln!(
+        svg,
+        r#"<text class="caption" x="{}" y="{}">{}</text>"#,
+        x,
+        y,
+        escape(caption),
+    );
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -1,11 +1,14 @@
 //! Error.
 
+use crate::limits::LimitExceeded;
+
 /// Main error.
 #[derive(Debug)]
 pub struct Error<'a> {
     pub stack: Vec<&'static str>,
     expectation: String,
     tail: &'a str,
+    limit_exceeded: Option<LimitExceeded>,
 }
 
 impl<'a> Error<'a> {
@@ -17,9 +20,32 @@ impl<'a> Error<'a> {
             stack: vec![],
             expectation: expectation.into(),
             tail,
+            limit_exceeded: None,
         }
     }
 
+    /// What the parser was expecting, e.g. `"char 'a'"`.
+    pub fn expectation(&self) -> &str {
+        &self.expectation
+    }
+
+    /// The input left where the parser has failed.
+    pub fn tail(&self) -> &'a str {
+        self.tail
+    }
+
+    /// The limit exceeded, if it's the reason of the error, see
+    /// [`limits`](crate::limits).
+    pub fn limit_exceeded(&self) -> Option<LimitExceeded> {
+        self.limit_exceeded
+    }
+
+    pub(crate) fn limit(mut self, limit: LimitExceeded) -> Self {
+        self.limit_exceeded = Some(limit);
+
+        self
+    }
+
     pub(crate) fn push(mut self, name: &'static str) -> Self {
         self.stack.push(name);
 
@@ -0,0 +1,429 @@
+//! Printing parsers as grammars.
+//!
+//! [`export`] prints the structure of a parser (see
+//! [`describe`](crate::describe)) in one of the [`Notation`]s, one rule per
+//! line. Every [`.named()`](Parser::named) parser becomes a rule. If the
+//! parser itself is not named, it's printed as the rule `start` (or `start2`
+//! and so on, if a rule is already named so).
+//!
+//! The notations can't express everything the parsers do. The opaque parsers
+//! are printed as comments or prose, and so are the lookaheads and the
+//! case-insensitive parsers other than literals in the notations without
+//! them. A comment in a PEG runs to the end of the line, so the rule goes on
+//! on the next one. For the recursive parsers, see
+//! [`describe`](crate::describe).
+//!
+//! # Examples
+//!
+//! ```
+//! use p_arse::{
+//!     export::{export, Notation},
+//!     CharExt,
+//!     Parser,
+//! };
+//!
+//! let digit = '0'.to('9').named("digit");
+//! let number = ('-'.opt(), digit.more()).named("number");
+//! let list = ('[', (number, (", ", number).zore()).opt(), ']').named("list");
+//!
+//! assert_eq!(
+//!     export(&list, Notation::Peg),
+//!     "\
+//! list   <- '[' (number (', ' number)*)? ']'
+//! number <- '-'? digit+
+//! digit  <- [0-9]
+//! ",
+//! );
+//! assert_eq!(
+//!     export(&list, Notation::Ebnf),
+//!     "\
+//! list   ::= \"[\" (number (\", \" number)*)? \"]\"
+//! number ::= \"-\"? digit+
+//! digit  ::= [0-9]
+//! ",
+//! );
+//! assert_eq!(
+//!     export(&list, Notation::Abnf),
+//!     "\
+//! list   = \"[\" [number *(\", \" number)] \"]\"
+//! number = [\"-\"] 1*digit
+//! digit  = %x30-39
+//! ",
+//! );
+//! ```
+
+use crate::{
+    describe::GrammarNode,
+    grammar::{peg_class, peg_literal},
+    Parser,
+};
+
+/// A notation of grammars.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Notation {
+    /// Parsing expression grammars, like [`Grammar`](crate::grammar::Grammar)
+    /// reads them, e.g. `number <- '-'? [0-9]+`. The opaque parsers are
+    /// comments in empty groups, e.g. `(# function` and `)` on the next line.
+    Peg,
+    /// The extended Backus-Naur form used by the W3C, e.g.
+    /// `number ::= "-"? [0-9]+`.
+    Ebnf,
+    /// The augmented Backus-Naur form of RFC 5234, with the case-sensitive
+    /// strings of RFC 7405, e.g. `number = ["-"] 1*%x30-39`.
+    Abnf,
+}
+
+// The binding powers of the operators, the primary expressions bind
+// strongest.
+const CHOICE: u8 = 0;
+const SEQUENCE: u8 = 1;
+const PREFIX: u8 = 2;
+const SUFFIX: u8 = 3;
+const PRIMARY: u8 = 4;
+
+/// Prints the parser as a grammar in the notation.
+pub fn export<P: Parser>(parser: &P, notation: Notation) -> String {
+    parser.describe().export(notation)
+}
+
+// Prints the node as a PEG expression on one line, e.g. in a message.
+pub(crate) fn peg(node: &GrammarNode) -> String {
+    let printer = Printer {
+        notation: Notation::Peg,
+        comments: false,
+    };
+
+    printer.print(node).0
+}
+
+// Prints the nodes in a notation. The opaque parsers are printed as comments
+// in PEGs, so that the grammar can be read back, unless the node has to fit
+// on one line.
+#[derive(Copy, Clone)]
+struct Printer {
+    notation: Notation,
+    comments: bool,
+}
+
+impl GrammarNode {
+    /// Prints the rules of the tree as a grammar in the notation, see
+    /// [`export`].
+    pub fn export(&self, notation: Notation) -> String {
+        let printer = Printer {
+            notation,
+            comments: true,
+        };
+        let rules: Vec<_> = self
+            .rules_with_start()
+            .iter()
+            .map(|(name, node)| (printer.name(name), printer.print(node).0))
+            .collect();
+        let width = rules.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+        let definition = match notation {
+            Notation::Peg => "<-",
+            Notation::Ebnf => "::=",
+            Notation::Abnf => "=",
+        };
+        // The lines following a comment are aligned with the expression.
+        let indent = format!("\n{:1$}", "", width + definition.len() + 2);
+
+        rules
+            .iter()
+            .map(|(name, node)| {
+                format!(
+                    "{:width$} {} {}\n",
+                    name,
+                    definition,
+                    node.replace('\n', &indent),
+                    width = width
+                )
+            })
+            .collect()
+    }
+}
+
+impl Printer {
+    fn name(self, name: &str) -> String {
+        match self.notation {
+            // The rule names consist of letters, digits and hyphens.
+            Notation::Abnf => name.replace('_', "-"),
+            _ => name.to_string(),
+        }
+    }
+
+    // The comment or the prose describing what can't be expressed.
+    fn prose(self, prose: &str) -> (String, u8) {
+        let prose = match self.notation {
+            Notation::Peg | Notation::Abnf => {
+                format!("<{}>", prose.replace('>', ""))
+            }
+            Notation::Ebnf => format!("/* {} */", prose.replace("*/", "")),
+        };
+
+        (prose, PRIMARY)
+    }
+
+    fn wrap(self, node: &GrammarNode, precedence: u8) -> String {
+        match self.print(node) {
+            (text, printed) if printed < precedence => format!("({})", text),
+            (text, _) => text,
+        }
+    }
+
+    fn join(
+        self,
+        nodes: &[GrammarNode],
+        separator: &str,
+        precedence: u8,
+    ) -> String {
+        nodes
+            .iter()
+            .map(|node| self.wrap(node, precedence))
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+
+    // The node and its precedence.
+    fn print(self, node: &GrammarNode) -> (String, u8) {
+        match node {
+            GrammarNode::Choice(nodes) => {
+                let separator = match self.notation {
+                    Notation::Ebnf => " | ",
+                    _ => " / ",
+                };
+
+                (self.join(nodes, separator, SEQUENCE), CHOICE)
+            }
+            GrammarNode::Sequence(nodes) => match nodes.len() {
+                0 => self.literal("", true),
+                1 => self.print(&nodes[0]),
+                _ => (self.join(nodes, " ", PREFIX), SEQUENCE),
+            },
+            GrammarNode::Repeat { node, min, max } => {
+                self.repeat(node, *min, *max)
+            }
+            GrammarNode::Lookahead { node, positive } => match self.notation {
+                Notation::Peg => {
+                    let operator = if *positive { "&" } else { "!" };
+
+                    (format!("{}{}", operator, self.wrap(node, SUFFIX)), PREFIX)
+                }
+                _ => {
+                    let followed = match positive {
+                        true => "followed by",
+                        false => "not followed by",
+                    };
+
+                    self.prose(&format!("{} {}", followed, self.print(node).0))
+                }
+            },
+            GrammarNode::Literal(literal) => self.literal(literal, true),
+            GrammarNode::NoCase(node) => match (self.notation, &**node) {
+                (_, GrammarNode::Literal(literal)) => {
+                    self.literal(literal, false)
+                }
+                (Notation::Peg, node) => {
+                    (format!("{}i", self.wrap(node, PRIMARY)), PRIMARY)
+                }
+                (_, node) => {
+                    let node = self.print(node).0;
+
+                    self.prose(&format!("case-insensitive {}", node))
+                }
+            },
+            GrammarNode::Class(ranges) => self.class(ranges),
+            GrammarNode::Any => match self.notation {
+                Notation::Peg => (".".to_string(), PRIMARY),
+                _ => self.class(&[('\0', char::MAX)]),
+            },
+            GrammarNode::End => match self.notation {
+                Notation::Peg => ("!.".to_string(), PREFIX),
+                _ => self.prose("end of input"),
+            },
+            GrammarNode::Rule { name, .. } | GrammarNode::Ref(name) => {
+                (self.name(name), PRIMARY)
+            }
+            // A comment in a group, so that the rest of the rule goes on the
+            // next line.
+            GrammarNode::Opaque(description)
+                if self.notation == Notation::Peg && self.comments =>
+            {
+                let description = description.replace('\n', " ");
+
+                (format!("(# {}\n)", description), PRIMARY)
+            }
+            GrammarNode::Opaque(description) => self.prose(description),
+        }
+    }
+
+    fn repeat(
+        self,
+        node: &GrammarNode,
+        min: usize,
+        max: Option<usize>,
+    ) -> (String, u8) {
+        if let Notation::Abnf = self.notation {
+            let times = match (min, max) {
+                (0, Some(1)) => {
+                    return (format!("[{}]", self.print(node).0), PRIMARY)
+                }
+                (0, None) => "*".to_string(),
+                (min, None) => format!("{}*", min),
+                (min, Some(max)) if min == max => min.to_string(),
+                (0, Some(max)) => format!("*{}", max),
+                (min, Some(max)) => format!("{}*{}", min, max),
+            };
+
+            return (format!("{}{}", times, self.wrap(node, PRIMARY)), SUFFIX);
+        }
+
+        let operator = match (min, max) {
+            (0, Some(1)) => "?",
+            (0, None) => "*",
+            (1, None) => "+",
+            // Repeated by hand, e.g. `e{2,3}` is `e e e?`.
+            (min, max) => {
+                let optional = match max {
+                    Some(max) => {
+                        vec![
+                            GrammarNode::repeat(node.clone(), 0, Some(1));
+                            max - min
+                        ]
+                    }
+                    None => vec![GrammarNode::repeat(node.clone(), 0, None)],
+                };
+                let nodes = vec![node.clone(); min].into_iter().chain(optional);
+
+                return self.print(&GrammarNode::Sequence(nodes.collect()));
+            }
+        };
+
+        (format!("{}{}", self.wrap(node, PRIMARY), operator), SUFFIX)
+    }
+
+    fn literal(self, literal: &str, case_sensitive: bool) -> (String, u8) {
+        let pieces = match self.notation {
+            Notation::Peg => {
+                let suffix = if case_sensitive { "" } else { "i" };
+
+                return (
+                    format!("{}{}", peg_literal(literal), suffix),
+                    PRIMARY,
+                );
+            }
+            Notation::Ebnf => ebnf_literal(literal, case_sensitive),
+            Notation::Abnf => abnf_literal(literal, case_sensitive),
+        };
+
+        match pieces.len() {
+            0 => ("\"\"".to_string(), PRIMARY),
+            1 => (pieces[0].clone(), PRIMARY),
+            _ => (pieces.join(" "), SEQUENCE),
+        }
+    }
+
+    fn class(self, ranges: &[(char, char)]) -> (String, u8) {
+        match self.notation {
+            Notation::Peg => (peg_class(ranges), PRIMARY),
+            Notation::Ebnf => {
+                let c = |c: char| match c {
+                    ' '..='~' if !"[]-^\\".contains(c) => c.to_string(),
+                    c => format!("#x{:X}", c as u32),
+                };
+                let ranges: String = ranges
+                    .iter()
+                    .map(|&(from, to)| match from == to {
+                        true => c(from),
+                        false => format!("{}-{}", c(from), c(to)),
+                    })
+                    .collect();
+
+                (format!("[{}]", ranges), PRIMARY)
+            }
+            Notation::Abnf => {
+                let ranges: Vec<_> = ranges
+                    .iter()
+                    .map(|&(from, to)| match from == to {
+                        true => format!("%x{:X}", from as u32),
+                        false => format!("%x{:X}-{:X}", from as u32, to as u32),
+                    })
+                    .collect();
+
+                match ranges.len() {
+                    1 => (ranges[0].clone(), PRIMARY),
+                    _ => (ranges.join(" / "), CHOICE),
+                }
+            }
+        }
+    }
+}
+
+// The quoted runs of the literal and the codes of the other characters.
+fn ebnf_literal(literal: &str, case_sensitive: bool) -> Vec<String> {
+    let mut pieces = vec![];
+    let mut run = String::new();
+
+    for c in literal.chars() {
+        let upper = c.to_uppercase().collect::<String>();
+        let lower = c.to_lowercase().collect::<String>();
+        let caseless = !case_sensitive
+            && upper != lower
+            && upper.chars().count() == 1
+            && lower.chars().count() == 1;
+
+        if !caseless && c != '"' && !c.is_control() {
+            run.push(c);
+            continue;
+        }
+
+        if !run.is_empty() {
+            pieces.push(format!("\"{}\"", run));
+            run.clear();
+        }
+
+        match caseless {
+            true => pieces.push(format!("[{}{}]", lower, upper)),
+            false => pieces.push(format!("#x{:X}", c as u32)),
+        }
+    }
+
+    if !run.is_empty() {
+        pieces.push(format!("\"{}\"", run));
+    }
+
+    pieces
+}
+
+// The quoted runs of the literal and the codes of the other characters. The
+// quoted strings of ABNF are case-insensitive, unless prefixed with `%s`.
+fn abnf_literal(literal: &str, case_sensitive: bool) -> Vec<String> {
+    let mut pieces = vec![];
+    let mut run = String::new();
+    let quote = |run: &str| {
+        let has_letters = run.chars().any(|c| c.is_ascii_alphabetic());
+
+        match case_sensitive && has_letters {
+            true => format!("%s\"{}\"", run),
+            false => format!("\"{}\"", run),
+        }
+    };
+
+    for c in literal.chars() {
+        if matches!(c, ' '..='~') && c != '"' {
+            run.push(c);
+            continue;
+        }
+
+        if !run.is_empty() {
+            pieces.push(quote(&run));
+            run.clear();
+        }
+        pieces.push(format!("%x{:X}", c as u32));
+    }
+
+    if !run.is_empty() {
+        pieces.push(quote(&run));
+    }
+
+    pieces
+}
@@ -1,6 +1,18 @@
 //! Implementation of [`Parser`] for functions.
-
-use crate::{Parser, Result};
+//!
+//! The parsers calling functions, [`fun`] and [`rec`], are opaque to
+//! [`describe`]. The [`rule`]s are built from parsers instead, so their
+//! structure, including the recursion, is described.
+
+use crate::{
+    describe::{self, GrammarNode},
+    error::Error,
+    limits,
+    search::FirstSet,
+    sequence::Leaf,
+    Parser,
+    Result,
+};
 
 pub trait Fun<T>: for<'a> Fn(&'a str) -> Result<'a, T> {}
 
@@ -20,15 +32,54 @@ impl<'f, T> Copy for Function<'f, T> {}
 impl<'f, T> Parser for Function<'f, T> {
     type Output = T;
 
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::Opaque("function".to_string())
+    }
+
     fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        let _nested = limits::enter(tail)?;
+
         (self.f)(tail)
     }
 }
 
+impl<'f, T> Leaf for Function<'f, T> {}
+
 pub fn fun<T>(f: &dyn Fun<T>) -> Function<'_, T> {
     Function { f }
 }
 
+/// Boxes a closure so that it can be returned from the function passed to
+/// [`.bind()`](crate::Parser::bind), i.e. when it captures values by move.
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::{function::boxed, Parser, CharExt};
+///
+/// // A here-doc, i.e. `<<END\n...\nEND`.
+/// let tag = ("<<", 'A'.to('Z').more().maps(|s| s.to_string()), '\n');
+/// let here_doc = tag.bind(|(_, tag, _)| {
+///     boxed(move |tail| {
+///         let terminator = format!("\n{}", tag);
+///         let end = tail.find(&terminator).unwrap_or(tail.len());
+///         let (body, tail) = tail.split_at(end);
+///         let (_, tail) = terminator.as_str().p_arse(tail)?;
+///
+///         Ok((body.to_string(), tail))
+///     })
+/// });
+///
+/// let (body, _tail) = here_doc.p_arse("<<END\nline\nEND").unwrap();
+/// assert_eq!(body, "line");
+/// ```
+pub fn boxed<'f, T, F>(f: F) -> Box<dyn Fun<T> + 'f>
+where
+    F: Fun<T> + 'f,
+{
+    Box::new(f)
+}
+
 pub trait Rec<T>:
     for<'a> Fn(&'a str, RecursiveFunction<'_, T>) -> Result<'a, T>
 {
@@ -54,13 +105,134 @@ impl<'f, T> Copy for RecursiveFunction<'f, T> {}
 impl<'f, T> Parser for RecursiveFunction<'f, T> {
     type Output = T;
 
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::Opaque("function".to_string())
+    }
+
     fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        let _nested = limits::enter(tail)?;
+
         (self.f)(tail, *self)
     }
 }
 
+impl<'f, T> Leaf for RecursiveFunction<'f, T> {}
+
 pub fn rec<T>(f: &'_ dyn Rec<T>) -> RecursiveFunction<'_, T> {
     RecursiveFunction {
         f: f as &dyn Rec<_>,
     }
 }
+
+// The body of a [`Rule`], building its parser from the rule itself.
+trait RuleBody<'f, T> {
+    fn p_arse<'a>(&self, tail: &'a str, rule: Rule<'f, T>) -> Result<'a, T>;
+
+    fn describe(&self, rule: Rule<'f, T>) -> GrammarNode;
+}
+
+impl<'f, T, F, P> RuleBody<'f, T> for F
+where
+    F: Fn(Rule<'f, T>) -> P,
+    P: Parser<Output = T>,
+    T: 'f,
+{
+    fn p_arse<'a>(&self, tail: &'a str, rule: Rule<'f, T>) -> Result<'a, T> {
+        self(rule).p_arse(tail)
+    }
+
+    fn describe(&self, rule: Rule<'f, T>) -> GrammarNode {
+        self(rule).describe()
+    }
+}
+
+/// A named, possibly recursive [`Parser`], returned by [`rule`].
+pub struct Rule<'f, T>
+where
+    T: 'f,
+{
+    name: &'static str,
+    body: &'f dyn RuleBody<'f, T>,
+}
+
+impl<'f, T> Clone for Rule<'f, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'f, T> Copy for Rule<'f, T> {}
+
+impl<'f, T> Parser for Rule<'f, T> {
+    type Output = T;
+
+    fn first_set(&self) -> Option<FirstSet> {
+        // The first set of a left-recursive rule would never be computed.
+        None
+    }
+
+    fn describe(&self) -> GrammarNode {
+        describe::rule(self.name, || self.body.describe(*self))
+    }
+
+    fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        let _nested = limits::enter(tail)?;
+
+        self.body
+            .p_arse(tail, *self)
+            .map_err(|error: Error| error.push(self.name))
+    }
+}
+
+impl<'f, T> Leaf for Rule<'f, T> {}
+
+/// A named parser built by the closure from the parser itself, so that it
+/// can match itself recursively.
+///
+/// Unlike [`rec`], whose closure is code, a rule is described (see
+/// [`describe`]) as a [`GrammarNode::Rule`] the first time it appears and as
+/// a [`GrammarNode::Ref`] to it afterwards, including in its own body. The rules are told apart by their names. Like
+/// [`.named()`](Parser::named), the rule's name is added to the errors.
+///
+/// A closure capturing variables has to be bound to a variable first, since
+/// the rule borrows it. A closure without captures can be a constant, e.g. for
+/// mutually recursive rules.
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::{
+///     describe::GrammarNode::{self, *},
+///     rule,
+///     Parser,
+/// };
+///
+/// // Nested brackets, e.g. `[[][[]]]`.
+/// let body = |nested| ('[', nested, ']').ignore().zore().ignore();
+/// let nested = rule("nested", &body);
+///
+/// assert!(nested.p_arse("[[][[]]]").is_ok());
+/// assert_eq!(
+///     nested.describe(),
+///     Rule {
+///         name: "nested".into(),
+///         node: Box::new(GrammarNode::repeat(
+///             Sequence(vec![
+///                 Literal("[".into()),
+///                 Ref("nested".into()),
+///                 Literal("]".into()),
+///             ]),
+///             0,
+///             None,
+///         )),
+///     },
+/// );
+/// ```
+pub const fn rule<'f, T, F, P>(name: &'static str, body: &'f F) -> Rule<'f, T>
+where
+    F: Fn(Rule<'f, T>) -> P,
+    P: Parser<Output = T>,
+    T: 'f,
+{
+    Rule { name, body }
+}
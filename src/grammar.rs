@@ -0,0 +1,777 @@
+//! Grammars loaded at runtime from the PEG notation.
+//!
+//! The notation is the one from the [original paper](crate#reference) on
+//! PEGs. Each definition `Rule <- Expression` is followed by the next one or
+//! the end of the text, and the first rule is the start rule:
+//!
+//! | Expression   | Meaning                                                  |
+//! |--------------|----------------------------------------------------------|
+//! | `'abc'`      | literal, also `"abc"`, with escapes: `\n`, `\'`, `\101` |
+//! | `[a-z_]`     | character class                                          |
+//! | `'abc'i`     | case-insensitive literal, also `[a-z]i`                  |
+//! | `.`          | any character                                            |
+//! | `(e)`        | grouping                                                 |
+//! | `e?`         | optional                                                 |
+//! | `e*`         | zero or more                                             |
+//! | `e+`         | one or more                                              |
+//! | `&e`         | positive lookahead                                       |
+//! | `!e`         | negative lookahead                                       |
+//! | `e1 e2`      | sequence                                                 |
+//! | `e1 / e2`    | prioritized choice                                       |
+//!
+//! Comments start with `#` and run to the end of the line.
+//!
+//! The rules parse into a generic tree of [`Node`]s. Each node is a match of
+//! a rule, its children are the matches of the rules it referred to.
+//! Left-recursive rules are not supported.
+//!
+//! # Examples
+//!
+//! ```
+//! use p_arse::{grammar::Grammar, Parser};
+//!
+//! let grammar = Grammar::from_peg(
+//!     "
+//!     ## Sums of numbers.
+//!     Sum    <- Number ('+' Number)* !.
+//!     Number <- [0-9]+
+//!     ",
+//! )
+//! .unwrap();
+//!
+//! let (sum, _) = grammar.start().p_arse("1+20+300").unwrap();
+//! let numbers: Vec<_> = sum.children.iter().map(|n| &n.text[..]).collect();
+//!
+//! assert_eq!(sum.rule, "Sum");
+//! assert_eq!(numbers, vec!["1", "20", "300"]);
+//! assert!(grammar.start().p_arse("1+").is_err());
+//! ```
+
+use std::{collections::HashMap, fmt, iter::once, str::FromStr};
+
+use crate::{
+    case::no_case,
+    class::CharClass,
+    describe::GrammarNode,
+    function::fun,
+    lexeme::{lexer, line_comment, trivia, Lexer, LineComment, Trivia},
+    limits,
+    lint::{self, Lint},
+    numbers::octal,
+    seq,
+    sequence::Leaf,
+    utils::{any, satisfy, take_while},
+    CharExt,
+    Error,
+    Parser,
+    Result,
+};
+
+/// A parsing expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    /// `e1 / e2 / ...`
+    Choice(Vec<Expr>),
+    /// `e1 e2 ...`
+    Sequence(Vec<Expr>),
+    /// `&e`
+    And(Box<Expr>),
+    /// `!e`
+    Not(Box<Expr>),
+    /// `e?`
+    Optional(Box<Expr>),
+    /// `e*`
+    ZeroOrMore(Box<Expr>),
+    /// `e+`
+    OneOrMore(Box<Expr>),
+    /// `'abc'`
+    Literal(String),
+    /// `[a-z]`
+    Class(CharClass),
+    /// `'abc'i`, `[a-z]i`: the literal or the class, matched
+    /// case-insensitively like [`no_case`] does.
+    NoCase(Box<Expr>),
+    /// `.`
+    Any,
+    /// A reference to a rule.
+    Rule(String),
+}
+
+/// A grammar: a list of named rules, the first of which is the start rule.
+#[derive(Debug, Clone)]
+pub struct Grammar {
+    rules: Vec<(String, Expr)>,
+    index: HashMap<String, usize>,
+}
+
+/// An error in the text of a grammar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrammarError {
+    pub message: String,
+    /// The line of the error, starting from 1.
+    pub line: usize,
+    /// The column of the error in characters, starting from 1.
+    pub column: usize,
+}
+
+impl GrammarError {
+    fn new(message: String, text: &str, tail: &str) -> Self {
+        let before = &text[..text.len() - tail.len()];
+        let line = before.matches('\n').count() + 1;
+        let column = before.chars().rev().take_while(|&c| c != '\n').count();
+
+        GrammarError {
+            message,
+            line,
+            column: column + 1,
+        }
+    }
+}
+
+impl fmt::Display for GrammarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for GrammarError {}
+
+/// A match of a rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node<'g> {
+    pub rule: &'g str,
+    /// The matched input.
+    pub text: String,
+    /// The matches of the rules referred to, in the order of the input.
+    pub children: Vec<Node<'g>>,
+}
+
+/// A [`Parser`] matching a rule of a [`Grammar`], returned by
+/// [`Grammar::rule`] and [`Grammar::start`].
+#[derive(Copy, Clone)]
+pub struct RuleRef<'g> {
+    grammar: &'g Grammar,
+    index: usize,
+}
+
+// The parser of the notation.
+
+fn lx() -> Lexer<Trivia<LineComment<'static>>> {
+    lexer(trivia(line_comment("#")))
+}
+
+fn identifier<'a>(tail: &'a str) -> Result<'a, String> {
+    let start = satisfy(|c| c.is_ascii_alphabetic() || c == '_');
+    let rest = take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    lx().lexeme((start, rest).maps(|s| s.to_string()))
+        .p_arse(tail)
+}
+
+fn character<'a>(tail: &'a str) -> Result<'a, char> {
+    let escaped = seq!(~'\\', satisfy(|c| "nrt'\"[]\\".contains(c)));
+    let escaped = escaped.map(|c| match c {
+        'n' => '\n',
+        'r' => '\r',
+        't' => '\t',
+        c => c,
+    });
+    let octal = seq!(~'\\', octal::<u32>().digits(1, 3));
+    let octal = octal.map(|code| char::from_u32(code).unwrap());
+    let plain = seq!(~'\\'.not_ahead(), any());
+
+    escaped.or(octal).or(plain).p_arse(tail)
+}
+
+// The literal or the class, case-insensitive if directly followed by `i`.
+fn caseless<P>(expr: P) -> impl Parser<Output = Expr>
+where
+    P: Parser<Output = Expr>,
+{
+    let identifier = satisfy(|c| c.is_ascii_alphanumeric() || c == '_');
+    let suffix = ('i', identifier.not_ahead()).opt();
+
+    (expr, suffix).map(|(expr, suffix)| match suffix {
+        Some(_) => Expr::NoCase(Box::new(expr)),
+        None => expr,
+    })
+}
+
+fn literal<'a>(tail: &'a str) -> Result<'a, Expr> {
+    let quoted = |quote: char| {
+        let content = seq!(~quote.not_ahead(), fun(&character)).zore();
+
+        seq!(~quote, content, ~quote)
+    };
+    let literal = quoted('\'').or(quoted('"'));
+
+    let literal =
+        literal.map(|chars| Expr::Literal(chars.into_iter().collect()));
+
+    lx().lexeme(caseless(literal)).p_arse(tail)
+}
+
+fn class<'a>(tail: &'a str) -> Result<'a, Expr> {
+    let bound = fun(&character);
+    let to = seq!(~'-', ~']'.not_ahead(), bound).opt();
+    let range = (bound, to).map(|(from, to)| (from, to.unwrap_or(from)));
+    let class = seq!(~'[', seq!(~']'.not_ahead(), range).zore(), ~']');
+    let class = class.map(|ranges| Expr::Class(CharClass::from_ranges(ranges)));
+
+    lx().lexeme(caseless(class)).p_arse(tail)
+}
+
+fn primary<'a>(tail: &'a str) -> Result<'a, Expr> {
+    let lx = lx();
+    let rule = seq!(fun(&identifier), ~lx.lexeme("<-").not_ahead());
+    let group = seq!(~lx.lexeme('('), fun(&expression), ~lx.lexeme(')'));
+
+    rule.map(Expr::Rule)
+        .or(group)
+        .or(fun(&literal))
+        .or(fun(&class))
+        .or(lx.lexeme('.').map(|_| Expr::Any))
+        .p_arse(tail)
+}
+
+fn suffix<'a>(tail: &'a str) -> Result<'a, Expr> {
+    let lx = lx();
+    let operator = lx.lexeme('?').or(lx.lexeme('*')).or(lx.lexeme('+'));
+    let suffix = (fun(&primary), operator.opt());
+
+    suffix
+        .map(|(expr, operator)| match operator {
+            Some('?') => Expr::Optional(Box::new(expr)),
+            Some('*') => Expr::ZeroOrMore(Box::new(expr)),
+            Some('+') => Expr::OneOrMore(Box::new(expr)),
+            _ => expr,
+        })
+        .p_arse(tail)
+}
+
+fn prefix<'a>(tail: &'a str) -> Result<'a, Expr> {
+    let lx = lx();
+    let operator = lx.lexeme('&').or(lx.lexeme('!'));
+    let prefix = (operator.opt(), fun(&suffix));
+
+    prefix
+        .map(|(operator, expr)| match operator {
+            Some('&') => Expr::And(Box::new(expr)),
+            Some('!') => Expr::Not(Box::new(expr)),
+            _ => expr,
+        })
+        .p_arse(tail)
+}
+
+fn sequence<'a>(tail: &'a str) -> Result<'a, Expr> {
+    fun(&prefix)
+        .zore()
+        .map(|mut exprs| match exprs.len() {
+            1 => exprs.pop().unwrap(),
+            _ => Expr::Sequence(exprs),
+        })
+        .p_arse(tail)
+}
+
+fn expression<'a>(tail: &'a str) -> Result<'a, Expr> {
+    let rest = seq!(~lx().lexeme('/'), fun(&sequence)).zore();
+
+    (fun(&sequence), rest)
+        .map(|(first, rest)| match rest.is_empty() {
+            true => first,
+            false => Expr::Choice(once(first).chain(rest).collect()),
+        })
+        .p_arse(tail)
+}
+
+fn definition<'a>(tail: &'a str) -> Result<'a, (String, Expr)> {
+    seq!(fun(&identifier), ~lx().lexeme("<-"), fun(&expression)).p_arse(tail)
+}
+
+// The error at the definition which failed to parse, in the terms of the
+// notation. The expressions can be empty, so a definition fails on what's
+// left of the previous one: a malformed token, a stray character or a rule
+// name not followed by `<-`.
+fn syntax_error(tail: &str) -> (String, &str) {
+    if let Some(error) = token_error(tail) {
+        return error;
+    }
+
+    match fun(&identifier).p_arse(tail) {
+        Ok((_, rest)) => ("expected `<-`".into(), rest),
+        Err(_) => match tail.chars().next() {
+            Some(c) => (format!("unexpected `{}`", c), tail),
+            None => ("expected a rule".into(), tail),
+        },
+    }
+}
+
+// The error in the malformed token at the start of the tail, if any.
+fn token_error(tail: &str) -> Option<(String, &str)> {
+    let lx = lx();
+
+    match tail.chars().next()? {
+        quote @ ('\'' | '"') => unterminated(tail, quote, "literal"),
+        '[' => unterminated(tail, ']', "class"),
+        '(' if fun(&primary).p_arse(tail).is_err() => {
+            let (_, rest) = lx.lexeme('(').p_arse(tail).ok()?;
+            let (_, rest) = fun(&expression).p_arse(rest).ok()?;
+
+            token_error(rest).or_else(|| Some(("expected `)`".into(), rest)))
+        }
+        operator @ ('&' | '!') if fun(&prefix).p_arse(tail).is_err() => {
+            let (_, rest) = lx.lexeme(operator).p_arse(tail).ok()?;
+            let expected =
+                format!("expected an expression after `{}`", operator);
+
+            token_error(rest).or(Some((expected, rest)))
+        }
+        _ => None,
+    }
+}
+
+// The error in the literal or the class at the start of the tail, if it
+// isn't closed by `close`.
+fn unterminated<'a>(
+    tail: &'a str,
+    close: char,
+    what: &str,
+) -> Option<(String, &'a str)> {
+    let mut rest = &tail[1..];
+
+    while !rest.starts_with(close) {
+        match character(rest) {
+            Ok((_, next)) => rest = next,
+            Err(_) if rest.is_empty() => {
+                return Some((format!("unterminated {}", what), tail))
+            }
+            Err(_) => return Some(("invalid escape".into(), rest)),
+        }
+    }
+
+    None
+}
+
+impl Grammar {
+    /// Parses the text of a grammar, checking that the rules are unique, all
+    /// the referred rules are defined and none of them is left-recursive (see
+    /// [`Lint::LeftRecursion`]), which would never stop.
+    pub fn from_peg(text: &str) -> std::result::Result<Self, GrammarError> {
+        let syntax_error = |tail| {
+            let (message, position) = syntax_error(tail);
+
+            GrammarError::new(message, text, position)
+        };
+        let (_, mut tail) =
+            lx().trivia().p_arse(text).map_err(|_| syntax_error(text))?;
+        let mut grammar = Grammar {
+            rules: vec![],
+            index: HashMap::new(),
+        };
+        let mut positions = vec![];
+
+        if tail.is_empty() {
+            return Err(GrammarError::new("no rules".into(), text, tail));
+        }
+
+        while !tail.is_empty() {
+            let ((name, expr), rest) = fun(&definition)
+                .p_arse(tail)
+                .map_err(|_| syntax_error(tail))?;
+
+            if grammar.index.contains_key(&name) {
+                let message = format!("rule {} is already defined", name);
+
+                return Err(GrammarError::new(message, text, tail));
+            }
+
+            grammar.index.insert(name.clone(), grammar.rules.len());
+            grammar.rules.push((name, expr));
+            positions.push(tail);
+            tail = rest;
+        }
+
+        for ((_, expr), position) in grammar.rules.iter().zip(&positions) {
+            if let Some(name) = grammar.undefined(expr) {
+                let message = format!("rule {} is not defined", name);
+
+                return Err(GrammarError::new(message, text, position));
+            }
+        }
+
+        if let Some(rules) = lint::left_recursion(grammar.described()) {
+            let position = positions[grammar.index[&rules[0]]];
+            let message = Lint::LeftRecursion { rules }.to_string();
+
+            return Err(GrammarError::new(message, text, position));
+        }
+
+        Ok(grammar)
+    }
+
+    // The rules described as trees.
+    pub(crate) fn described(&self) -> Vec<(String, GrammarNode)> {
+        self.rules()
+            .map(|(name, expr)| (name.to_string(), expr.describe()))
+            .collect()
+    }
+
+    // The first rule referred to by the expression which is not defined.
+    fn undefined<'e>(&self, expr: &'e Expr) -> Option<&'e str> {
+        match expr {
+            Expr::Choice(exprs) | Expr::Sequence(exprs) => {
+                exprs.iter().find_map(|expr| self.undefined(expr))
+            }
+            Expr::And(expr)
+            | Expr::Not(expr)
+            | Expr::Optional(expr)
+            | Expr::ZeroOrMore(expr)
+            | Expr::OneOrMore(expr) => self.undefined(expr),
+            Expr::Rule(name) if !self.index.contains_key(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// The rules in the order of their definitions.
+    pub fn rules(&self) -> impl Iterator<Item = (&str, &Expr)> {
+        self.rules.iter().map(|(name, expr)| (name.as_str(), expr))
+    }
+
+    pub fn rule(&self, name: &str) -> Option<RuleRef<'_>> {
+        let index = *self.index.get(name)?;
+
+        Some(RuleRef {
+            grammar: self,
+            index,
+        })
+    }
+
+    /// The first rule.
+    pub fn start(&self) -> RuleRef<'_> {
+        RuleRef {
+            grammar: self,
+            index: 0,
+        }
+    }
+
+    // Matches the expression, appending the matches of the rules to
+    // `children`.
+    fn eval<'a, 'g>(
+        &'g self,
+        expr: &'g Expr,
+        tail: &'a str,
+        children: &mut Vec<Node<'g>>,
+    ) -> Result<'a, ()> {
+        match expr {
+            Expr::Choice(exprs) => {
+                let len = children.len();
+                let mut result = Ok(((), tail));
+
+                for expr in exprs {
+                    limits::step(tail)?;
+                    result = self.eval(expr, tail, children);
+
+                    if result.is_ok() {
+                        break;
+                    }
+
+                    children.truncate(len);
+                }
+
+                result
+            }
+            Expr::Sequence(exprs) => {
+                let mut tail = tail;
+
+                for expr in exprs {
+                    tail = self.eval(expr, tail, children)?.1;
+                }
+
+                Ok(((), tail))
+            }
+            Expr::And(expr) => {
+                self.eval(expr, tail, &mut vec![])?;
+
+                Ok(((), tail))
+            }
+            Expr::Not(expr) => match self.eval(expr, tail, &mut vec![]) {
+                Ok(_) => Err(Error::expecting(format!("not {}", expr), tail)),
+                Err(_) => Ok(((), tail)),
+            },
+            Expr::Optional(expr) => {
+                let len = children.len();
+
+                self.eval(expr, tail, children).or_else(|_| {
+                    children.truncate(len);
+
+                    Ok(((), tail))
+                })
+            }
+            Expr::ZeroOrMore(expr) => self.eval_zore(expr, 0, tail, children),
+            Expr::OneOrMore(expr) => {
+                let (_, rest) = self.eval(expr, tail, children)?;
+                limits::repetition(1, tail)?;
+
+                self.eval_zore(expr, 1, rest, children)
+            }
+            Expr::Literal(literal) => {
+                literal.as_str().p_arse(tail).map(|(_, tail)| ((), tail))
+            }
+            Expr::Class(class) => {
+                class.p_arse(tail).map(|(_, tail)| ((), tail))
+            }
+            Expr::NoCase(caseless) => match &**caseless {
+                Expr::Literal(literal) => no_case(literal.as_str())
+                    .p_arse(tail)
+                    .map(|(_, tail)| ((), tail)),
+                Expr::Class(class) => class
+                    .ranges()
+                    .into_iter()
+                    .find_map(|(from, to)| {
+                        no_case(from.to(to)).p_arse(tail).ok()
+                    })
+                    .map(|(_, tail)| ((), tail))
+                    .ok_or_else(|| Error::expecting(expr.to_string(), tail)),
+                // The notation only has case-insensitive literals and classes.
+                caseless => self.eval(caseless, tail, children),
+            },
+            Expr::Any => any().p_arse(tail).map(|(_, tail)| ((), tail)),
+            Expr::Rule(name) => {
+                let (node, tail) = self.rule(name).unwrap().p_arse(tail)?;
+                children.push(node);
+
+                Ok(((), tail))
+            }
+        }
+    }
+
+    // Matches the expression repeatedly, after `count` matches so far.
+    fn eval_zore<'a, 'g>(
+        &'g self,
+        expr: &'g Expr,
+        mut count: usize,
+        mut tail: &'a str,
+        children: &mut Vec<Node<'g>>,
+    ) -> Result<'a, ()> {
+        loop {
+            let len = children.len();
+
+            match self.eval(expr, tail, children) {
+                // Stops on matching nothing as well.
+                Ok((_, rest)) if rest.len() < tail.len() => {
+                    count += 1;
+                    limits::repetition(count, tail)?;
+                    tail = rest;
+                }
+                Ok(_) => return Ok(((), tail)),
+                Err(_) => {
+                    children.truncate(len);
+
+                    return Ok(((), tail));
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for Grammar {
+    type Err = GrammarError;
+
+    fn from_str(text: &str) -> std::result::Result<Self, Self::Err> {
+        Grammar::from_peg(text)
+    }
+}
+
+// Escapes the character in a literal or a class.
+fn escape(c: char, special: &str) -> String {
+    match c {
+        '\n' => "\\n".into(),
+        '\r' => "\\r".into(),
+        '\t' => "\\t".into(),
+        c if special.contains(c) => format!("\\{}", c),
+        c if c.is_control() && (c as u32) < 0o300 => {
+            format!("\\{:03o}", c as u32)
+        }
+        c => c.to_string(),
+    }
+}
+
+// The literal in the PEG notation, e.g. `'a\'b'`.
+pub(crate) fn peg_literal(literal: &str) -> String {
+    let escaped: String = literal.chars().map(|c| escape(c, "'\\")).collect();
+
+    format!("'{}'", escaped)
+}
+
+// The class in the PEG notation, e.g. `[a-z_]`.
+pub(crate) fn peg_class(ranges: &[(char, char)]) -> String {
+    // `-` can't be escaped with a backslash.
+    let escape = |c| match c {
+        '-' => "\\055".to_string(),
+        c => escape(c, "[]\\"),
+    };
+    let mut class = "[".to_string();
+
+    for &(from, to) in ranges {
+        match to as u32 - from as u32 {
+            0 => class += &escape(from),
+            1 => class += &format!("{}{}", escape(from), escape(to)),
+            _ => class += &format!("{}-{}", escape(from), escape(to)),
+        }
+    }
+    class.push(']');
+
+    class
+}
+
+impl Expr {
+    /// The structure of the expression, with the rules referenced by
+    /// [`GrammarNode::Ref`].
+    pub fn describe(&self) -> GrammarNode {
+        match self {
+            Expr::Choice(exprs) => {
+                GrammarNode::choice(exprs.iter().map(Expr::describe))
+            }
+            Expr::Sequence(exprs) => {
+                GrammarNode::sequence(exprs.iter().map(Expr::describe))
+            }
+            Expr::And(expr) => GrammarNode::lookahead(expr.describe(), true),
+            Expr::Not(expr) => GrammarNode::lookahead(expr.describe(), false),
+            Expr::Optional(expr) => {
+                GrammarNode::repeat(expr.describe(), 0, Some(1))
+            }
+            Expr::ZeroOrMore(expr) => {
+                GrammarNode::repeat(expr.describe(), 0, None)
+            }
+            Expr::OneOrMore(expr) => {
+                GrammarNode::repeat(expr.describe(), 1, None)
+            }
+            Expr::Literal(literal) => GrammarNode::Literal(literal.clone()),
+            Expr::Class(class) => GrammarNode::Class(class.ranges()),
+            Expr::NoCase(expr) => {
+                GrammarNode::NoCase(Box::new(expr.describe()))
+            }
+            Expr::Any => GrammarNode::Any,
+            Expr::Rule(name) => GrammarNode::Ref(name.clone()),
+        }
+    }
+
+    // The binding power of the operator, the primary expressions bind
+    // strongest.
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Choice(_) => 0,
+            Expr::Sequence(exprs) if exprs.len() > 1 => 1,
+            Expr::And(_) | Expr::Not(_) => 2,
+            Expr::Optional(_) | Expr::ZeroOrMore(_) | Expr::OneOrMore(_) => 3,
+            _ => 4,
+        }
+    }
+
+    fn fmt_at(
+        &self,
+        precedence: u8,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        if self.precedence() < precedence {
+            write!(f, "(")?;
+            self.fmt_at(0, f)?;
+
+            return write!(f, ")");
+        }
+
+        let join = |f: &mut fmt::Formatter<'_>,
+                    exprs: &[Expr],
+                    separator,
+                    precedence| {
+            for (i, expr) in exprs.iter().enumerate() {
+                if i > 0 {
+                    write!(f, "{}", separator)?;
+                }
+                expr.fmt_at(precedence, f)?;
+            }
+
+            Ok(())
+        };
+
+        match self {
+            Expr::Choice(exprs) => join(f, exprs, " / ", 1),
+            Expr::Sequence(exprs) if exprs.is_empty() => write!(f, "''"),
+            Expr::Sequence(exprs) => join(f, exprs, " ", 2),
+            Expr::And(expr) => write!(f, "&").and_then(|_| expr.fmt_at(3, f)),
+            Expr::Not(expr) => write!(f, "!").and_then(|_| expr.fmt_at(3, f)),
+            Expr::Optional(expr) => {
+                expr.fmt_at(4, f).and_then(|_| write!(f, "?"))
+            }
+            Expr::ZeroOrMore(expr) => {
+                expr.fmt_at(4, f).and_then(|_| write!(f, "*"))
+            }
+            Expr::OneOrMore(expr) => {
+                expr.fmt_at(4, f).and_then(|_| write!(f, "+"))
+            }
+            Expr::Literal(literal) => write!(f, "{}", peg_literal(literal)),
+            Expr::Class(class) => write!(f, "{}", peg_class(&class.ranges())),
+            Expr::NoCase(expr) => {
+                expr.fmt_at(4, f).and_then(|_| write!(f, "i"))
+            }
+            Expr::Any => write!(f, "."),
+            Expr::Rule(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Formats the expression in the PEG notation.
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_at(0, f)
+    }
+}
+
+/// Formats the grammar in the PEG notation, one rule per line.
+impl fmt::Display for Grammar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.rules().map(|(name, _)| name.len()).max().unwrap_or(0);
+
+        for (name, expr) in self.rules() {
+            writeln!(f, "{:width$} <- {}", name, expr, width = width)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'g> RuleRef<'g> {
+    pub fn name(&self) -> &'g str {
+        &self.grammar.rules[self.index].0
+    }
+
+    pub fn expr(&self) -> &'g Expr {
+        &self.grammar.rules[self.index].1
+    }
+}
+
+impl<'g> Parser for RuleRef<'g> {
+    type Output = Node<'g>;
+
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::Rule {
+            name: self.name().to_string(),
+            node: Box::new(self.expr().describe()),
+        }
+    }
+
+    fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        let _nested = limits::enter(tail)?;
+        let mut children = vec![];
+        let (_, rest) = self.grammar.eval(self.expr(), tail, &mut children)?;
+        let node = Node {
+            rule: self.name(),
+            text: tail[..tail.len() - rest.len()].to_string(),
+            children,
+        };
+
+        Ok((node, rest))
+    }
+}
+
+impl Leaf for RuleRef<'_> {}
@@ -0,0 +1,469 @@
+//! Helpers for whitespace-insensitive grammars.
+//!
+//! A lexeme is a parser followed by trivia, i.e. whitespace and comments.
+//! If every token of a grammar skips the trivia following it, the only other
+//! place the trivia has to be skipped is the beginning of the input.
+//!
+//! # Examples
+//!
+//! ```
+//! use p_arse::{
+//!     lexeme::{block_comment, lexer, line_comment, trivia},
+//!     CharExt,
+//!     Parser,
+//! };
+//!
+//! let comment = line_comment("//").or(block_comment("/*", "*/"));
+//! let lx = lexer(trivia(comment));
+//!
+//! let ident = lx.lexeme('a'.to('z').more().maps(|s| s.to_string()));
+//! let number = lx.lexeme('0'.to('9').more().maps(|s| s.parse().unwrap()));
+//! let assignment =
+//!     (lx.trivia(), lx.keyword("let"), ident, lx.lexeme('='), number);
+//!
+//! let input = "// answer\nlet /* the */ x = 42 // to everything";
+//! let ((_, _, x, _, n), tail) = assignment.p_arse(input).unwrap();
+//! assert_eq!((x.as_str(), n, tail), ("x", 42, ""));
+//!
+//! // `let` is not a keyword here.
+//! assert!(assignment.p_arse("letx = 42").is_err());
+//! ```
+
+use crate::{
+    describe::GrammarNode,
+    search::FirstSet,
+    sequence::Leaf,
+    Error,
+    Parser,
+    Result,
+};
+
+/// A [`Parser`] skipping zero or more whitespace characters, returned by
+/// [`ws`].
+#[derive(Copy, Clone)]
+pub struct Whitespace;
+
+/// Skips zero or more whitespace characters, as defined by
+/// [`char::is_whitespace`]. It's the default trivia of
+/// [`.lexeme()`](Parser::lexeme) and [`keyword`].
+pub fn ws() -> Whitespace {
+    Whitespace
+}
+
+impl Parser for Whitespace {
+    type Output = ();
+
+    fn describe(&self) -> GrammarNode {
+        let c = GrammarNode::Opaque("whitespace".to_string());
+
+        GrammarNode::repeat(c, 0, None)
+    }
+
+    fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        Ok(((), tail.trim_start()))
+    }
+}
+
+impl Leaf for Whitespace {}
+
+/// A [`Parser`] skipping a comment running to the end of the line, returned
+/// by [`line_comment`].
+#[derive(Copy, Clone)]
+pub struct LineComment<'s> {
+    start: &'s str,
+}
+
+/// Skips a comment starting with `start` and running to the end of the line.
+/// The line break is not a part of the comment.
+pub fn line_comment(start: &str) -> LineComment<'_> {
+    LineComment { start }
+}
+
+impl Parser for LineComment<'_> {
+    type Output = ();
+
+    fn first_set(&self) -> Option<FirstSet> {
+        self.start.first_set()
+    }
+
+    fn describe(&self) -> GrammarNode {
+        let not_nl =
+            GrammarNode::Class(vec![('\0', '\t'), ('\u{B}', char::MAX)]);
+
+        GrammarNode::sequence(vec![
+            self.start.describe(),
+            GrammarNode::repeat(not_nl, 0, None),
+        ])
+    }
+
+    fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        let comment = tail.strip_prefix(self.start).ok_or_else(|| {
+            Error::expecting(format!("comment '{}'", self.start), tail)
+        })?;
+        let end = comment.find('\n').unwrap_or(comment.len());
+
+        Ok(((), &comment[end..]))
+    }
+}
+
+impl Leaf for LineComment<'_> {}
+
+/// A [`Parser`] skipping a delimited comment, returned by [`block_comment`].
+#[derive(Copy, Clone)]
+pub struct BlockComment<'s> {
+    open: &'s str,
+    close: &'s str,
+    nested: bool,
+}
+
+/// Skips a comment delimited by `open` and `close`. Fails if the comment is
+/// not closed.
+pub fn block_comment<'s>(open: &'s str, close: &'s str) -> BlockComment<'s> {
+    BlockComment {
+        open,
+        close,
+        nested: false,
+    }
+}
+
+/// Like [`block_comment`], but the comments can be nested, like in Rust.
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::{lexeme::nested_block_comment, Parser};
+///
+/// let comment = nested_block_comment("/*", "*/");
+/// assert_eq!(comment.p_arse("/* a /* b */ c */d").unwrap().1, "d");
+/// assert!(comment.p_arse("/* a /* b */ c").is_err());
+/// ```
+pub fn nested_block_comment<'s>(
+    open: &'s str,
+    close: &'s str,
+) -> BlockComment<'s> {
+    BlockComment {
+        open,
+        close,
+        nested: true,
+    }
+}
+
+impl Parser for BlockComment<'_> {
+    type Output = ();
+
+    fn first_set(&self) -> Option<FirstSet> {
+        self.open.first_set()
+    }
+
+    fn describe(&self) -> GrammarNode {
+        if self.nested {
+            return GrammarNode::Opaque(format!(
+                "nested comment '{}' ... '{}'",
+                self.open, self.close
+            ));
+        }
+
+        let c = GrammarNode::sequence(vec![
+            GrammarNode::lookahead(self.close.describe(), false),
+            GrammarNode::Any,
+        ]);
+
+        GrammarNode::sequence(vec![
+            self.open.describe(),
+            GrammarNode::repeat(c, 0, None),
+            self.close.describe(),
+        ])
+    }
+
+    fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        let mut comment = tail.strip_prefix(self.open).ok_or_else(|| {
+            Error::expecting(format!("comment '{}'", self.open), tail)
+        })?;
+        let mut depth = 1;
+
+        while depth > 0 {
+            if let Some(rest) = comment.strip_prefix(self.close) {
+                depth -= 1;
+                comment = rest;
+            } else if let Some(rest) =
+                comment.strip_prefix(self.open).filter(|_| self.nested)
+            {
+                depth += 1;
+                comment = rest;
+            } else {
+                let mut chars = comment.chars();
+                chars.next().ok_or_else(|| {
+                    Error::expecting(format!("'{}'", self.close), comment)
+                })?;
+                comment = chars.as_str();
+            }
+        }
+
+        Ok(((), comment))
+    }
+}
+
+impl Leaf for BlockComment<'_> {}
+
+/// A [`Parser`] skipping whitespace and comments, returned by [`trivia`].
+#[derive(Copy, Clone)]
+pub struct Trivia<C, W = Whitespace>
+where
+    C: Parser,
+    W: Parser,
+{
+    comment: C,
+    whitespace: W,
+}
+
+/// Skips any sequence of whitespace and comments.
+pub fn trivia<C: Parser>(comment: C) -> Trivia<C> {
+    Trivia {
+        comment,
+        whitespace: ws(),
+    }
+}
+
+impl<C, W> Trivia<C, W>
+where
+    C: Parser,
+    W: Parser,
+{
+    /// Replaces the parser skipping whitespace, [`ws`] by default, e.g. to
+    /// keep significant line breaks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use p_arse::{
+    ///     lexeme::{lexer, line_comment, trivia},
+    ///     take_while,
+    ///     Parser,
+    /// };
+    ///
+    /// let blanks = take_while(|c| c == ' ' || c == '\t');
+    /// let lx = lexer(trivia(line_comment("#")).whitespace(blanks));
+    /// let line = (lx.lexeme("x"), lx.lexeme('\n'));
+    ///
+    /// assert_eq!(line.p_arse("x # comment\n y").unwrap().1, "y");
+    /// assert!(line.p_arse("x \n\n").is_ok());
+    /// assert!(line.p_arse("x # comment").is_err());
+    /// ```
+    pub fn whitespace<V: Parser>(self, whitespace: V) -> Trivia<C, V> {
+        Trivia {
+            comment: self.comment,
+            whitespace,
+        }
+    }
+}
+
+impl<C, W> Parser for Trivia<C, W>
+where
+    C: Parser,
+    W: Parser,
+{
+    type Output = ();
+
+    fn describe(&self) -> GrammarNode {
+        let comment = GrammarNode::sequence(vec![
+            self.comment.describe(),
+            self.whitespace.describe(),
+        ]);
+
+        GrammarNode::sequence(vec![
+            self.whitespace.describe(),
+            GrammarNode::repeat(comment, 0, None),
+        ])
+    }
+
+    fn p_arse<'a>(&self, mut tail: &'a str) -> Result<'a, Self::Output> {
+        loop {
+            if let Ok((_, rest)) = self.whitespace.p_arse(tail) {
+                tail = rest;
+            }
+
+            match self.comment.p_arse(tail) {
+                // Stops on comments matching nothing as well.
+                Ok((_, rest)) if rest.len() < tail.len() => tail = rest,
+                _ => return Ok(((), tail)),
+            }
+        }
+    }
+}
+
+impl<C, W> Leaf for Trivia<C, W>
+where
+    C: Parser,
+    W: Parser,
+{
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// A configuration of lexemes and keywords: the trivia skipped after them
+/// and the characters forming words.
+#[derive(Copy, Clone)]
+pub struct Lexer<T>
+where
+    T: Parser,
+{
+    trivia: T,
+    is_word_char: fn(char) -> bool,
+}
+
+/// Creates a [`Lexer`] skipping the given trivia, e.g. [`ws`] or [`trivia`].
+/// The words consist of alphanumeric characters and underscores.
+pub fn lexer<T: Parser>(trivia: T) -> Lexer<T> {
+    Lexer {
+        trivia,
+        is_word_char,
+    }
+}
+
+impl<T> Lexer<T>
+where
+    T: Parser,
+{
+    /// Sets the characters which can't follow a keyword.
+    pub fn word_chars(self, is_word_char: fn(char) -> bool) -> Self {
+        Lexer {
+            is_word_char,
+            ..self
+        }
+    }
+
+    /// The trivia, to be skipped at the beginning of the input.
+    pub fn trivia(&self) -> T {
+        self.trivia
+    }
+
+    /// Matches the parser and skips the trivia following it.
+    pub fn lexeme<P: Parser>(&self, parser: P) -> Lexeme<P, T> {
+        Lexeme {
+            parser,
+            trivia: self.trivia,
+        }
+    }
+
+    /// Matches the word, unless it's followed by a word character, and skips
+    /// the trivia following it.
+    pub fn keyword<'k>(&self, word: &'k str) -> Keyword<'k, T> {
+        Keyword {
+            word,
+            trivia: self.trivia,
+            is_word_char: self.is_word_char,
+        }
+    }
+}
+
+/// A [`Parser`] skipping the trivia following the parser, returned by
+/// [`.lexeme()`](Parser::lexeme) and [`Lexer::lexeme`].
+#[derive(Copy, Clone)]
+pub struct Lexeme<P, T>
+where
+    P: Parser,
+    T: Parser,
+{
+    parser: P,
+    trivia: T,
+}
+
+impl<P, T> Parser for Lexeme<P, T>
+where
+    P: Parser,
+    T: Parser,
+{
+    type Output = P::Output;
+
+    fn first_set(&self) -> Option<FirstSet> {
+        self.parser.first_set()
+    }
+
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::sequence(vec![
+            self.parser.describe(),
+            self.trivia.describe(),
+        ])
+    }
+
+    fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        let (output, tail) = self.parser.p_arse(tail)?;
+        let (_, tail) = self.trivia.p_arse(tail)?;
+
+        Ok((output, tail))
+    }
+}
+
+impl<P, T> Leaf for Lexeme<P, T>
+where
+    P: Parser,
+    T: Parser,
+{
+}
+
+/// A [`Parser`] matching a whole word, returned by [`keyword`] and
+/// [`Lexer::keyword`].
+#[derive(Copy, Clone)]
+pub struct Keyword<'k, T>
+where
+    T: Parser,
+{
+    word: &'k str,
+    trivia: T,
+    is_word_char: fn(char) -> bool,
+}
+
+/// Matches the word, unless it's followed by an alphanumeric character or an
+/// underscore, and skips the whitespace following it.
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::{lexeme::keyword, Parser};
+///
+/// let if_ = keyword("if");
+/// assert_eq!(if_.p_arse("if  x").unwrap(), ("if", "x"));
+/// assert_eq!(if_.p_arse("if(x)").unwrap(), ("if", "(x)"));
+/// assert!(if_.p_arse("iffy").is_err());
+/// assert!(if_.p_arse("if_x").is_err());
+/// ```
+pub fn keyword(word: &str) -> Keyword<'_, Whitespace> {
+    lexer(ws()).keyword(word)
+}
+
+impl<'k, T> Parser for Keyword<'k, T>
+where
+    T: Parser,
+{
+    type Output = &'k str;
+
+    fn first_set(&self) -> Option<FirstSet> {
+        self.word.first_set()
+    }
+
+    fn describe(&self) -> GrammarNode {
+        let word_char = GrammarNode::Opaque("word char".to_string());
+
+        GrammarNode::sequence(vec![
+            self.word.describe(),
+            GrammarNode::lookahead(word_char, false),
+            self.trivia.describe(),
+        ])
+    }
+
+    fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        let rest = tail
+            .strip_prefix(self.word)
+            .filter(|rest| !rest.starts_with(self.is_word_char))
+            .ok_or_else(|| {
+                Error::expecting(format!("keyword '{}'", self.word), tail)
+            })?;
+        let (_, rest) = self.trivia.p_arse(rest)?;
+
+        Ok((self.word, rest))
+    }
+}
+
+impl<T> Leaf for Keyword<'_, T> where T: Parser {}
@@ -68,19 +68,53 @@
 //! IENY
 //! ```
 
+pub mod case;
+pub mod class;
+pub mod describe;
+pub mod diagram;
 pub mod error;
+pub mod export;
 pub mod function;
+pub mod grammar;
+pub mod lexeme;
+pub mod limits;
+pub mod lint;
 pub mod literal;
+mod macros;
+pub mod numbers;
+pub mod parse;
 pub mod parser;
+pub mod pattern;
+pub mod search;
 pub mod sequence;
+pub mod state;
+pub mod strings;
+#[cfg(feature = "unicode")]
+pub mod unicode;
 pub mod utils;
 pub mod wrapper;
 
 pub use crate::{
+    case::{no_case, no_case_ascii},
+    class::{none_of, one_of, CharClass},
     error::{Error, Result},
-    function::{fun, rec, Fun},
-    literal::CharExt,
+    function::{fun, rec, rule, Fun},
+    literal::{literals, literals_map, CharExt},
+    parse::{parse, Parse},
     parser::Parser,
     sequence::TupleExt,
-    utils::{any, eoi},
+    utils::{
+        any,
+        eoi,
+        satisfy,
+        take_till,
+        take_until,
+        take_while,
+        take_while1,
+    },
+    wrapper::Either,
 };
+
+/// Derives [`Parse`], see [`parse`](mod@parse).
+#[cfg(feature = "derive")]
+pub use p_arse_macros::Parse;
@@ -0,0 +1,299 @@
+//! Limits of the resources used by parsing.
+//!
+//! Parsing untrusted input, deeply nested input can overflow the stack of
+//! recursive parsers and backtracking can take a very long time.
+//! [`Parser::p_arse_limited`] runs a parser within [`Limits`] of:
+//!
+//! - the depth, i.e. the nesting of [`fun`](crate::fun),
+//!   [`rec`](crate::rec) and [`Grammar`](crate::grammar::Grammar) rule
+//!   parsers,
+//! - the steps, i.e. the calls of these parsers, the alternatives tried by
+//!   [`.or()`](Parser::or) and the grammars, and the iterations of
+//!   repetitions,
+//! - the length of the input, in bytes,
+//! - the matches of a single repetition.
+//!
+//! Exceeding a limit fails the parsing with an [`Error`] telling which limit
+//! it was (see [`Error::limit_exceeded`]). The error can't be recovered from:
+//! once a limit is exceeded, the parsers checking the limits fail, and so
+//! does the whole parsing, even if a parser (e.g. [`.opt()`](Parser::opt))
+//! ignores the error.
+//!
+//! # Examples
+//!
+//! ```
+//! use p_arse::{limits::{LimitExceeded, Limits}, rec, Parser};
+//!
+//! // Nested brackets, e.g. `[[][[]]]`.
+//! let nested = rec(&|tail, nested| {
+//!     ('[', nested.zore(), ']').ignore().p_arse(tail)
+//! });
+//! let limits = Limits::default().max_depth(100);
+//!
+//! let shallow = "[]".repeat(50);
+//! assert!(nested.zore().p_arse_limited(&shallow, limits).is_ok());
+//!
+//! let deep = "[".repeat(1000);
+//! let error = nested.p_arse_limited(&deep, limits).unwrap_err();
+//! assert_eq!(error.limit_exceeded(), Some(LimitExceeded::Depth));
+//! assert_eq!(error.expectation(), "depth of at most 100");
+//! assert_eq!(error.tail(), &deep[100..]);
+//! ```
+
+use std::cell::{Cell, RefCell};
+
+use crate::{Error, Parser, Result};
+
+/// The limits of the resources used by
+/// [`.p_arse_limited()`](Parser::p_arse_limited). The default is no limits.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Limits {
+    max_depth: Option<usize>,
+    max_steps: Option<usize>,
+    max_input_len: Option<usize>,
+    max_repetitions: Option<usize>,
+}
+
+impl Limits {
+    /// Limits the nesting of parsers.
+    pub fn max_depth(self, max_depth: usize) -> Self {
+        Self {
+            max_depth: Some(max_depth),
+            ..self
+        }
+    }
+
+    /// Limits the number of steps.
+    pub fn max_steps(self, max_steps: usize) -> Self {
+        Self {
+            max_steps: Some(max_steps),
+            ..self
+        }
+    }
+
+    /// Limits the length of the input, in bytes.
+    pub fn max_input_len(self, max_input_len: usize) -> Self {
+        Self {
+            max_input_len: Some(max_input_len),
+            ..self
+        }
+    }
+
+    /// Limits the number of matches of each repetition.
+    pub fn max_repetitions(self, max_repetitions: usize) -> Self {
+        Self {
+            max_repetitions: Some(max_repetitions),
+            ..self
+        }
+    }
+}
+
+/// A limit exceeded, see [`Error::limit_exceeded`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LimitExceeded {
+    Depth,
+    Steps,
+    InputLength,
+    Repetitions,
+}
+
+impl LimitExceeded {
+    fn expectation(self, limits: &Limits) -> String {
+        let (what, max) = match self {
+            LimitExceeded::Depth => ("depth", limits.max_depth),
+            LimitExceeded::Steps => ("steps", limits.max_steps),
+            LimitExceeded::InputLength => {
+                ("input length", limits.max_input_len)
+            }
+            LimitExceeded::Repetitions => {
+                ("repetitions", limits.max_repetitions)
+            }
+        };
+
+        format!("{} of at most {}", what, max.unwrap_or(0))
+    }
+}
+
+// The limits of a parsing and the resources used so far.
+struct Frame {
+    limits: Limits,
+    depth: usize,
+    steps: usize,
+    // The limit exceeded and the length of the input left at that point.
+    exceeded: Option<(LimitExceeded, usize)>,
+}
+
+thread_local! {
+    // The number of frames, to skip the checks without limits.
+    static INSTALLED: Cell<usize> = const { Cell::new(0) };
+    static FRAMES: RefCell<Vec<Frame>> = const { RefCell::new(vec![]) };
+}
+
+fn installed() -> bool {
+    INSTALLED.with(Cell::get) > 0
+}
+
+// Checks the limit with the innermost frame, recording it if it's exceeded.
+fn check<'a, F>(tail: &'a str, f: F) -> std::result::Result<(), Error<'a>>
+where
+    F: FnOnce(&mut Frame) -> Option<LimitExceeded>,
+{
+    if !installed() {
+        return Ok(());
+    }
+
+    FRAMES.with(|frames| {
+        let mut frames = frames.borrow_mut();
+        let frame = match frames.last_mut() {
+            Some(frame) => frame,
+            None => return Ok(()),
+        };
+
+        if frame.exceeded.is_none() {
+            frame.exceeded = f(frame).map(|limit| (limit, tail.len()));
+        }
+
+        match frame.exceeded {
+            Some((limit, _)) => Err(exceeding(limit, &frame.limits, tail)),
+            None => Ok(()),
+        }
+    })
+}
+
+fn exceeding<'a>(
+    limit: LimitExceeded,
+    limits: &Limits,
+    tail: &'a str,
+) -> Error<'a> {
+    Error::expecting(limit.expectation(limits), tail).limit(limit)
+}
+
+fn count_step(frame: &mut Frame) -> Option<LimitExceeded> {
+    frame.steps += 1;
+
+    match frame.limits.max_steps {
+        Some(max) if frame.steps > max => Some(LimitExceeded::Steps),
+        _ => None,
+    }
+}
+
+/// Counts a step.
+pub(crate) fn step(tail: &str) -> std::result::Result<(), Error<'_>> {
+    check(tail, count_step)
+}
+
+/// Counts a step and a match of a repetition, the `count`th one.
+pub(crate) fn repetition(
+    count: usize,
+    tail: &str,
+) -> std::result::Result<(), Error<'_>> {
+    check(tail, |frame| {
+        count_step(frame).or(match frame.limits.max_repetitions {
+            Some(max) if count > max => Some(LimitExceeded::Repetitions),
+            _ => None,
+        })
+    })
+}
+
+/// Counts a step and a level of nesting, until the returned guard is
+/// dropped.
+pub(crate) fn enter(tail: &str) -> std::result::Result<Nested, Error<'_>> {
+    if !installed() {
+        return Ok(Nested);
+    }
+
+    FRAMES.with(|frames| {
+        if let Some(frame) = frames.borrow_mut().last_mut() {
+            frame.depth += 1;
+        }
+    });
+    let nested = Nested;
+
+    check(tail, |frame| {
+        count_step(frame).or(match frame.limits.max_depth {
+            Some(max) if frame.depth > max => Some(LimitExceeded::Depth),
+            _ => None,
+        })
+    })?;
+
+    Ok(nested)
+}
+
+/// A level of nesting, left when dropped.
+pub(crate) struct Nested;
+
+impl Drop for Nested {
+    fn drop(&mut self) {
+        if !installed() {
+            return;
+        }
+
+        FRAMES.with(|frames| {
+            if let Some(frame) = frames.borrow_mut().last_mut() {
+                frame.depth -= 1;
+            }
+        });
+    }
+}
+
+/// Removes the frame from the stack even if parsing panics.
+struct Installed;
+
+impl Installed {
+    fn install(limits: Limits) -> Self {
+        let frame = Frame {
+            limits,
+            depth: 0,
+            steps: 0,
+            exceeded: None,
+        };
+        FRAMES.with(|frames| frames.borrow_mut().push(frame));
+        INSTALLED.with(|installed| installed.set(installed.get() + 1));
+
+        Installed
+    }
+
+    fn uninstall(self) -> Option<Frame> {
+        std::mem::forget(self);
+        INSTALLED.with(|installed| installed.set(installed.get() - 1));
+
+        FRAMES.with(|frames| frames.borrow_mut().pop())
+    }
+}
+
+impl Drop for Installed {
+    fn drop(&mut self) {
+        INSTALLED.with(|installed| installed.set(installed.get() - 1));
+        FRAMES.with(|frames| frames.borrow_mut().pop());
+    }
+}
+
+pub(crate) fn p_arse_limited<'a, P>(
+    parser: &P,
+    tail: &'a str,
+    limits: Limits,
+) -> Result<'a, P::Output>
+where
+    P: Parser,
+{
+    if let Some(max) = limits.max_input_len {
+        if tail.len() > max {
+            return Err(exceeding(LimitExceeded::InputLength, &limits, tail));
+        }
+    }
+
+    let installed = Installed::install(limits);
+    let result = parser.p_arse(tail);
+
+    match installed.uninstall() {
+        Some(Frame {
+            exceeded: Some((limit, len)),
+            ..
+        }) => {
+            let rest = tail.get(tail.len().saturating_sub(len)..);
+
+            Err(exceeding(limit, &limits, rest.unwrap_or(tail)))
+        }
+        _ => result,
+    }
+}
@@ -0,0 +1,468 @@
+//! Checks of grammars for common mistakes.
+//!
+//! [`lint`] looks for the pitfalls of PEGs in the structure of a parser (see
+//! [`describe`](crate::describe)) and [`Grammar::lint`] in the rules of a
+//! grammar:
+//!
+//! - alternatives which are never tried, because an earlier one matches
+//!   whenever they would, e.g. `"ab"` in `"a".or("ab")`,
+//! - repetitions of parsers which can match nothing, and so stop (or fail,
+//!   see [`EmptyMatch`](crate::wrapper::EmptyMatch)) where they wouldn't be
+//!   expected to, e.g. `eoi().zore()` or `"a".opt().more()`,
+//! - rules referring to themselves before consuming anything, e.g.
+//!   `Expr <- Expr '+' Term / Term`,
+//! - rules not used by the first rule of a grammar,
+//! - different rules with the same name, e.g. `'a'.named("x")` and
+//!   `'b'.named("x")`.
+//!
+//! The checks are conservative: the opaque parsers are assumed to consume
+//! input and not to shadow anything. For the recursive parsers, see
+//! [`describe`](crate::describe).
+//!
+//! # Examples
+//!
+//! ```
+//! use p_arse::{eoi, lint::lint, CharExt, Parser};
+//!
+//! let keyword = "in".or("int").named("keyword");
+//! let lints = lint(&(keyword, eoi().zore()));
+//!
+//! assert_eq!(
+//!     lints.iter().map(|l| l.to_string()).collect::<Vec<_>>(),
+//!     vec![
+//!         "rule start: (!.)* repeats a parser which can match nothing",
+//!         "rule keyword: 'int' is never tried, 'in' matches first",
+//!     ],
+//! );
+//!
+//! // The lints can be checked in tests.
+//! let identifier = ('a'.to('z'), 'a'.to('z').or('0'.to('9')).zore());
+//! assert_eq!(lint(&identifier), vec![]);
+//! ```
+
+use std::{collections::HashMap, fmt};
+
+use crate::{describe::GrammarNode, export::peg, grammar::Grammar, Parser};
+
+/// A mistake found by [`lint`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Lint {
+    /// The alternative is never tried, because the earlier one (`by`)
+    /// matches whenever it would.
+    ShadowedAlternative {
+        rule: String,
+        alternative: GrammarNode,
+        by: GrammarNode,
+    },
+    /// The repeated node can match nothing, which stops the repetition.
+    NullableRepetition { rule: String, node: GrammarNode },
+    /// Each of the rules refers to the next one, and the last one to the
+    /// first one, before consuming anything.
+    LeftRecursion { rules: Vec<String> },
+    /// The rule is not used by the first rule.
+    UnusedRule { rule: String },
+    /// Different rules have the same name, so only the first one is checked.
+    DuplicateRule { rule: String },
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Lint::ShadowedAlternative {
+                rule,
+                alternative,
+                by,
+            } => write!(
+                f,
+                "rule {}: {} is never tried, {} matches first",
+                rule,
+                peg(alternative),
+                peg(by),
+            ),
+            Lint::NullableRepetition { rule, node } => write!(
+                f,
+                "rule {}: {} repeats a parser which can match nothing",
+                rule,
+                peg(node),
+            ),
+            Lint::LeftRecursion { rules } => {
+                write!(
+                    f,
+                    "left recursion: {} -> {}",
+                    rules.join(" -> "),
+                    rules[0]
+                )
+            }
+            Lint::UnusedRule { rule } => write!(f, "rule {} is not used", rule),
+            Lint::DuplicateRule { rule } => {
+                write!(f, "rule {} is defined more than once", rule)
+            }
+        }
+    }
+}
+
+/// Checks the rules of the parser, see [`lint`](self).
+pub fn lint<P: Parser>(parser: &P) -> Vec<Lint> {
+    parser.describe().lint()
+}
+
+impl GrammarNode {
+    /// Checks the rules of the tree, see [`lint`](crate::lint).
+    pub fn lint(&self) -> Vec<Lint> {
+        Rules::new(self.definitions_with_start()).lint()
+    }
+}
+
+impl Grammar {
+    /// Checks the rules of the grammar, see [`lint`](crate::lint).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use p_arse::{grammar::Grammar, lint::Lint};
+    ///
+    /// let grammar = Grammar::from_peg(
+    ///     "
+    ///     Test  <- Name ('=' / '==') Name
+    ///     Name  <- [a-z]+
+    ///     Digit <- [0-9]
+    ///     ",
+    /// )
+    /// .unwrap();
+    /// let lints = grammar.lint();
+    ///
+    /// assert_eq!(lints.len(), 2);
+    /// assert_eq!(
+    ///     lints[0].to_string(),
+    ///     "rule Test: '==' is never tried, '=' matches first",
+    /// );
+    /// assert_eq!(
+    ///     lints[1],
+    ///     Lint::UnusedRule {
+    ///         rule: "Digit".into(),
+    ///     },
+    /// );
+    /// ```
+    pub fn lint(&self) -> Vec<Lint> {
+        Rules::new(self.described()).lint()
+    }
+}
+
+/// The first chain of left-recursive rules, see [`Lint::LeftRecursion`].
+pub(crate) fn left_recursion(
+    rules: Vec<(String, GrammarNode)>,
+) -> Option<Vec<String>> {
+    let rules = Rules::new(rules);
+    let cycle = rules
+        .rules
+        .iter()
+        .find_map(|(name, _)| rules.left_cycle(name))?;
+
+    Some(cycle.into_iter().map(str::to_string).collect())
+}
+
+// The rules, the first of them is the start rule, which of them can match
+// nothing and the names of the rules defined more than once.
+struct Rules {
+    rules: Vec<(String, GrammarNode)>,
+    nullable: HashMap<String, bool>,
+    duplicates: Vec<String>,
+}
+
+impl Rules {
+    // The first definition of each name is kept.
+    fn new(definitions: Vec<(String, GrammarNode)>) -> Self {
+        let mut rules: Vec<(String, GrammarNode)> = vec![];
+        let mut duplicates = vec![];
+
+        for (name, node) in definitions {
+            match rules.iter().find(|(defined, _)| *defined == name) {
+                // The same rule used in several places.
+                Some((_, first)) if *first == node => {}
+                Some(_) if duplicates.contains(&name) => {}
+                Some(_) => duplicates.push(name),
+                None => rules.push((name, node)),
+            }
+        }
+
+        let mut this = Rules {
+            nullable: HashMap::new(),
+            rules,
+            duplicates,
+        };
+
+        // A rule is nullable if its definition is, given the rules known to
+        // be nullable so far.
+        loop {
+            let nullable: HashMap<_, _> = this
+                .rules
+                .iter()
+                .map(|(name, node)| (name.clone(), this.is_nullable(node)))
+                .collect();
+
+            if nullable == this.nullable {
+                break this;
+            }
+            this.nullable = nullable;
+        }
+    }
+
+    fn lint(&self) -> Vec<Lint> {
+        let mut lints = vec![];
+
+        for (name, node) in &self.rules {
+            self.lint_node(name, node, &mut lints);
+        }
+        self.lint_left_recursion(&mut lints);
+        self.lint_unused(&mut lints);
+
+        for rule in &self.duplicates {
+            lints.push(Lint::DuplicateRule { rule: rule.clone() });
+        }
+
+        lints
+    }
+
+    fn lint_node(&self, rule: &str, node: &GrammarNode, lints: &mut Vec<Lint>) {
+        match node {
+            GrammarNode::Repeat {
+                node: repeated,
+                max: None,
+                ..
+            } if self.is_nullable(repeated) => {
+                lints.push(Lint::NullableRepetition {
+                    rule: rule.to_string(),
+                    node: node.clone(),
+                });
+            }
+            GrammarNode::Choice(nodes) => {
+                for (i, alternative) in nodes.iter().enumerate() {
+                    let by = nodes[..i]
+                        .iter()
+                        .find(|earlier| shadows(earlier, alternative));
+
+                    if let Some(by) = by {
+                        lints.push(Lint::ShadowedAlternative {
+                            rule: rule.to_string(),
+                            alternative: alternative.clone(),
+                            by: by.clone(),
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        for child in node.children() {
+            self.lint_node(rule, child, lints);
+        }
+    }
+
+    fn lint_left_recursion(&self, lints: &mut Vec<Lint>) {
+        let mut reported: Vec<&str> = vec![];
+
+        for (name, _) in &self.rules {
+            if reported.contains(&name.as_str()) {
+                continue;
+            }
+
+            if let Some(cycle) = self.left_cycle(name) {
+                reported.extend(cycle.iter().copied());
+                lints.push(Lint::LeftRecursion {
+                    rules: cycle.iter().map(|name| name.to_string()).collect(),
+                });
+            }
+        }
+    }
+
+    fn lint_unused(&self, lints: &mut Vec<Lint>) {
+        let mut used = vec![];
+        let mut pending = match self.rules.first() {
+            Some((start, _)) => vec![start.as_str()],
+            None => vec![],
+        };
+
+        while let Some(name) = pending.pop() {
+            if used.contains(&name) {
+                continue;
+            }
+            used.push(name);
+
+            if let Some(node) = self.rule(name) {
+                let mut refs = vec![];
+                collect_refs(node, &mut refs);
+                pending.extend(refs);
+            }
+        }
+
+        for (name, _) in &self.rules {
+            if !used.contains(&name.as_str()) {
+                lints.push(Lint::UnusedRule { rule: name.clone() });
+            }
+        }
+    }
+
+    fn rule(&self, name: &str) -> Option<&GrammarNode> {
+        let rule = self.rules.iter().find(|(defined, _)| defined == name);
+
+        rule.map(|(_, node)| node)
+    }
+
+    // Whether the node can match nothing.
+    fn is_nullable(&self, node: &GrammarNode) -> bool {
+        match node {
+            GrammarNode::Sequence(nodes) => {
+                nodes.iter().all(|node| self.is_nullable(node))
+            }
+            GrammarNode::Choice(nodes) => {
+                nodes.iter().any(|node| self.is_nullable(node))
+            }
+            GrammarNode::Repeat { node, min, .. } => {
+                *min == 0 || self.is_nullable(node)
+            }
+            GrammarNode::Lookahead { .. } | GrammarNode::End => true,
+            GrammarNode::Literal(literal) => literal.is_empty(),
+            GrammarNode::NoCase(node) | GrammarNode::Rule { node, .. } => {
+                self.is_nullable(node)
+            }
+            GrammarNode::Ref(name) => {
+                self.nullable.get(name).copied().unwrap_or(false)
+            }
+            GrammarNode::Class(_)
+            | GrammarNode::Any
+            | GrammarNode::Opaque(_) => false,
+        }
+    }
+
+    // The rules the node refers to before consuming anything.
+    fn left_refs<'n>(&self, node: &'n GrammarNode, refs: &mut Vec<&'n str>) {
+        match node {
+            GrammarNode::Sequence(nodes) => {
+                for node in nodes {
+                    self.left_refs(node, refs);
+
+                    if !self.is_nullable(node) {
+                        break;
+                    }
+                }
+            }
+            GrammarNode::Choice(nodes) => {
+                for node in nodes {
+                    self.left_refs(node, refs);
+                }
+            }
+            GrammarNode::Repeat { node, .. }
+            | GrammarNode::Lookahead { node, .. }
+            | GrammarNode::NoCase(node) => self.left_refs(node, refs),
+            GrammarNode::Rule { name, .. } | GrammarNode::Ref(name) => {
+                refs.push(name)
+            }
+            _ => {}
+        }
+    }
+
+    // The shortest chain of rules from the rule back to itself, referring to
+    // each other before consuming anything.
+    fn left_cycle<'r>(&'r self, start: &'r str) -> Option<Vec<&'r str>> {
+        let mut chains = vec![vec![start]];
+        let mut visited = vec![start];
+
+        while !chains.is_empty() {
+            let mut next = vec![];
+
+            for chain in chains {
+                let last = chain[chain.len() - 1];
+                let mut refs = vec![];
+
+                if let Some(node) = self.rule(last) {
+                    self.left_refs(node, &mut refs);
+                }
+
+                for name in refs {
+                    if name == start {
+                        return Some(chain);
+                    }
+
+                    if !visited.contains(&name) {
+                        visited.push(name);
+                        next.push(
+                            chain.iter().copied().chain(Some(name)).collect(),
+                        );
+                    }
+                }
+            }
+            chains = next;
+        }
+
+        None
+    }
+}
+
+fn collect_refs<'n>(node: &'n GrammarNode, refs: &mut Vec<&'n str>) {
+    if let GrammarNode::Rule { name, .. } | GrammarNode::Ref(name) = node {
+        refs.push(name);
+    }
+
+    for child in node.children() {
+        collect_refs(child, refs);
+    }
+}
+
+// Whether the earlier alternative matches whenever the later one would.
+fn shadows(earlier: &GrammarNode, later: &GrammarNode) -> bool {
+    if always_matches(earlier) {
+        return true;
+    }
+
+    let (prefix, case_sensitive) = match leading_literal(later) {
+        Some(prefix) => prefix,
+        None => return false,
+    };
+
+    match earlier {
+        GrammarNode::Literal(literal) => {
+            case_sensitive && prefix.starts_with(&**literal)
+        }
+        GrammarNode::NoCase(node) => match &**node {
+            GrammarNode::Literal(literal) => {
+                prefix.to_lowercase().starts_with(&literal.to_lowercase())
+            }
+            _ => false,
+        },
+        GrammarNode::Class(ranges) => match prefix.chars().next() {
+            Some(c) if case_sensitive => {
+                ranges.iter().any(|&(from, to)| from <= c && c <= to)
+            }
+            _ => false,
+        },
+        GrammarNode::Any => !prefix.is_empty(),
+        _ => false,
+    }
+}
+
+// Whether the node matches any input.
+fn always_matches(node: &GrammarNode) -> bool {
+    match node {
+        GrammarNode::Sequence(nodes) => nodes.iter().all(always_matches),
+        GrammarNode::Choice(nodes) => nodes.iter().any(always_matches),
+        GrammarNode::Repeat { node, min, .. } => {
+            *min == 0 || always_matches(node)
+        }
+        GrammarNode::Literal(literal) => literal.is_empty(),
+        GrammarNode::NoCase(node) => always_matches(node),
+        _ => false,
+    }
+}
+
+// The literal every match of the node starts with, and whether it's
+// case-sensitive.
+fn leading_literal(node: &GrammarNode) -> Option<(&str, bool)> {
+    match node {
+        GrammarNode::Literal(literal) => Some((literal, true)),
+        GrammarNode::Sequence(nodes) => leading_literal(nodes.first()?),
+        GrammarNode::NoCase(node) => {
+            leading_literal(node).map(|(literal, _)| (literal, false))
+        }
+        _ => None,
+    }
+}
@@ -1,10 +1,25 @@
 //! String slices and characters.
 
-use crate::{Error, Parser, Result};
+use crate::{
+    describe::GrammarNode,
+    search::FirstSet,
+    sequence::Leaf,
+    Error,
+    Parser,
+    Result,
+};
 
 impl<'b> Parser for &'b str {
     type Output = &'b str;
 
+    fn first_set(&self) -> Option<FirstSet> {
+        self.chars().next().map(FirstSet::char)
+    }
+
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::Literal(self.to_string())
+    }
+
     fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
         let stripped = tail.strip_prefix(self).ok_or_else(|| {
             Error::expecting(format!("string '{}'", self), tail)
@@ -14,9 +29,19 @@ impl<'b> Parser for &'b str {
     }
 }
 
+impl Leaf for &str {}
+
 impl Parser for char {
     type Output = char;
 
+    fn first_set(&self) -> Option<FirstSet> {
+        Some(FirstSet::char(*self))
+    }
+
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::Literal(self.to_string())
+    }
+
     fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
         let mut chars = tail.chars();
         let first = chars.next().ok_or_else(|| {
@@ -33,6 +58,8 @@ impl Parser for char {
     }
 }
 
+impl Leaf for char {}
+
 /// A [`Copy`] [`char`] range.
 #[derive(Copy, Clone)]
 pub struct CharRange {
@@ -53,6 +80,13 @@ pub trait CharExt {
     fn to(self, to: char) -> CharRange;
 }
 
+impl CharRange {
+    /// The first and the last character of the range.
+    pub fn bounds(&self) -> (char, char) {
+        (self.from, self.to)
+    }
+}
+
 impl CharExt for char {
     fn to(self, to: char) -> CharRange {
         CharRange { from: self, to }
@@ -62,6 +96,14 @@ impl CharExt for char {
 impl Parser for CharRange {
     type Output = char;
 
+    fn first_set(&self) -> Option<FirstSet> {
+        Some(FirstSet::range(self.from, self.to))
+    }
+
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::Class(vec![(self.from, self.to)])
+    }
+
     fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
         let mut chars = tail.chars();
         let first = chars.next().ok_or_else(|| {
@@ -83,3 +125,197 @@ impl Parser for CharRange {
         }
     }
 }
+
+impl Leaf for CharRange {}
+
+/// A set of string literals matched by the longest alternative, built by
+/// [`literals`] or [`literals_map`].
+///
+/// Unlike a chain of [`.or()`](Parser::or), the result doesn't depend on the
+/// order of the literals. The literals are stored in a trie, so the matching
+/// time depends on the length of the match, not on the number of literals.
+/// Since the set is not [`Copy`], the [`Parser`] is implemented for
+/// `&Literals<T>`.
+#[derive(Debug, Clone)]
+pub struct Literals<T> {
+    // The root is the first node.
+    nodes: Vec<Node<T>>,
+    description: String,
+}
+
+#[derive(Debug, Clone)]
+struct Node<T> {
+    // Sorted by the character.
+    children: Vec<(char, usize)>,
+    // Present iff a literal ends at the node.
+    value: Option<T>,
+}
+
+impl<T> Node<T> {
+    fn new() -> Self {
+        Node {
+            children: vec![],
+            value: None,
+        }
+    }
+}
+
+impl<T> Literals<T> {
+    fn insert(&mut self, literal: &str, value: T) {
+        let mut node = 0;
+
+        for c in literal.chars() {
+            node = match self.nodes[node]
+                .children
+                .binary_search_by_key(&c, |&(child, _)| child)
+            {
+                Ok(i) => self.nodes[node].children[i].1,
+                Err(i) => {
+                    let child = self.nodes.len();
+                    self.nodes.push(Node::new());
+                    self.nodes[node].children.insert(i, (c, child));
+
+                    child
+                }
+            };
+        }
+
+        self.nodes[node].value = Some(value);
+    }
+
+    // The value of the longest literal prefixing the input and the rest of
+    // the input.
+    fn longest<'a>(&self, tail: &'a str) -> Option<(&T, &'a str)> {
+        let mut node = &self.nodes[0];
+        let mut longest = node.value.as_ref().map(|value| (value, tail));
+        let mut chars = tail.chars();
+
+        while let Some(c) = chars.next() {
+            match node.children.binary_search_by_key(&c, |&(child, _)| child) {
+                Ok(i) => node = &self.nodes[node.children[i].1],
+                Err(_) => break,
+            }
+
+            if let Some(value) = &node.value {
+                longest = Some((value, chars.as_str()));
+            }
+        }
+
+        longest
+    }
+}
+
+/// Builds a parser matching the longest of the literals and outputting it.
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::{literals, Parser};
+///
+/// // "<".or("<=") would never match "<=".
+/// let operators = literals(&["<", "<=", "=", "==", "!="]);
+///
+/// assert_eq!((&operators).p_arse("<= 1").unwrap(), ("<=", " 1"));
+/// assert_eq!((&operators).p_arse("< 1").unwrap(), ("<", " 1"));
+/// assert!((&operators).p_arse("!1").is_err());
+/// ```
+pub fn literals<'l>(literals: &[&'l str]) -> Literals<&'l str> {
+    literals_map(literals.iter().map(|&literal| (literal, literal)))
+}
+
+/// Builds a parser matching the longest of the literals and outputting the
+/// value paired with it. If a literal is repeated, the last value is used.
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::{literals_map, Parser};
+///
+/// #[derive(Clone, Debug, PartialEq)]
+/// enum Keyword {
+///     In,
+///     Int,
+///     If,
+/// }
+///
+/// let keyword = literals_map(vec![
+///     ("in", Keyword::In),
+///     ("int", Keyword::Int),
+///     ("if", Keyword::If),
+/// ]);
+///
+/// assert_eq!((&keyword).p_arse("int x").unwrap(), (Keyword::Int, " x"));
+/// assert_eq!((&keyword).p_arse("in x").unwrap(), (Keyword::In, " x"));
+/// ```
+pub fn literals_map<'l, I, T>(literals: I) -> Literals<T>
+where
+    I: IntoIterator<Item = (&'l str, T)>,
+{
+    let mut set = Literals {
+        nodes: vec![Node::new()],
+        description: String::new(),
+    };
+    let mut descriptions = vec![];
+
+    for (literal, value) in literals {
+        descriptions.push(format!("'{}'", literal));
+        set.insert(literal, value);
+    }
+
+    set.description = format!("one of {}", descriptions.join(", "));
+
+    set
+}
+
+impl<T> Parser for &Literals<T>
+where
+    T: Clone,
+{
+    type Output = T;
+
+    fn first_set(&self) -> Option<FirstSet> {
+        let root = &self.nodes[0];
+
+        // The empty literal matches anything.
+        if root.value.is_some() {
+            return None;
+        }
+
+        Some(FirstSet::from_ranges(
+            root.children.iter().map(|&(c, _)| (c, c)),
+        ))
+    }
+
+    // The longest match is the first match if the longer literals are tried
+    // first.
+    fn describe(&self) -> GrammarNode {
+        let mut literals = vec![];
+        let mut stack = vec![(0, String::new())];
+
+        while let Some((node, prefix)) = stack.pop() {
+            let node = &self.nodes[node];
+
+            if node.value.is_some() {
+                literals.push(prefix.clone());
+            }
+
+            for &(c, child) in node.children.iter().rev() {
+                stack.push((child, format!("{}{}", prefix, c)));
+            }
+        }
+        literals
+            .sort_by_key(|literal| std::cmp::Reverse(literal.chars().count()));
+
+        GrammarNode::choice(literals.into_iter().map(GrammarNode::Literal))
+    }
+
+    fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        let (value, tail) = self
+            .longest(tail)
+            .ok_or_else(|| Error::expecting(&self.description, tail))?;
+
+        Ok((value.clone(), tail))
+    }
+}
+
+impl<T> Leaf for &Literals<T> where T: Clone {}
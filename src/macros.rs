@@ -0,0 +1,120 @@
+//! Macros.
+
+/// Maps each alternative of a prioritized choice into a variant of an enum.
+///
+/// `alt_enum!(p0 => f0, p1 => f1, ...)` is a shorthand for
+/// `p0.map(f0).or(p1.map(f1).or(...))`. Each `f` may be a tuple variant
+/// (which is a function) or a closure, so the alternatives don't need to be
+/// mapped into a common type first.
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::{alt_enum, CharExt, Parser};
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Token {
+///     Digit(char),
+///     Word(&'static str),
+///     Nothing,
+/// }
+///
+/// let token = alt_enum!(
+///     '0'.to('9') => Token::Digit,
+///     "word" => Token::Word,
+///     "nothing" => |_| Token::Nothing,
+/// );
+///
+/// assert_eq!(token.p_arse("1").unwrap().0, Token::Digit('1'));
+/// assert_eq!(token.p_arse("word").unwrap().0, Token::Word("word"));
+/// assert_eq!(token.p_arse("nothing").unwrap().0, Token::Nothing);
+/// ```
+#[macro_export]
+macro_rules! alt_enum {
+    ($parser:expr => $variant:expr $(,)?) => {
+        $crate::Parser::map($parser, $variant)
+    };
+    ($parser:expr => $variant:expr, $($rest:tt)+) => {
+        $crate::Parser::or(
+            $crate::Parser::map($parser, $variant),
+            $crate::alt_enum!($($rest)+),
+        )
+    };
+}
+
+/// Constructs a sequence whose output contains only the kept elements.
+///
+/// Elements prefixed with `~` are matched but their outputs are discarded.
+/// The output is a flat tuple of the remaining outputs, a single output if
+/// only one element is kept, or `()` if none are. Unlike tuples, `seq!` is not
+/// limited to 6 elements. Kept outputs are not flattened themselves: a kept
+/// element whose output is a tuple stays a nested tuple, see
+/// [`.flatten()`](crate::TupleExt::flatten) for flattening sequences.
+///
+/// It replaces counting indices with [`.r0()`](crate::TupleExt::r0),
+/// [`.r1()`](crate::TupleExt::r1) etc. Since the whole sequence is still
+/// matched, [`.maps()`](crate::Parser::maps) captures the full slice, including
+/// the discarded elements.
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::{seq, CharExt, Parser};
+///
+/// let hex_d = ('0'.to('9')).or('a'.to('f'));
+/// let hex_dd = (hex_d, hex_d).maps(|s| u8::from_str_radix(s, 16).unwrap());
+/// let color = seq!(~"#", hex_dd, hex_dd, hex_dd);
+///
+/// let ((r, g, b), _tail) = color.p_arse("#defec8").unwrap();
+/// assert_eq!((r, g, b), (0xde, 0xfe, 0xc8));
+///
+/// let ws = ' '.zore();
+/// let word = 'a'.to('z').more().maps(|s| s.to_string());
+/// let padded = seq!(~ws, word, ~ws);
+///
+/// assert_eq!(padded.p_arse("  abc  ").unwrap().0, "abc");
+/// ```
+#[macro_export]
+macro_rules! seq {
+    // Munch the elements, accumulating the parsers, the bindings in the
+    // output pattern and the kept bindings. The kept bindings are all named
+    // `output`, but each is created by a different expansion and so has its
+    // own hygiene context: they don't shadow each other.
+    (@munch [$($parsers:tt)*] [$($bindings:tt)*] [$($kept:tt)*]
+        ~ $parser:expr $(, $($rest:tt)*)?) => {
+        $crate::seq!(@munch
+            [$($parsers)* ($parser)]
+            [$($bindings)* _]
+            [$($kept)*]
+            $($($rest)*)?)
+    };
+    (@munch [$($parsers:tt)*] [$($bindings:tt)*] [$($kept:tt)*]
+        $parser:expr $(, $($rest:tt)*)?) => {
+        $crate::seq!(@munch
+            [$($parsers)* ($parser)]
+            [$($bindings)* output]
+            [$($kept)* output]
+            $($($rest)*)?)
+    };
+    (@munch [$($parsers:tt)*] [$($bindings:tt)*] [$($kept:tt)*]) => {
+        $crate::Parser::map(
+            $crate::seq!(@nest $($parsers)*),
+            |$crate::seq!(@nest $($bindings)*)| $crate::seq!(@output $($kept)*),
+        )
+    };
+
+    // Nest the parsers (and the pattern) in pairs to avoid the limit on the
+    // length of tuples.
+    (@nest $last:tt) => { $last };
+    (@nest $first:tt $($rest:tt)+) => {
+        ($first, $crate::seq!(@nest $($rest)+))
+    };
+
+    (@output) => { () };
+    (@output $only:tt) => { $only };
+    (@output $($kept:tt)+) => { ($($kept),+) };
+
+    ($($elements:tt)+) => {
+        $crate::seq!(@munch [] [] [] $($elements)+)
+    };
+}
@@ -0,0 +1,486 @@
+//! Integers and floating point numbers.
+//!
+//! The numbers are converted while parsing, so a number which doesn't fit in
+//! its type fails like any other mismatch, with the error pointing at the
+//! beginning of the number.
+//!
+//! # Examples
+//!
+//! ```
+//! use p_arse::{numbers::{float, hex, int}, Parser};
+//!
+//! let (n, _) = int::<i64>().separator('_').p_arse("-1_000_000").unwrap();
+//! assert_eq!(n, -1_000_000);
+//!
+//! let color = ('#', hex::<u8>().digits(2, 2).more());
+//! assert_eq!(color.p_arse("#defec8").unwrap().0 .1, vec![0xde, 0xfe, 0xc8]);
+//!
+//! assert_eq!(float::<f64>().p_arse("-1.5e3").unwrap().0, -1500.0);
+//! assert!(int::<u8>().p_arse("256").is_err());
+//! ```
+
+use std::marker::PhantomData;
+
+use crate::{
+    describe::GrammarNode,
+    search::FirstSet,
+    sequence::Leaf,
+    Error,
+    Parser,
+    Result,
+};
+
+/// A primitive integer type.
+pub trait Integer: Copy {
+    const NAME: &'static str;
+
+    /// Computes `self * radix + digit`, or `self * radix - digit` for
+    /// negative numbers, returning `None` on overflow.
+    fn push_digit(self, radix: u32, digit: u32, negative: bool)
+        -> Option<Self>;
+
+    fn zero() -> Self;
+}
+
+macro_rules! integer {
+    ($($t:ident),*) => {
+        $(
+            impl Integer for $t {
+                const NAME: &'static str = stringify!($t);
+
+                fn push_digit(
+                    self,
+                    radix: u32,
+                    digit: u32,
+                    negative: bool,
+                ) -> Option<Self> {
+                    let shifted = self.checked_mul(radix as $t)?;
+
+                    if negative {
+                        shifted.checked_sub(digit as $t)
+                    } else {
+                        shifted.checked_add(digit as $t)
+                    }
+                }
+
+                fn zero() -> Self {
+                    0
+                }
+            }
+        )*
+    };
+}
+
+integer!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// A [`Parser`] matching an integer, returned by [`uint`], [`int`], [`hex`],
+/// [`octal`] and [`binary`].
+pub struct Int<T>
+where
+    T: Integer,
+{
+    radix: u32,
+    signed: bool,
+    separator: Option<char>,
+    min_digits: usize,
+    max_digits: usize,
+    phantom: PhantomData<T>,
+}
+
+impl<T> Clone for Int<T>
+where
+    T: Integer,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Int<T> where T: Integer {}
+
+fn integer<T: Integer>(radix: u32, signed: bool) -> Int<T> {
+    Int {
+        radix,
+        signed,
+        separator: None,
+        min_digits: 1,
+        max_digits: usize::MAX,
+        phantom: PhantomData,
+    }
+}
+
+/// Matches decimal digits.
+pub fn uint<T: Integer>() -> Int<T> {
+    integer(10, false)
+}
+
+/// Matches decimal digits preceded by an optional sign, `+` or `-`. Only the
+/// signed types accept `-`, except for `-0`.
+pub fn int<T: Integer>() -> Int<T> {
+    integer(10, true)
+}
+
+/// Matches hexadecimal digits, in either case. The `0x` prefix is not a part
+/// of the number.
+pub fn hex<T: Integer>() -> Int<T> {
+    integer(16, false)
+}
+
+/// Matches octal digits. The `0o` prefix is not a part of the number.
+pub fn octal<T: Integer>() -> Int<T> {
+    integer(8, false)
+}
+
+/// Matches binary digits. The `0b` prefix is not a part of the number.
+pub fn binary<T: Integer>() -> Int<T> {
+    integer(2, false)
+}
+
+impl<T> Int<T>
+where
+    T: Integer,
+{
+    /// Matches digits in the given radix, from 2 to 36.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the radix is not in the range from 2 to 36.
+    pub fn radix(self, radix: u32) -> Self {
+        assert!((2..=36).contains(&radix), "invalid radix {}", radix);
+
+        Int { radix, ..self }
+    }
+
+    /// Allows the separator between digits, e.g. `1_000`. The separators
+    /// don't count as digits.
+    pub fn separator(self, separator: char) -> Self {
+        Int {
+            separator: Some(separator),
+            ..self
+        }
+    }
+
+    /// Limits the number of digits. The parser stops after `max` digits, and
+    /// fails if there are less than `min` of them.
+    pub fn digits(self, min: usize, max: usize) -> Self {
+        Int {
+            min_digits: min,
+            max_digits: max,
+            ..self
+        }
+    }
+}
+
+impl<T> Parser for Int<T>
+where
+    T: Integer,
+{
+    type Output = T;
+
+    fn first_set(&self) -> Option<FirstSet> {
+        let digits = (0..self.radix.min(10))
+            .map(|d| char::from_digit(d, 10).unwrap())
+            .map(|c| (c, c));
+        let letters = (10..self.radix).flat_map(|d| {
+            let lower = char::from_digit(d, self.radix).unwrap();
+
+            [
+                (lower, lower),
+                (lower.to_ascii_uppercase(), lower.to_ascii_uppercase()),
+            ]
+        });
+        let signs = if self.signed {
+            vec![('+', '+'), ('-', '-')]
+        } else {
+            vec![]
+        };
+
+        Some(FirstSet::from_ranges(digits.chain(letters).chain(signs)))
+    }
+
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::Opaque(format!("{} number", T::NAME))
+    }
+
+    fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        let start = tail;
+        let (negative, mut tail) = match tail.chars().next() {
+            Some(sign @ ('+' | '-')) if self.signed => {
+                (sign == '-', &tail[1..])
+            }
+            _ => (false, tail),
+        };
+        let mut number = T::zero();
+        let mut digits = 0;
+
+        while digits < self.max_digits {
+            let mut chars = tail.chars();
+
+            match chars.next() {
+                // The separator is only a part of the number between digits.
+                Some(c) if digits > 0 && Some(c) == self.separator => {
+                    match chars.clone().next() {
+                        Some(c) if c.is_digit(self.radix) => {}
+                        _ => break,
+                    }
+                }
+                Some(c) => match c.to_digit(self.radix) {
+                    Some(digit) => {
+                        number = number
+                            .push_digit(self.radix, digit, negative)
+                            .ok_or_else(|| {
+                                Error::expecting(
+                                    format!("number within {}", T::NAME),
+                                    start,
+                                )
+                            })?;
+                        digits += 1;
+                    }
+                    None => break,
+                },
+                None => break,
+            }
+
+            tail = chars.as_str();
+        }
+
+        if digits < self.min_digits.max(1) {
+            return Err(Error::expecting(
+                format!("digit in base {}", self.radix),
+                tail,
+            ));
+        }
+
+        Ok((number, tail))
+    }
+}
+
+impl<T> Leaf for Int<T> where T: Integer {}
+
+/// A primitive floating point type.
+pub trait Float: Copy + std::str::FromStr {
+    const NAME: &'static str;
+
+    fn is_finite(self) -> bool;
+}
+
+impl Float for f32 {
+    const NAME: &'static str = "f32";
+
+    fn is_finite(self) -> bool {
+        f32::is_finite(self)
+    }
+}
+
+impl Float for f64 {
+    const NAME: &'static str = "f64";
+
+    fn is_finite(self) -> bool {
+        f64::is_finite(self)
+    }
+}
+
+/// The syntax of floating point numbers.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum FloatSyntax {
+    /// `-?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?`, e.g. `-0.5e10`.
+    Json,
+    /// Like Rust literals, with an optional `-` and without suffixes, e.g.
+    /// `1_000.`, `-0.5e1_0`. Integers are accepted as well.
+    Rust,
+    /// Like C literals, with an optional sign and without suffixes, e.g.
+    /// `+.5`, `1.e10`. Integers are accepted as well.
+    C,
+}
+
+/// A [`Parser`] matching a floating point number, returned by [`float`].
+pub struct FloatParser<T>
+where
+    T: Float,
+{
+    syntax: FloatSyntax,
+    phantom: PhantomData<T>,
+}
+
+impl<T> Clone for FloatParser<T>
+where
+    T: Float,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for FloatParser<T> where T: Float {}
+
+/// Matches a floating point number in the JSON syntax. Other syntaxes can be
+/// chosen with [`FloatParser::syntax`].
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::{
+///     numbers::{float, FloatSyntax},
+///     Parser,
+/// };
+///
+/// let rust = float::<f64>().syntax(FloatSyntax::Rust);
+/// assert_eq!(rust.p_arse("1_000.5").unwrap().0, 1000.5);
+/// assert_eq!(rust.p_arse("1..2").unwrap(), (1.0, "..2"));
+///
+/// let c = float::<f32>().syntax(FloatSyntax::C);
+/// assert_eq!(c.p_arse(".5f").unwrap(), (0.5, "f"));
+///
+/// assert!(float::<f64>().p_arse(".5").is_err());
+/// assert!(float::<f32>().p_arse("1e39").is_err());
+/// ```
+pub fn float<T: Float>() -> FloatParser<T> {
+    FloatParser {
+        syntax: FloatSyntax::Json,
+        phantom: PhantomData,
+    }
+}
+
+impl<T> FloatParser<T>
+where
+    T: Float,
+{
+    pub fn syntax(self, syntax: FloatSyntax) -> Self {
+        FloatParser { syntax, ..self }
+    }
+}
+
+// Skips the digits (and the separators following them), returning the number
+// of digits.
+fn digits(tail: &mut &str, separator: Option<char>) -> usize {
+    let mut count = 0;
+
+    loop {
+        let mut chars = tail.chars();
+
+        match chars.next() {
+            Some(c) if c.is_ascii_digit() => count += 1,
+            Some(c) if count > 0 && Some(c) == separator => {}
+            _ => return count,
+        }
+
+        *tail = chars.as_str();
+    }
+}
+
+fn skip(tail: &mut &str, c: char) -> bool {
+    match tail.strip_prefix(c) {
+        Some(rest) => {
+            *tail = rest;
+
+            true
+        }
+        None => false,
+    }
+}
+
+impl FloatSyntax {
+    // Returns the rest of the input after the number, if any.
+    fn skip(self, mut tail: &str) -> Option<&str> {
+        match self {
+            FloatSyntax::Json => {
+                skip(&mut tail, '-');
+
+                if !skip(&mut tail, '0') && digits(&mut tail, None) == 0 {
+                    return None;
+                }
+
+                let mut fraction = tail;
+                if skip(&mut fraction, '.') && digits(&mut fraction, None) > 0 {
+                    tail = fraction;
+                }
+            }
+            FloatSyntax::Rust => {
+                skip(&mut tail, '-');
+
+                if digits(&mut tail, Some('_')) == 0 {
+                    return None;
+                }
+
+                let mut fraction = tail;
+                if skip(&mut fraction, '.') {
+                    // `1.` is a number, unlike `1..` or `1.x`.
+                    if digits(&mut fraction, Some('_')) > 0
+                        || !fraction.starts_with(|c: char| {
+                            c == '.' || c == '_' || c.is_alphabetic()
+                        })
+                    {
+                        tail = fraction;
+                    }
+                }
+            }
+            FloatSyntax::C => {
+                let _ = skip(&mut tail, '+') || skip(&mut tail, '-');
+                let whole = digits(&mut tail, None);
+
+                // `1.` and `.1` are numbers, unlike `.`.
+                let mut fraction = tail;
+                if skip(&mut fraction, '.')
+                    && whole + digits(&mut fraction, None) > 0
+                {
+                    tail = fraction;
+                } else if whole == 0 {
+                    return None;
+                }
+            }
+        }
+
+        let separator = (self == FloatSyntax::Rust).then_some('_');
+        let mut exponent = tail;
+        if skip(&mut exponent, 'e') || skip(&mut exponent, 'E') {
+            let _ = skip(&mut exponent, '+') || skip(&mut exponent, '-');
+
+            if digits(&mut exponent, separator) > 0 {
+                tail = exponent;
+            }
+        }
+
+        Some(tail)
+    }
+}
+
+impl<T> Parser for FloatParser<T>
+where
+    T: Float,
+{
+    type Output = T;
+
+    fn first_set(&self) -> Option<FirstSet> {
+        let signs_and_dot = match self.syntax {
+            FloatSyntax::Json | FloatSyntax::Rust => vec![('-', '-')],
+            FloatSyntax::C => vec![('+', '+'), ('-', '-'), ('.', '.')],
+        };
+
+        Some(FirstSet::from_ranges(
+            signs_and_dot.into_iter().chain(Some(('0', '9'))),
+        ))
+    }
+
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::Opaque(format!("{} number", T::NAME))
+    }
+
+    fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        let rest = self.syntax.skip(tail).ok_or_else(|| {
+            Error::expecting(format!("{} number", T::NAME), tail)
+        })?;
+        let matched = &tail[..tail.len() - rest.len()];
+        let number = matched
+            .replace('_', "")
+            .parse::<T>()
+            .ok()
+            .filter(|number| number.is_finite())
+            .ok_or_else(|| {
+                Error::expecting(format!("number within {}", T::NAME), tail)
+            })?;
+
+        Ok((number, rest))
+    }
+}
+
+impl<T> Leaf for FloatParser<T> where T: Float {}
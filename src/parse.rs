@@ -0,0 +1,341 @@
+//! Types parsed by their own grammar.
+//!
+//! [`Parse`] is implemented for some primitive types and can be derived for
+//! AST types with `#[derive(Parse)]`, re-exported from the `p-arse-macros`
+//! crate with the `derive` feature (enabled by default). A field of a derived
+//! type is parsed by the [`Parse`] implementation of its type. The `p_arse`
+//! attribute adds to it:
+//!
+//! - `#[p_arse(lit = "...")]` matches the literal before the field, the
+//!   fields of the variant or the fields of the struct. A unit variant or a
+//!   unit struct matches just its literal. A `()` field with a literal is a
+//!   token, e.g. a closing bracket.
+//! - `#[p_arse(sep = "...")]` on a [`Vec`] field matches the separator
+//!   between its elements, using [`separated`]. It's an error on the other
+//!   fields.
+//! - `#[p_arse(skip_ws)]` on a field, a variant or a type skips whitespace
+//!   before each literal and field, including the elements of [`Vec`]s. A
+//!   [`Vec`] is recognized by its type written as `Vec<T>`, possibly with a
+//!   path, e.g. `std::vec::Vec<T>`, but not as an alias of it.
+//!
+//! The variants of an enum are tried in order, like [`Parser::or`].
+//!
+//! # Examples
+//!
+//! ```
+//! use p_arse::{parse, Parse, Parser, Result};
+//!
+//! #[derive(Parse, Debug, PartialEq)]
+//! #[p_arse(skip_ws)]
+//! enum Expr {
+//!     Call(Ident, #[p_arse(lit = "(", sep = ",")] Vec<Expr>, Close),
+//!     #[p_arse(lit = "-")]
+//!     Neg(Box<Expr>),
+//!     Number(u32),
+//! }
+//!
+//! #[derive(Parse, Debug, PartialEq)]
+//! #[p_arse(lit = ")")]
+//! struct Close;
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct Ident(String);
+//!
+//! impl Parse for Ident {
+//!     fn p_arse<'a>(tail: &'a str) -> Result<'a, Self> {
+//!         let ident = p_arse::take_while1(char::is_alphabetic);
+//!
+//!         ident.maps(|s| Ident(s.to_string())).p_arse(tail)
+//!     }
+//! }
+//!
+//! let (expr, _) = parse::<Expr>().p_arse("max(1, -2 , f( ))").unwrap();
+//! assert_eq!(
+//!     expr,
+//!     Expr::Call(
+//!         Ident("max".into()),
+//!         vec![
+//!             Expr::Number(1),
+//!             Expr::Neg(Box::new(Expr::Number(2))),
+//!             Expr::Call(Ident("f".into()), vec![], Close),
+//!         ],
+//!         Close,
+//!     ),
+//! );
+//! ```
+//!
+//! ```compile_fail
+//! use p_arse::Parse;
+//!
+//! #[derive(Parse)]
+//! struct Pair(#[p_arse(sep = ",")] (u8, u8)); // Not a `Vec`.
+//! ```
+
+use std::marker::PhantomData;
+
+use crate::{
+    describe::GrammarNode,
+    limits,
+    numbers::{float, int, uint},
+    sequence::Leaf,
+    state,
+    Parser,
+    Result,
+};
+
+/// A type with a grammar.
+pub trait Parse: Sized {
+    /// Parses the value at the beginning of the input.
+    fn p_arse<'a>(tail: &'a str) -> Result<'a, Self>;
+}
+
+/// A [`Parser`] using [`Parse`], returned by [`parse`].
+pub struct Parsed<T>
+where
+    T: Parse,
+{
+    phantom: PhantomData<T>,
+}
+
+impl<T> Clone for Parsed<T>
+where
+    T: Parse,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Parsed<T> where T: Parse {}
+
+/// Parses a `T`.
+pub fn parse<T: Parse>() -> Parsed<T> {
+    Parsed {
+        phantom: PhantomData,
+    }
+}
+
+impl<T> Parser for Parsed<T>
+where
+    T: Parse,
+{
+    type Output = T;
+
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::Opaque(std::any::type_name::<T>().to_string())
+    }
+
+    fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        // The derived types can be recursive.
+        let _nested = limits::enter(tail)?;
+
+        T::p_arse(tail)
+    }
+}
+
+impl<T> Leaf for Parsed<T> where T: Parse {}
+
+/// A [`Parser`] matching zero or more `T`s separated by a parser, returned
+/// by [`separated`].
+pub struct Separated<T, S>
+where
+    T: Parse,
+    S: Parser,
+{
+    separator: S,
+    skip_ws: bool,
+    phantom: PhantomData<T>,
+}
+
+impl<T, S> Clone for Separated<T, S>
+where
+    T: Parse,
+    S: Parser,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, S> Copy for Separated<T, S>
+where
+    T: Parse,
+    S: Parser,
+{
+}
+
+/// Matches zero or more `T`s separated by the separator. If `skip_ws` is
+/// set, skips whitespace before each separator and element. The whitespace
+/// following the last element is not skipped.
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::{parse::separated, Parser};
+///
+/// let numbers = separated::<u8, _>(',', true);
+/// assert_eq!(numbers.p_arse(" 1 , 2,3 ;").unwrap(), (vec![1, 2, 3], " ;"));
+/// assert_eq!(numbers.p_arse("1,").unwrap(), (vec![1], ","));
+/// assert_eq!(numbers.p_arse(";").unwrap(), (vec![], ";"));
+/// ```
+pub fn separated<T: Parse, S: Parser>(
+    separator: S,
+    skip_ws: bool,
+) -> Separated<T, S> {
+    Separated {
+        separator,
+        skip_ws,
+        phantom: PhantomData,
+    }
+}
+
+impl<T, S> Separated<T, S>
+where
+    T: Parse,
+    S: Parser,
+{
+    fn skip<'a>(&self, tail: &'a str) -> &'a str {
+        match self.skip_ws {
+            true => tail.trim_start(),
+            false => tail,
+        }
+    }
+
+    // The next element, preceded by a separator unless it's the first one.
+    fn p_arse_element<'a>(&self, tail: &'a str, first: bool) -> Result<'a, T> {
+        let mut tail = self.skip(tail);
+
+        if !first {
+            let (_, rest) = self.separator.p_arse(tail)?;
+            tail = self.skip(rest);
+        }
+
+        T::p_arse(tail)
+    }
+}
+
+impl<T, S> Parser for Separated<T, S>
+where
+    T: Parse,
+    S: Parser,
+{
+    type Output = Vec<T>;
+
+    fn describe(&self) -> GrammarNode {
+        let element = parse::<T>().describe();
+        let rest = GrammarNode::sequence(vec![
+            self.separator.describe(),
+            element.clone(),
+        ]);
+        let elements = GrammarNode::sequence(vec![
+            element,
+            GrammarNode::repeat(rest, 0, None),
+        ]);
+
+        GrammarNode::repeat(elements, 0, Some(1))
+    }
+
+    fn p_arse<'a>(&self, mut tail: &'a str) -> Result<'a, Self::Output> {
+        let mut output = vec![];
+        let mut checkpoint = state::checkpoint();
+
+        while let Ok((element, rest)) =
+            self.p_arse_element(tail, output.is_empty())
+        {
+            limits::repetition(output.len() + 1, tail)?;
+
+            // Stops on separators and elements matching nothing.
+            let progress = rest.len() < tail.len();
+            tail = rest;
+            output.push(element);
+            checkpoint.update();
+
+            if !progress {
+                break;
+            }
+        }
+        checkpoint.restore();
+
+        Ok((output, tail))
+    }
+}
+
+impl<T, S> Leaf for Separated<T, S>
+where
+    T: Parse,
+    S: Parser,
+{
+}
+
+impl Parse for () {
+    fn p_arse<'a>(tail: &'a str) -> Result<'a, Self> {
+        Ok(((), tail))
+    }
+}
+
+impl Parse for bool {
+    fn p_arse<'a>(tail: &'a str) -> Result<'a, Self> {
+        "true".map(|_| true).or("false".map(|_| false)).p_arse(tail)
+    }
+}
+
+impl Parse for char {
+    fn p_arse<'a>(tail: &'a str) -> Result<'a, Self> {
+        crate::any().p_arse(tail)
+    }
+}
+
+macro_rules! parse_integers {
+    ($parser:ident: $($t:ty),*) => {
+        $(
+            impl Parse for $t {
+                fn p_arse<'a>(tail: &'a str) -> Result<'a, Self> {
+                    $parser().p_arse(tail)
+                }
+            }
+        )*
+    };
+}
+
+parse_integers!(uint: u8, u16, u32, u64, u128, usize);
+parse_integers!(int: i8, i16, i32, i64, i128, isize);
+
+impl Parse for f32 {
+    fn p_arse<'a>(tail: &'a str) -> Result<'a, Self> {
+        float().p_arse(tail)
+    }
+}
+
+impl Parse for f64 {
+    fn p_arse<'a>(tail: &'a str) -> Result<'a, Self> {
+        float().p_arse(tail)
+    }
+}
+
+impl<T> Parse for Box<T>
+where
+    T: Parse,
+{
+    fn p_arse<'a>(tail: &'a str) -> Result<'a, Self> {
+        parse::<T>().map(Box::new).p_arse(tail)
+    }
+}
+
+impl<T> Parse for Option<T>
+where
+    T: Parse,
+{
+    fn p_arse<'a>(tail: &'a str) -> Result<'a, Self> {
+        parse::<T>().opt().p_arse(tail)
+    }
+}
+
+/// Zero or more `T`s. Stops on a `T` matching nothing.
+impl<T> Parse for Vec<T>
+where
+    T: Parse,
+{
+    fn p_arse<'a>(tail: &'a str) -> Result<'a, Self> {
+        separated("", false).p_arse(tail)
+    }
+}
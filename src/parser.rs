@@ -2,7 +2,17 @@
 
 use std::{fmt::Debug, marker::PhantomData};
 
-use crate::{wrapper::*, Result};
+use crate::{
+    describe::GrammarNode,
+    function::Fun,
+    lexeme::{lexer, ws, Lexeme, Whitespace},
+    limits::{self, Limits},
+    pattern::AsPattern,
+    search::{FindIter, FirstSet, Match},
+    state,
+    wrapper::*,
+    Result,
+};
 
 #[allow(clippy::needless_doctest_main)]
 /// The main trait.
@@ -33,6 +43,89 @@ pub trait Parser: Sized + Copy {
     /// ```
     fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output>;
 
+    /// Returns the set of characters a match of the parser can start at, i.e.
+    /// the input must begin with one of them for the parser to succeed, or
+    /// [`None`] if it's unknown or if the parser may succeed whatever the input
+    /// begins with (e.g. [`.opt()`](Parser::opt) matching nothing). A positive
+    /// look-ahead has the set of its parser, although it consumes nothing.
+    ///
+    /// It's used to skip the positions where the parser can't match when
+    /// searching, i.e. in [`.find_iter()`](Parser::find_iter). The default
+    /// implementation returns [`None`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use p_arse::{Parser, CharExt};
+    ///
+    /// let digit_or_x = ('0'.to('9')).or('x');
+    /// let first_set = digit_or_x.first_set().unwrap();
+    ///
+    /// assert!(first_set.contains('5'));
+    /// assert!(first_set.contains('x'));
+    /// assert!(!first_set.contains('y'));
+    ///
+    /// assert!(digit_or_x.opt().first_set().is_none());
+    /// ```
+    fn first_set(&self) -> Option<FirstSet> {
+        None
+    }
+
+    /// Describes the structure of the parser, see [`describe`](crate::describe).
+    /// The default implementation returns [`GrammarNode::Opaque`] with the
+    /// name of the type.
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::Opaque(std::any::type_name::<Self>().to_string())
+    }
+
+    /// Finds the first match of the parser anywhere in the input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use p_arse::{Parser, CharExt};
+    ///
+    /// let number = '0'.to('9').more().maps(|s| s.parse::<u32>().unwrap());
+    /// let found = number.find("width: 800px").unwrap();
+    ///
+    /// assert_eq!(found.output, 800);
+    /// assert_eq!(found.range(), 7..10);
+    /// assert_eq!(found.as_str(), "800");
+    /// ```
+    fn find<'a>(&self, haystack: &'a str) -> Option<Match<'a, Self::Output>> {
+        self.find_iter(haystack).next()
+    }
+
+    /// Returns an iterator over all non-overlapping matches of the parser in
+    /// the input, with their spans and outputs.
+    ///
+    /// At each position the parser is matched against the rest of the input.
+    /// After a successful match the search continues at its end. Positions
+    /// which can't begin a match according to the
+    /// [`.first_set()`](Parser::first_set) are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use p_arse::{Parser, CharExt};
+    ///
+    /// let hex_d = ('0'.to('9')).or('a'.to('f'));
+    /// let hex_ddd = (hex_d, hex_d, hex_d);
+    /// let color = ('#', hex_ddd, hex_ddd).maps(|s| s.to_string());
+    ///
+    /// let css = "a { color: #defec8; } b { color: #c0ffee; }";
+    /// let colors = color
+    ///     .find_iter(css)
+    ///     .map(|found| (found.start(), found.output))
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(colors[0], (11, "#defec8".to_string()));
+    /// assert_eq!(colors[1], (33, "#c0ffee".to_string()));
+    /// ```
+    fn find_iter<'a>(&self, haystack: &'a str) -> FindIter<'a, Self> {
+        FindIter::new(*self, haystack)
+    }
+
     /// Maps the parser's output.
     ///
     /// # Examples
@@ -111,6 +204,38 @@ pub trait Parser: Sized + Copy {
         }
     }
 
+    /// Returns an alternative of two parsers with different outputs.
+    ///
+    /// Works like [`.or()`](Parser::or), except that the outputs don't have
+    /// to be of the same type. The output of the first parser is wrapped in
+    /// [`Either::Left`], the output of the second one in [`Either::Right`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use p_arse::{Either, Parser, CharExt};
+    ///
+    /// let digit_or_word = ('0'.to('9')).or_either("word");
+    ///
+    /// assert_eq!(digit_or_word.p_arse("7").unwrap().0, Either::Left('7'));
+    /// assert_eq!(
+    ///     digit_or_word.p_arse("word").unwrap().0,
+    ///     Either::Right("word"),
+    /// );
+    /// ```
+    ///
+    /// To map more than two alternatives into a user-defined enum, see
+    /// [`alt_enum!`](crate::alt_enum).
+    fn or_either<P>(self, other: P) -> OrEither<Self, P>
+    where
+        P: Parser,
+    {
+        OrEither {
+            parser_0: self,
+            parser_1: other,
+        }
+    }
+
     /// Makes the parser optional.
     ///
     /// The returned higher order parser always succeeds and returns
@@ -134,7 +259,9 @@ pub trait Parser: Sized + Copy {
     /// Makes the parser match **z**ero or m**ore** times.
     ///
     /// The returned higher order parser always succeeds and returns a [`Vec`]
-    /// containing the outputs from the successful parsings.
+    /// containing the outputs from the successful parsings. It stops when the
+    /// parser matches without consuming anything, unless configured
+    /// otherwise with [`.empty_match()`](ZeroOrMore::empty_match).
     ///
     /// # Examples
     ///
@@ -149,7 +276,10 @@ pub trait Parser: Sized + Copy {
     /// assert!(anything.p_arse("abc").is_ok());
     /// ```
     fn zore(self) -> ZeroOrMore<Self> {
-        ZeroOrMore { parser: self }
+        ZeroOrMore {
+            parser: self,
+            empty_match: EmptyMatch::Stop,
+        }
     }
 
     /// Makes the parser match one or more times.
@@ -170,7 +300,10 @@ pub trait Parser: Sized + Copy {
     /// assert!(bees.p_arse("bbb").is_ok());
     /// ```
     fn more(self) -> OneOrMore<Self> {
-        OneOrMore { parser: self }
+        OneOrMore {
+            parser: self,
+            empty_match: EmptyMatch::Stop,
+        }
     }
 
     /// Turns the parser into a negative look-ahead.
@@ -234,6 +367,228 @@ pub trait Parser: Sized + Copy {
         PositiveLookahead { parser: self }
     }
 
+    /// Chooses the parser for the rest of the input based on the output.
+    ///
+    /// The supplied function is called with the output of the parser and
+    /// returns a [`Continuation`], which is used to parse the rest of the
+    /// input. The continuation doesn't have to be [`Copy`], so it can be built
+    /// dynamically, i.e. it can be a [`Vec`] of parsers, an owned [`String`] or
+    /// a boxed closure (see [`boxed`](crate::function::boxed)).
+    ///
+    /// It's useful for context-sensitive formats, e.g. length-prefixed
+    /// strings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use p_arse::{Parser, CharExt, any};
+    ///
+    /// // Netstrings, i.e. `3:abc,`.
+    /// let length = '0'.to('9').more().maps(|s| s.parse::<usize>().unwrap());
+    /// let content = (length, ':').bind(|(n, _)| vec![any(); n]);
+    /// let netstring = (content, ',');
+    ///
+    /// assert!(netstring.p_arse("3:abc,").is_ok());
+    /// assert!(netstring.p_arse("3:ab,").is_err());
+    /// assert!(netstring.p_arse("3:abcd,").is_err());
+    /// ```
+    fn bind<F, C>(self, f: F) -> Bind<Self, F, C>
+    where
+        F: Fn(Self::Output) -> C + Copy,
+        C: Continuation,
+    {
+        Bind {
+            parser: self,
+            f,
+            marker: PhantomData,
+        }
+    }
+
+    /// Attempts to parse the input with a user state.
+    ///
+    /// The state is available to [`.map_with_state()`](Parser::map_with_state),
+    /// [`.try_map_with_state()`](Parser::try_map_with_state) and custom
+    /// parsers (see [`with_state`](crate::state::with_state)). The changes
+    /// made by the branches which failed are undone, the remaining ones are
+    /// written back to `state`, even if parsing fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use p_arse::{Parser, CharExt};
+    ///
+    /// let letter = 'a'.to('z').map_with_state(|c, seen: &mut Vec<char>| {
+    ///     seen.push(c);
+    ///
+    ///     c
+    /// });
+    /// // The first alternative fails after the first letter.
+    /// let letters = (letter, letter, '!').or((letter, letter, '?'));
+    ///
+    /// let mut seen: Vec<char> = vec![];
+    /// letters.p_arse_with("ab?", &mut seen).unwrap();
+    ///
+    /// assert_eq!(seen, vec!['a', 'b']);
+    /// ```
+    fn p_arse_with<'a, S>(
+        &self,
+        tail: &'a str,
+        state: &mut S,
+    ) -> Result<'a, Self::Output>
+    where
+        S: Clone + 'static,
+    {
+        state::p_arse_with(self, tail, state)
+    }
+
+    /// Parses the input within the limits of the resources used, see
+    /// [`limits`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use p_arse::{limits::{LimitExceeded, Limits}, Parser};
+    ///
+    /// let a_or_b = 'a'.or('b').zore();
+    ///
+    /// let limits = Limits::default().max_repetitions(3);
+    /// assert!(a_or_b.p_arse_limited("abb", limits).is_ok());
+    /// let error = a_or_b.p_arse_limited("abba", limits).unwrap_err();
+    /// assert_eq!(error.limit_exceeded(), Some(LimitExceeded::Repetitions));
+    /// assert_eq!(error.tail(), "a");
+    ///
+    /// let limits = Limits::default().max_input_len(3);
+    /// let error = a_or_b.p_arse_limited("abba", limits).unwrap_err();
+    /// assert_eq!(error.limit_exceeded(), Some(LimitExceeded::InputLength));
+    /// ```
+    fn p_arse_limited<'a>(
+        &self,
+        tail: &'a str,
+        limits: Limits,
+    ) -> Result<'a, Self::Output> {
+        limits::p_arse_limited(self, tail, limits)
+    }
+
+    /// Maps the parser's output with access to the user state.
+    ///
+    /// Fails if the parser isn't run with a state of type `S` (see
+    /// [`.p_arse_with()`](Parser::p_arse_with)).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use p_arse::{Parser, CharExt};
+    ///
+    /// let depth = |_, depth: &mut usize| {
+    ///     *depth += 1;
+    ///
+    ///     *depth
+    /// };
+    /// let opening = '('.map_with_state(depth);
+    ///
+    /// let mut depth: usize = 0;
+    /// let (levels, _tail) =
+    ///     opening.more().p_arse_with("(((", &mut depth).unwrap();
+    ///
+    /// assert_eq!(levels, vec![1, 2, 3]);
+    /// assert_eq!(depth, 3);
+    /// ```
+    fn map_with_state<F, S, U>(self, f: F) -> MapWithState<Self, F, S, U>
+    where
+        F: Fn(Self::Output, &mut S) -> U + Copy,
+        S: 'static,
+    {
+        MapWithState {
+            parser: self,
+            f,
+            marker: PhantomData,
+        }
+    }
+
+    /// Maps the parser's output with access to the user state. Fails if the
+    /// function returns an error, which is used as the expectation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashSet;
+    ///
+    /// use p_arse::{Parser, CharExt};
+    ///
+    /// let unique = |key: char, seen: &mut HashSet<char>| {
+    ///     if seen.insert(key) {
+    ///         Ok(key)
+    ///     } else {
+    ///         Err("unique key")
+    ///     }
+    /// };
+    /// let keys = 'a'.to('z').try_map_with_state(unique).more();
+    ///
+    /// let mut seen = HashSet::<char>::new();
+    /// assert!(keys.p_arse_with("abc", &mut seen).is_ok());
+    ///
+    /// let mut seen = HashSet::<char>::new();
+    /// let (keys, tail) = keys.p_arse_with("aba", &mut seen).unwrap();
+    ///
+    /// assert_eq!(keys, vec!['a', 'b']);
+    /// assert_eq!(tail, "a");
+    /// ```
+    fn try_map_with_state<F, S, U, E>(
+        self,
+        f: F,
+    ) -> TryMapWithState<Self, F, S, U, E>
+    where
+        F: Fn(Self::Output, &mut S) -> std::result::Result<U, E> + Copy,
+        S: 'static,
+        E: Into<String>,
+    {
+        TryMapWithState {
+            parser: self,
+            f,
+            marker: PhantomData,
+        }
+    }
+
+    /// Turns the parser into a [`Pattern`](std::str::pattern::Pattern), so it
+    /// can be used with [`str::find`], [`str::split`], [`str::matches`],
+    /// [`str::replace`], [`str::trim_start_matches`] etc.
+    ///
+    /// The search tries to match the parser at each position of the string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use p_arse::{Parser, CharExt};
+    ///
+    /// let digits = '0'.to('9').more();
+    /// let separator = (' '.zore(), ',', ' '.zore());
+    ///
+    /// assert_eq!("abc123".find(digits.as_pattern()), Some(3));
+    /// assert_eq!(
+    ///     "a , b,c".split(separator.as_pattern()).collect::<Vec<_>>(),
+    ///     vec!["a", "b", "c"],
+    /// );
+    /// assert_eq!("x12y345".replace(digits.as_pattern(), "#"), "x#y#");
+    /// ```
+    fn as_pattern(self) -> AsPattern<Self> {
+        AsPattern { parser: self }
+    }
+
+    /// Skips the whitespace following the parser. See [`lexeme`](crate::lexeme)
+    /// for skipping comments as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use p_arse::{lexeme::ws, Parser};
+    ///
+    /// let list = (ws(), '['.lexeme(), "a".lexeme(), ']'.lexeme());
+    /// assert!(list.p_arse(" [ a\n]\n").unwrap().1.is_empty());
+    /// ```
+    fn lexeme(self) -> Lexeme<Self, Whitespace> {
+        lexer(ws()).lexeme(self)
+    }
+
     fn named(self, name: &'static str) -> Named<Self> {
         Named { parser: self, name }
     }
@@ -252,3 +607,64 @@ pub trait Parser: Sized + Copy {
         Debugged { parser: self }
     }
 }
+
+/// A parser used once, i.e. returned from the function passed to
+/// [`.bind()`](Parser::bind).
+///
+/// Unlike [`Parser`], it doesn't have to be [`Copy`].
+pub trait Continuation {
+    type Output;
+
+    /// Consumes the continuation and attempts to parse the input.
+    fn p_arse_once<'a>(self, tail: &'a str) -> Result<'a, Self::Output>;
+}
+
+impl<P> Continuation for P
+where
+    P: Parser,
+{
+    type Output = P::Output;
+
+    fn p_arse_once<'a>(self, tail: &'a str) -> Result<'a, Self::Output> {
+        self.p_arse(tail)
+    }
+}
+
+/// An owned string, corresponding to a literal built at runtime.
+impl Continuation for String {
+    type Output = String;
+
+    fn p_arse_once<'a>(self, tail: &'a str) -> Result<'a, Self::Output> {
+        let ((), tail) = self.as_str().ignore().p_arse(tail)?;
+
+        Ok((self, tail))
+    }
+}
+
+/// A sequence of continuations of any length.
+impl<C> Continuation for Vec<C>
+where
+    C: Continuation,
+{
+    type Output = Vec<C::Output>;
+
+    fn p_arse_once<'a>(self, mut tail: &'a str) -> Result<'a, Self::Output> {
+        let mut output = Vec::with_capacity(self.len());
+
+        for continuation in self {
+            let (output_i, tail_i) = continuation.p_arse_once(tail)?;
+            tail = tail_i;
+            output.push(output_i);
+        }
+
+        Ok((output, tail))
+    }
+}
+
+impl<'f, T> Continuation for Box<dyn Fun<T> + 'f> {
+    type Output = T;
+
+    fn p_arse_once<'a>(self, tail: &'a str) -> Result<'a, Self::Output> {
+        (self)(tail)
+    }
+}
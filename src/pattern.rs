@@ -0,0 +1,127 @@
+//! Implementation of [`Pattern`] for parsers.
+
+use std::str::pattern::{Pattern, SearchStep, Searcher};
+
+use crate::{
+    search::{matched_len, FirstSet},
+    Parser,
+};
+
+/// A [`Parser`] used as a [`Pattern`], returned by
+/// [`.as_pattern()`](Parser::as_pattern).
+#[derive(Copy, Clone)]
+pub struct AsPattern<P>
+where
+    P: Parser,
+{
+    pub(crate) parser: P,
+}
+
+impl<P> Pattern for AsPattern<P>
+where
+    P: Parser,
+{
+    type Searcher<'a> = ParserSearcher<'a, P>;
+
+    fn into_searcher(self, haystack: &str) -> Self::Searcher<'_> {
+        ParserSearcher {
+            parser: self.parser,
+            first_set: self.parser.first_set(),
+            haystack,
+            position: 0,
+            matched_empty: false,
+            finished: false,
+        }
+    }
+
+    fn is_prefix_of(self, haystack: &str) -> bool {
+        self.strip_prefix_of(haystack).is_some()
+    }
+
+    fn strip_prefix_of(self, haystack: &str) -> Option<&str> {
+        self.parser
+            .p_arse(haystack)
+            .ok()
+            .map(|(_, tail)| tail)
+            .filter(|tail| matched_len(haystack, tail).is_some())
+    }
+}
+
+/// The [`Searcher`] of [`AsPattern`].
+///
+/// At each position the parser is matched against the rest of the haystack.
+/// After a successful match the search continues at its end, otherwise it
+/// skips one character, or all the characters outside of the parser's
+/// [first set](Parser::first_set). Matches which don't return a suffix of the
+/// haystack, which only faulty parsers do, are rejected.
+pub struct ParserSearcher<'a, P>
+where
+    P: Parser,
+{
+    parser: P,
+    first_set: Option<FirstSet>,
+    haystack: &'a str,
+    position: usize,
+    // Prevents matching the empty string at the same position forever.
+    matched_empty: bool,
+    finished: bool,
+}
+
+unsafe impl<'a, P> Searcher<'a> for ParserSearcher<'a, P>
+where
+    P: Parser,
+{
+    fn haystack(&self) -> &'a str {
+        self.haystack
+    }
+
+    fn next(&mut self) -> SearchStep {
+        if self.finished {
+            return SearchStep::Done;
+        }
+
+        let start = self.position;
+        let tail = &self.haystack[start..];
+
+        if !self.matched_empty {
+            let matched = self.parser.p_arse(tail).ok();
+
+            if let Some(len) =
+                matched.and_then(|(_, new_tail)| matched_len(tail, new_tail))
+            {
+                let end = start + len;
+                self.position = end;
+                self.matched_empty = end == start;
+
+                return SearchStep::Match(start, end);
+            }
+        }
+
+        self.matched_empty = false;
+
+        if let Some(first_set) = &self.first_set {
+            // Reject everything up to the next character which may begin a
+            // match.
+            let skipped = first_set.skip(tail).unwrap_or(tail.len());
+
+            if skipped > 0 {
+                self.position += skipped;
+
+                return SearchStep::Reject(start, self.position);
+            }
+        }
+
+        match tail.chars().next() {
+            Some(c) => {
+                self.position += c.len_utf8();
+
+                SearchStep::Reject(start, self.position)
+            }
+            None => {
+                self.finished = true;
+
+                SearchStep::Done
+            }
+        }
+    }
+}
@@ -0,0 +1,373 @@
+//! Searching for matches anywhere in the input.
+
+use std::{borrow::Cow, ops::Range};
+
+use crate::Parser;
+
+/// A set of characters, used to skip the positions where a parser can't
+/// match. See [`Parser::first_set`].
+///
+/// Like in [`CharClass`](crate::CharClass), the ASCII characters are kept in
+/// a bitset, so that sets of ASCII characters are built without allocating
+/// and searched for byte by byte.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FirstSet {
+    // Bit `n` is set iff the character `n` belongs to the set.
+    pub(crate) ascii: u128,
+    // Sorted, non-overlapping and non-adjacent ranges of non-ASCII
+    // characters.
+    pub(crate) non_ascii: Vec<(char, char)>,
+}
+
+pub(crate) const FIRST_NON_ASCII: char = '\u{80}';
+
+impl FirstSet {
+    /// A set containing a single character.
+    pub fn char(c: char) -> Self {
+        Self::range(c, c)
+    }
+
+    /// A set containing the characters from `from` to `to` (inclusive).
+    pub fn range(from: char, to: char) -> Self {
+        Self::from_ranges(Some((from, to)))
+    }
+
+    /// A set containing the characters from the ranges.
+    pub fn from_ranges<I>(ranges: I) -> Self
+    where
+        I: IntoIterator<Item = (char, char)>,
+    {
+        let mut ascii = 0;
+        let mut non_ascii = vec![];
+
+        for (from, to) in ranges {
+            if from > to {
+                continue;
+            }
+
+            if from < FIRST_NON_ASCII {
+                let to_ascii = (to as u32).min(0x7F);
+
+                for c in from as u32..=to_ascii {
+                    ascii |= 1 << c;
+                }
+            }
+
+            if to >= FIRST_NON_ASCII {
+                non_ascii.push((from.max(FIRST_NON_ASCII), to));
+            }
+        }
+
+        FirstSet {
+            ascii,
+            non_ascii: merge(non_ascii),
+        }
+    }
+
+    /// The union of two sets.
+    pub fn union(&self, other: &Self) -> Self {
+        let non_ascii = self.non_ascii.iter().chain(&other.non_ascii);
+
+        FirstSet {
+            ascii: self.ascii | other.ascii,
+            non_ascii: merge(non_ascii.copied().collect()),
+        }
+    }
+
+    pub fn contains(&self, c: char) -> bool {
+        if c < FIRST_NON_ASCII {
+            self.ascii & (1 << c as u32) != 0
+        } else {
+            self.non_ascii
+                .binary_search_by(|&(from, to)| {
+                    if to < c {
+                        std::cmp::Ordering::Less
+                    } else if from > c {
+                        std::cmp::Ordering::Greater
+                    } else {
+                        std::cmp::Ordering::Equal
+                    }
+                })
+                .is_ok()
+        }
+    }
+
+    /// The sorted, non-overlapping inclusive ranges of the set.
+    pub fn ranges(&self) -> Vec<(char, char)> {
+        let mut ranges = vec![];
+        let mut c = 0;
+
+        while c < 0x80 {
+            if self.ascii & (1 << c) != 0 {
+                let from = c;
+                while c + 1 < 0x80 && self.ascii & (1 << (c + 1)) != 0 {
+                    c += 1;
+                }
+                ranges.push((char::from(from as u8), char::from(c as u8)));
+            }
+            c += 1;
+        }
+
+        match (ranges.last_mut(), self.non_ascii.first()) {
+            (Some((_, last_to)), Some(&(FIRST_NON_ASCII, to)))
+                if *last_to == '\u{7F}' =>
+            {
+                *last_to = to;
+                ranges.extend_from_slice(&self.non_ascii[1..]);
+            }
+            _ => ranges.extend_from_slice(&self.non_ascii),
+        }
+
+        ranges
+    }
+
+    /// Returns the set as a bitset if it contains only ASCII characters.
+    pub(crate) fn ascii_only(&self) -> Option<u128> {
+        if self.non_ascii.is_empty() {
+            Some(self.ascii)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the byte offset of the first character of `haystack` in the
+    /// set.
+    pub(crate) fn skip(&self, haystack: &str) -> Option<usize> {
+        match self.ascii_only() {
+            Some(ascii) => skip_ascii(ascii, haystack),
+            None => haystack.find(|c| self.contains(c)),
+        }
+    }
+}
+
+/// Returns the byte offset of the first character of `haystack` in the
+/// bitset of ASCII characters.
+pub(crate) fn skip_ascii(ascii: u128, haystack: &str) -> Option<usize> {
+    // ASCII bytes never occur inside other characters.
+    haystack
+        .bytes()
+        .position(|b| b < 0x80 && ascii & (1 << b) != 0)
+}
+
+// Sorts and merges the overlapping and adjacent ranges.
+fn merge(mut ranges: Vec<(char, char)>) -> Vec<(char, char)> {
+    ranges.sort_unstable();
+
+    let mut merged: Vec<(char, char)> = Vec::with_capacity(ranges.len());
+    for (from, to) in ranges {
+        match merged.last_mut() {
+            Some((_, last_to)) if (*last_to as u32) + 1 >= from as u32 => {
+                *last_to = (*last_to).max(to);
+            }
+            _ => merged.push((from, to)),
+        }
+    }
+
+    merged
+}
+
+/// Unions the first sets of two alternatives.
+pub(crate) fn union(
+    set_0: Option<FirstSet>,
+    set_1: Option<FirstSet>,
+) -> Option<FirstSet> {
+    Some(set_0?.union(&set_1?))
+}
+
+/// Returns the length of the match of a parser which returned `new_tail` after
+/// parsing `tail`, or [`None`] if `new_tail` isn't a suffix of `tail` starting
+/// at a character boundary, which only a faulty parser can return.
+pub(crate) fn matched_len(tail: &str, new_tail: &str) -> Option<usize> {
+    let len = tail.len().checked_sub(new_tail.len())?;
+
+    if tail.as_ptr().wrapping_add(len) == new_tail.as_ptr()
+        && tail.is_char_boundary(len)
+    {
+        Some(len)
+    } else {
+        None
+    }
+}
+
+/// A match found by [`Parser::find`] or [`Parser::find_iter`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match<'a, T> {
+    haystack: &'a str,
+    start: usize,
+    end: usize,
+    /// The parser's output.
+    pub output: T,
+}
+
+impl<'a, T> Match<'a, T> {
+    /// The byte offset of the start of the match.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// The byte offset of the end of the match.
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// The matched slice of the haystack.
+    pub fn as_str(&self) -> &'a str {
+        &self.haystack[self.start..self.end]
+    }
+}
+
+/// An iterator over the non-overlapping matches of a parser, returned by
+/// [`Parser::find_iter`].
+pub struct FindIter<'a, P>
+where
+    P: Parser,
+{
+    parser: P,
+    first_set: Option<FirstSet>,
+    haystack: &'a str,
+    position: usize,
+    // Prevents matching the empty string at the same position forever.
+    matched_empty: bool,
+}
+
+impl<'a, P> FindIter<'a, P>
+where
+    P: Parser,
+{
+    pub(crate) fn new(parser: P, haystack: &'a str) -> Self {
+        FindIter {
+            parser,
+            first_set: parser.first_set(),
+            haystack,
+            position: 0,
+            matched_empty: false,
+        }
+    }
+}
+
+impl<'a, P> Iterator for FindIter<'a, P>
+where
+    P: Parser,
+{
+    type Item = Match<'a, P::Output>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.position <= self.haystack.len() {
+            let tail = &self.haystack[self.position..];
+
+            if let Some(first_set) = &self.first_set {
+                self.position += first_set.skip(tail)?;
+            }
+
+            let start = self.position;
+            let tail = &self.haystack[start..];
+
+            if !self.matched_empty {
+                if let Ok((output, new_tail)) = self.parser.p_arse(tail) {
+                    if let Some(len) = matched_len(tail, new_tail) {
+                        let end = start + len;
+                        self.position = end;
+                        self.matched_empty = end == start;
+
+                        return Some(Match {
+                            haystack: self.haystack,
+                            start,
+                            end,
+                            output,
+                        });
+                    }
+                }
+            }
+
+            self.matched_empty = false;
+            self.position += tail.chars().next().map_or(1, char::len_utf8);
+        }
+
+        None
+    }
+}
+
+/// Replaces all non-overlapping matches of the parser with the strings
+/// returned by `f`.
+///
+/// `f` is called with the parser's output and the matched slice. If there are
+/// no matches, the haystack is returned without allocating.
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::{search::replace_all, CharExt, Parser};
+///
+/// let hex_d = ('0'.to('9')).or('a'.to('f')).or('A'.to('F'));
+/// let alnum = ('0'.to('9')).or('a'.to('z')).or('A'.to('Z'));
+/// let short_color = ('#', hex_d, hex_d, hex_d, alnum.not_ahead());
+///
+/// let css = "a { color: #FA0; } b { color: #c0ffee; }";
+/// let expanded = replace_all(css, short_color, |(_, r, g, b, _), _| {
+///     format!("#{0}{0}{1}{1}{2}{2}", r, g, b).to_lowercase()
+/// });
+///
+/// assert_eq!(expanded, "a { color: #ffaa00; } b { color: #c0ffee; }");
+/// ```
+pub fn replace_all<'a, P, F, R>(
+    haystack: &'a str,
+    parser: P,
+    f: F,
+) -> Cow<'a, str>
+where
+    P: Parser,
+    F: FnMut(P::Output, &'a str) -> R,
+    R: AsRef<str>,
+{
+    replacen(haystack, parser, usize::MAX, f)
+}
+
+/// Replaces the first `limit` non-overlapping matches of the parser with the
+/// strings returned by `f`.
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::{search::replacen, CharExt, Parser};
+///
+/// let ident = ('a'.to('z')).more();
+/// let redact = |_, matched: &str| "_".repeat(matched.len());
+/// let redacted = replacen("let x = y + z;", ident, 2, redact);
+///
+/// assert_eq!(redacted, "___ _ = y + z;");
+/// ```
+pub fn replacen<'a, P, F, R>(
+    haystack: &'a str,
+    parser: P,
+    limit: usize,
+    mut f: F,
+) -> Cow<'a, str>
+where
+    P: Parser,
+    F: FnMut(P::Output, &'a str) -> R,
+    R: AsRef<str>,
+{
+    let mut matches = parser.find_iter(haystack).take(limit).peekable();
+
+    if matches.peek().is_none() {
+        return Cow::Borrowed(haystack);
+    }
+
+    let mut replaced = String::with_capacity(haystack.len());
+    let mut last_end = 0;
+
+    for found in matches {
+        replaced.push_str(&haystack[last_end..found.start()]);
+        last_end = found.end();
+
+        let matched = found.as_str();
+        replaced.push_str(f(found.output, matched).as_ref());
+    }
+    replaced.push_str(&haystack[last_end..]);
+
+    Cow::Owned(replaced)
+}
@@ -1,10 +1,10 @@
 #![allow(clippy::type_complexity)]
 
-//! Sequences of up to 6 elements.
+//! Sequences of up to 6 elements and flattening of nested sequences.
 
-use duple::prelude::*;
+use duple::{prelude::*, TupleUnwrap};
 
-use crate::{Parser, Result};
+use crate::{describe::GrammarNode, search::FirstSet, Parser, Result};
 
 impl<P0> Parser for (P0,)
 where
@@ -12,6 +12,14 @@ where
 {
     type Output = P0::Output;
 
+    fn first_set(&self) -> Option<FirstSet> {
+        self.0.first_set()
+    }
+
+    fn describe(&self) -> GrammarNode {
+        self.0.describe()
+    }
+
     fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
         let (output_0, tail) = self.0.p_arse(tail)?;
 
@@ -26,6 +34,14 @@ where
 {
     type Output = (P0::Output, P1::Output);
 
+    fn first_set(&self) -> Option<FirstSet> {
+        self.0.first_set()
+    }
+
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::sequence(vec![self.0.describe(), self.1.describe()])
+    }
+
     fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
         let (output_0, tail) = self.0.p_arse(tail)?;
         let (output_1, tail) = self.1.p_arse(tail)?;
@@ -42,6 +58,18 @@ where
 {
     type Output = (P0::Output, P1::Output, P2::Output);
 
+    fn first_set(&self) -> Option<FirstSet> {
+        self.0.first_set()
+    }
+
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::sequence(vec![
+            self.0.describe(),
+            self.1.describe(),
+            self.2.describe(),
+        ])
+    }
+
     fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
         let (output_0, tail) = self.0.p_arse(tail)?;
         let (output_1, tail) = self.1.p_arse(tail)?;
@@ -60,6 +88,19 @@ where
 {
     type Output = (P0::Output, P1::Output, P2::Output, P3::Output);
 
+    fn first_set(&self) -> Option<FirstSet> {
+        self.0.first_set()
+    }
+
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::sequence(vec![
+            self.0.describe(),
+            self.1.describe(),
+            self.2.describe(),
+            self.3.describe(),
+        ])
+    }
+
     fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
         let (output_0, tail) = self.0.p_arse(tail)?;
         let (output_1, tail) = self.1.p_arse(tail)?;
@@ -80,6 +121,20 @@ where
 {
     type Output = (P0::Output, P1::Output, P2::Output, P3::Output, P4::Output);
 
+    fn first_set(&self) -> Option<FirstSet> {
+        self.0.first_set()
+    }
+
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::sequence(vec![
+            self.0.describe(),
+            self.1.describe(),
+            self.2.describe(),
+            self.3.describe(),
+            self.4.describe(),
+        ])
+    }
+
     fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
         let (output_0, tail) = self.0.p_arse(tail)?;
         let (output_1, tail) = self.1.p_arse(tail)?;
@@ -109,6 +164,21 @@ where
         P5::Output,
     );
 
+    fn first_set(&self) -> Option<FirstSet> {
+        self.0.first_set()
+    }
+
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::sequence(vec![
+            self.0.describe(),
+            self.1.describe(),
+            self.2.describe(),
+            self.3.describe(),
+            self.4.describe(),
+            self.5.describe(),
+        ])
+    }
+
     fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
         let (output_0, tail) = self.0.p_arse(tail)?;
         let (output_1, tail) = self.1.p_arse(tail)?;
@@ -166,6 +236,30 @@ pub trait TupleExt: Parser {
     {
         Remove5 { parser: self }
     }
+
+    /// Flattens the outputs of nested sequences into a single tuple.
+    ///
+    /// Elements which are sequences themselves (tuples or other flattened
+    /// sequences) are spliced into the output, elements which are not keep
+    /// their output. The flattened output can have at most 6 elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use p_arse::{Parser, TupleExt};
+    ///
+    /// let abc = ('a', ('b', 'c')).flatten();
+    /// let abcd = (abc, 'd').flatten();
+    ///
+    /// assert_eq!(abcd.p_arse("abcd").unwrap().0, ('a', 'b', 'c', 'd'));
+    /// ```
+    fn flatten(self) -> Flattened<Self>
+    where
+        Self: Flatten,
+        Self::Flat: TupleUnwrap,
+    {
+        Flattened { parser: self }
+    }
 }
 
 impl<P> TupleExt for P where P: Parser {}
@@ -186,6 +280,14 @@ where
 {
     type Output = <<P as Parser>::Output as TupleRemove0>::Removed;
 
+    fn first_set(&self) -> Option<FirstSet> {
+        self.parser.first_set()
+    }
+
+    fn describe(&self) -> GrammarNode {
+        self.parser.describe()
+    }
+
     fn p_arse<'a>(&self, input: &'a str) -> Result<'a, Self::Output> {
         self.parser
             .p_arse(input)
@@ -193,6 +295,13 @@ where
     }
 }
 
+impl<P> Leaf for Remove0<P>
+where
+    P: Parser,
+    P::Output: TupleRemove0,
+{
+}
+
 #[derive(Copy, Clone)]
 pub struct Remove1<P>
 where
@@ -209,6 +318,14 @@ where
 {
     type Output = <<P as Parser>::Output as TupleRemove1>::Removed;
 
+    fn first_set(&self) -> Option<FirstSet> {
+        self.parser.first_set()
+    }
+
+    fn describe(&self) -> GrammarNode {
+        self.parser.describe()
+    }
+
     fn p_arse<'a>(&self, input: &'a str) -> Result<'a, Self::Output> {
         self.parser
             .p_arse(input)
@@ -216,6 +333,13 @@ where
     }
 }
 
+impl<P> Leaf for Remove1<P>
+where
+    P: Parser,
+    P::Output: TupleRemove1,
+{
+}
+
 #[derive(Copy, Clone)]
 pub struct Remove2<P>
 where
@@ -232,6 +356,14 @@ where
 {
     type Output = <<P as Parser>::Output as TupleRemove2>::Removed;
 
+    fn first_set(&self) -> Option<FirstSet> {
+        self.parser.first_set()
+    }
+
+    fn describe(&self) -> GrammarNode {
+        self.parser.describe()
+    }
+
     fn p_arse<'a>(&self, input: &'a str) -> Result<'a, Self::Output> {
         self.parser
             .p_arse(input)
@@ -239,6 +371,13 @@ where
     }
 }
 
+impl<P> Leaf for Remove2<P>
+where
+    P: Parser,
+    P::Output: TupleRemove2,
+{
+}
+
 #[derive(Copy, Clone)]
 pub struct Remove3<P>
 where
@@ -255,6 +394,14 @@ where
 {
     type Output = <<P as Parser>::Output as TupleRemove3>::Removed;
 
+    fn first_set(&self) -> Option<FirstSet> {
+        self.parser.first_set()
+    }
+
+    fn describe(&self) -> GrammarNode {
+        self.parser.describe()
+    }
+
     fn p_arse<'a>(&self, input: &'a str) -> Result<'a, Self::Output> {
         self.parser
             .p_arse(input)
@@ -262,6 +409,13 @@ where
     }
 }
 
+impl<P> Leaf for Remove3<P>
+where
+    P: Parser,
+    P::Output: TupleRemove3,
+{
+}
+
 #[derive(Copy, Clone)]
 pub struct Remove4<P>
 where
@@ -278,6 +432,14 @@ where
 {
     type Output = <<P as Parser>::Output as TupleRemove4>::Removed;
 
+    fn first_set(&self) -> Option<FirstSet> {
+        self.parser.first_set()
+    }
+
+    fn describe(&self) -> GrammarNode {
+        self.parser.describe()
+    }
+
     fn p_arse<'a>(&self, input: &'a str) -> Result<'a, Self::Output> {
         self.parser
             .p_arse(input)
@@ -285,6 +447,13 @@ where
     }
 }
 
+impl<P> Leaf for Remove4<P>
+where
+    P: Parser,
+    P::Output: TupleRemove4,
+{
+}
+
 #[derive(Copy, Clone)]
 pub struct Remove5<P>
 where
@@ -301,9 +470,302 @@ where
 {
     type Output = <<P as Parser>::Output as TupleRemove5>::Removed;
 
+    fn first_set(&self) -> Option<FirstSet> {
+        self.parser.first_set()
+    }
+
+    fn describe(&self) -> GrammarNode {
+        self.parser.describe()
+    }
+
     fn p_arse<'a>(&self, input: &'a str) -> Result<'a, Self::Output> {
         self.parser
             .p_arse(input)
             .map(|(tuple, tail)| (tuple.rem5(), tail))
     }
 }
+
+impl<P> Leaf for Remove5<P>
+where
+    P: Parser,
+    P::Output: TupleRemove5,
+{
+}
+
+/// Concatenation of cons lists, i.e. `(A, (B, ()))`.
+pub trait Append<R> {
+    type Appended;
+
+    fn append(self, rest: R) -> Self::Appended;
+}
+
+impl<R> Append<R> for () {
+    type Appended = R;
+
+    fn append(self, rest: R) -> Self::Appended {
+        rest
+    }
+}
+
+impl<H, T, R> Append<R> for (H, T)
+where
+    T: Append<R>,
+{
+    type Appended = (H, T::Appended);
+
+    fn append(self, rest: R) -> Self::Appended {
+        let (head, tail) = self;
+
+        (head, tail.append(rest))
+    }
+}
+
+/// Concatenation of a cons list of cons lists.
+pub trait Concat {
+    type Concatenated;
+
+    fn concat(self) -> Self::Concatenated;
+}
+
+impl Concat for () {
+    type Concatenated = ();
+
+    fn concat(self) -> Self::Concatenated {}
+}
+
+impl<H, T> Concat for (H, T)
+where
+    T: Concat,
+    H: Append<T::Concatenated>,
+{
+    type Concatenated = H::Appended;
+
+    fn concat(self) -> Self::Concatenated {
+        let (head, tail) = self;
+
+        head.append(tail.concat())
+    }
+}
+
+/// A [`Parser`] whose output can be spliced into a flattened sequence.
+///
+/// It's implemented for sequences, which splice the outputs of their elements,
+/// and for every [`Leaf`], which contributes its output as a single element.
+pub trait Flatten: Parser {
+    /// The outputs as a cons list, i.e. `(A, (B, ()))`.
+    type Flat;
+
+    fn p_arse_flat<'a>(&self, tail: &'a str) -> Result<'a, Self::Flat>;
+}
+
+/// A [`Parser`] which is not a sequence. Its output is not spliced by
+/// [`.flatten()`](TupleExt::flatten).
+///
+/// Implement it for custom parsers to use them in flattened sequences.
+pub trait Leaf: Parser {}
+
+impl<P> Flatten for P
+where
+    P: Leaf,
+{
+    type Flat = (P::Output, ());
+
+    fn p_arse_flat<'a>(&self, tail: &'a str) -> Result<'a, Self::Flat> {
+        self.p_arse(tail).map(|(output, tail)| ((output, ()), tail))
+    }
+}
+
+impl<P0> Flatten for (P0,)
+where
+    P0: Flatten,
+    (P0::Flat, ()): Concat,
+{
+    type Flat = <(P0::Flat, ()) as Concat>::Concatenated;
+
+    fn p_arse_flat<'a>(&self, tail: &'a str) -> Result<'a, Self::Flat> {
+        let (output_0, tail) = self.0.p_arse_flat(tail)?;
+
+        Ok(((output_0, ()).concat(), tail))
+    }
+}
+
+impl<P0, P1> Flatten for (P0, P1)
+where
+    P0: Flatten,
+    P1: Flatten,
+    (P0::Flat, (P1::Flat, ())): Concat,
+{
+    type Flat = <(P0::Flat, (P1::Flat, ())) as Concat>::Concatenated;
+
+    fn p_arse_flat<'a>(&self, tail: &'a str) -> Result<'a, Self::Flat> {
+        let (output_0, tail) = self.0.p_arse_flat(tail)?;
+        let (output_1, tail) = self.1.p_arse_flat(tail)?;
+
+        Ok(((output_0, (output_1, ())).concat(), tail))
+    }
+}
+
+impl<P0, P1, P2> Flatten for (P0, P1, P2)
+where
+    P0: Flatten,
+    P1: Flatten,
+    P2: Flatten,
+    (P0::Flat, (P1::Flat, (P2::Flat, ()))): Concat,
+{
+    type Flat =
+        <(P0::Flat, (P1::Flat, (P2::Flat, ()))) as Concat>::Concatenated;
+
+    fn p_arse_flat<'a>(&self, tail: &'a str) -> Result<'a, Self::Flat> {
+        let (output_0, tail) = self.0.p_arse_flat(tail)?;
+        let (output_1, tail) = self.1.p_arse_flat(tail)?;
+        let (output_2, tail) = self.2.p_arse_flat(tail)?;
+
+        Ok(((output_0, (output_1, (output_2, ()))).concat(), tail))
+    }
+}
+
+impl<P0, P1, P2, P3> Flatten for (P0, P1, P2, P3)
+where
+    P0: Flatten,
+    P1: Flatten,
+    P2: Flatten,
+    P3: Flatten,
+    (P0::Flat, (P1::Flat, (P2::Flat, (P3::Flat, ())))): Concat,
+{
+    type Flat = <(P0::Flat, (P1::Flat, (P2::Flat, (P3::Flat, ())))) as Concat>::Concatenated;
+
+    fn p_arse_flat<'a>(&self, tail: &'a str) -> Result<'a, Self::Flat> {
+        let (output_0, tail) = self.0.p_arse_flat(tail)?;
+        let (output_1, tail) = self.1.p_arse_flat(tail)?;
+        let (output_2, tail) = self.2.p_arse_flat(tail)?;
+        let (output_3, tail) = self.3.p_arse_flat(tail)?;
+
+        Ok((
+            (output_0, (output_1, (output_2, (output_3, ())))).concat(),
+            tail,
+        ))
+    }
+}
+
+impl<P0, P1, P2, P3, P4> Flatten for (P0, P1, P2, P3, P4)
+where
+    P0: Flatten,
+    P1: Flatten,
+    P2: Flatten,
+    P3: Flatten,
+    P4: Flatten,
+    (P0::Flat, (P1::Flat, (P2::Flat, (P3::Flat, (P4::Flat, ()))))): Concat,
+{
+    type Flat = <(P0::Flat, (P1::Flat, (P2::Flat, (P3::Flat, (P4::Flat, ()))))) as Concat>::Concatenated;
+
+    fn p_arse_flat<'a>(&self, tail: &'a str) -> Result<'a, Self::Flat> {
+        let (output_0, tail) = self.0.p_arse_flat(tail)?;
+        let (output_1, tail) = self.1.p_arse_flat(tail)?;
+        let (output_2, tail) = self.2.p_arse_flat(tail)?;
+        let (output_3, tail) = self.3.p_arse_flat(tail)?;
+        let (output_4, tail) = self.4.p_arse_flat(tail)?;
+
+        Ok((
+            (output_0, (output_1, (output_2, (output_3, (output_4, ())))))
+                .concat(),
+            tail,
+        ))
+    }
+}
+
+impl<P0, P1, P2, P3, P4, P5> Flatten for (P0, P1, P2, P3, P4, P5)
+where
+    P0: Flatten,
+    P1: Flatten,
+    P2: Flatten,
+    P3: Flatten,
+    P4: Flatten,
+    P5: Flatten,
+    (
+        P0::Flat,
+        (P1::Flat, (P2::Flat, (P3::Flat, (P4::Flat, (P5::Flat, ()))))),
+    ): Concat,
+{
+    type Flat = <(
+        P0::Flat,
+        (P1::Flat, (P2::Flat, (P3::Flat, (P4::Flat, (P5::Flat, ()))))),
+    ) as Concat>::Concatenated;
+
+    fn p_arse_flat<'a>(&self, tail: &'a str) -> Result<'a, Self::Flat> {
+        let (output_0, tail) = self.0.p_arse_flat(tail)?;
+        let (output_1, tail) = self.1.p_arse_flat(tail)?;
+        let (output_2, tail) = self.2.p_arse_flat(tail)?;
+        let (output_3, tail) = self.3.p_arse_flat(tail)?;
+        let (output_4, tail) = self.4.p_arse_flat(tail)?;
+        let (output_5, tail) = self.5.p_arse_flat(tail)?;
+
+        Ok((
+            (
+                output_0,
+                (output_1, (output_2, (output_3, (output_4, (output_5, ()))))),
+            )
+                .concat(),
+            tail,
+        ))
+    }
+}
+
+pub struct Flattened<P>
+where
+    P: Flatten,
+    P::Flat: TupleUnwrap,
+{
+    parser: P,
+}
+
+impl<P> Clone for Flattened<P>
+where
+    P: Flatten,
+    P::Flat: TupleUnwrap,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P> Copy for Flattened<P>
+where
+    P: Flatten,
+    P::Flat: TupleUnwrap,
+{
+}
+
+impl<P> Parser for Flattened<P>
+where
+    P: Flatten,
+    P::Flat: TupleUnwrap,
+{
+    type Output = <P::Flat as TupleUnwrap>::Unwrapped;
+
+    fn first_set(&self) -> Option<FirstSet> {
+        self.parser.first_set()
+    }
+
+    fn describe(&self) -> GrammarNode {
+        self.parser.describe()
+    }
+
+    fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        self.parser
+            .p_arse_flat(tail)
+            .map(|(flat, tail)| (flat.unwrap(), tail))
+    }
+}
+
+// A flattened sequence nested in another one is spliced as well.
+impl<P> Flatten for Flattened<P>
+where
+    P: Flatten,
+    P::Flat: TupleUnwrap,
+{
+    type Flat = P::Flat;
+
+    fn p_arse_flat<'a>(&self, tail: &'a str) -> Result<'a, Self::Flat> {
+        self.parser.p_arse_flat(tail)
+    }
+}
@@ -0,0 +1,274 @@
+//! User state threaded through parsing.
+//!
+//! The state is supplied with [`Parser::p_arse_with`] and can be accessed by
+//! [`.map_with_state()`](Parser::map_with_state),
+//! [`.try_map_with_state()`](Parser::try_map_with_state) and, using
+//! [`with_state`], by custom [`fun`](crate::fun) and [`rec`](crate::rec)
+//! parsers. When a parser backtracks (in [`.or()`](Parser::or),
+//! [`.opt()`](Parser::opt), repetitions and look-aheads), the changes made to
+//! the state by the failed branch are undone.
+//!
+//! Undoing changes needs copies of the state, which are made lazily: a
+//! backtracking point copies the state only if it's changed after it, and
+//! parsing without a state makes no copies at all. States which are expensive
+//! to clone and changed often can be made cheaper to copy by sharing their
+//! unchanged parts, e.g. with [`Rc`](std::rc::Rc).
+
+use std::{
+    any::{type_name, Any},
+    cell::{Cell, RefCell},
+};
+
+use crate::{Error, Parser, Result};
+
+/// A type which can be used as the user state. It's implemented for every
+/// [`Clone`] type without non-`'static` references.
+pub trait State: Any {
+    fn boxed_clone(&self) -> Box<dyn State>;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+impl<S> State for S
+where
+    S: Any + Clone,
+{
+    fn boxed_clone(&self) -> Box<dyn State> {
+        Box::new(self.clone())
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+// The state supplied to a `.p_arse_with()` and the copies needed to undo its
+// changes.
+struct Frame {
+    // Taken out while it's accessed by `with_state`.
+    state: Option<Box<dyn State>>,
+    // The number of checkpoints which are neither restored nor dropped.
+    checkpoints: usize,
+    // The copies of the state at the checkpoints, by increasing checkpoint
+    // number. The state is copied at the first change after a checkpoint, so
+    // a checkpoint without a copy has the state of the next one (or the
+    // current state).
+    copies: Vec<(usize, Box<dyn State>)>,
+}
+
+impl Frame {
+    fn last_copy(&self) -> Option<usize> {
+        self.copies.last().map(|(number, _)| *number)
+    }
+
+    // Takes the state out to change it, copying it for the innermost
+    // checkpoint if needed.
+    fn take_state<S: 'static>(&mut self) -> Option<Box<dyn State>> {
+        let mut state = self.state.take()?;
+
+        if !state.as_any_mut().is::<S>() {
+            self.state = Some(state);
+
+            return None;
+        }
+
+        if self.checkpoints > 0 && self.last_copy() != Some(self.checkpoints) {
+            self.copies.push((self.checkpoints, (*state).boxed_clone()));
+        }
+
+        Some(state)
+    }
+
+    // Keeps the changes made since the checkpoint `number`.
+    fn release(&mut self, number: usize) {
+        if self.last_copy() == Some(number) {
+            let copy = self.copies.pop().map(|(_, copy)| copy);
+
+            // The copy is also the state at the previous checkpoint if it
+            // hasn't got a copy of its own.
+            if number > 1 && self.last_copy() != Some(number - 1) {
+                self.copies.extend(copy.map(|copy| (number - 1, copy)));
+            }
+        }
+
+        self.checkpoints = number - 1;
+    }
+
+    // Undoes the changes made since the checkpoint `number`.
+    fn restore(&mut self, number: usize) {
+        if self.last_copy() == Some(number) {
+            self.state = self.copies.pop().map(|(_, copy)| copy);
+        }
+
+        self.checkpoints = number - 1;
+    }
+}
+
+thread_local! {
+    // The number of frames, to skip the checkpoints without a state.
+    static INSTALLED: Cell<usize> = const { Cell::new(0) };
+    static FRAMES: RefCell<Vec<Frame>> = const { RefCell::new(vec![]) };
+}
+
+fn installed() -> bool {
+    INSTALLED.with(Cell::get) > 0
+}
+
+fn with_frame<F>(f: F)
+where
+    F: FnOnce(&mut Frame),
+{
+    FRAMES.with(|frames| {
+        if let Some(frame) = frames.borrow_mut().last_mut() {
+            f(frame);
+        }
+    });
+}
+
+/// Gives access to the state supplied to the innermost
+/// [`.p_arse_with()`](Parser::p_arse_with).
+///
+/// Returns [`None`] if there's no state, if it's not of type `S` or if it's
+/// already accessed, i.e. when called by a parser run inside `f`.
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::{fun, state::with_state, Parser};
+///
+/// let counted = fun(&|tail: &str| {
+///     let (a, tail) = 'a'.p_arse(tail)?;
+///     with_state(|count: &mut usize| *count += 1);
+///
+///     Ok((a, tail))
+/// });
+///
+/// let mut count: usize = 0;
+/// counted.more().p_arse_with("aaa", &mut count).unwrap();
+///
+/// assert_eq!(count, 3);
+/// ```
+pub fn with_state<S, R, F>(f: F) -> Option<R>
+where
+    S: 'static,
+    F: FnOnce(&mut S) -> R,
+{
+    if !installed() {
+        return None;
+    }
+
+    let mut state = FRAMES
+        .with(|frames| frames.borrow_mut().last_mut()?.take_state::<S>())?;
+    let result = state.as_any_mut().downcast_mut::<S>().map(f);
+    with_frame(|frame| frame.state = Some(state));
+
+    result
+}
+
+/// A point to which the changes of the state can be undone when
+/// backtracking. Dropping it keeps the changes.
+pub(crate) struct Checkpoint(Option<usize>);
+
+pub(crate) fn checkpoint() -> Checkpoint {
+    if !installed() {
+        return Checkpoint(None);
+    }
+
+    FRAMES.with(|frames| {
+        Checkpoint(frames.borrow_mut().last_mut().map(|frame| {
+            frame.checkpoints += 1;
+
+            frame.checkpoints
+        }))
+    })
+}
+
+impl Checkpoint {
+    /// Undoes the changes made since the checkpoint.
+    pub(crate) fn restore(mut self) {
+        if let Some(number) = self.0.take() {
+            with_frame(|frame| frame.restore(number));
+        }
+    }
+
+    /// Keeps the changes made so far and moves the checkpoint to the current
+    /// state.
+    pub(crate) fn update(&mut self) {
+        if let Some(number) = self.0 {
+            with_frame(|frame| {
+                frame.release(number);
+                frame.checkpoints += 1;
+            });
+        }
+    }
+}
+
+impl Drop for Checkpoint {
+    fn drop(&mut self) {
+        if let Some(number) = self.0 {
+            with_frame(|frame| frame.release(number));
+        }
+    }
+}
+
+/// Removes the state from the stack even if parsing panics.
+struct Installed;
+
+impl Installed {
+    fn install(state: Box<dyn State>) -> Self {
+        let frame = Frame {
+            state: Some(state),
+            checkpoints: 0,
+            copies: vec![],
+        };
+        FRAMES.with(|frames| frames.borrow_mut().push(frame));
+        INSTALLED.with(|installed| installed.set(installed.get() + 1));
+
+        Installed
+    }
+
+    fn uninstall(self) -> Option<Box<dyn State>> {
+        std::mem::forget(self);
+        INSTALLED.with(|installed| installed.set(installed.get() - 1));
+
+        FRAMES.with(|frames| frames.borrow_mut().pop()?.state)
+    }
+}
+
+impl Drop for Installed {
+    fn drop(&mut self) {
+        INSTALLED.with(|installed| installed.set(installed.get() - 1));
+        FRAMES.with(|frames| frames.borrow_mut().pop());
+    }
+}
+
+pub(crate) fn p_arse_with<'a, P, S>(
+    parser: &P,
+    tail: &'a str,
+    state: &mut S,
+) -> Result<'a, P::Output>
+where
+    P: Parser,
+    S: Clone + 'static,
+{
+    let installed = Installed::install(Box::new(state.clone()));
+    let result = parser.p_arse(tail);
+
+    if let Some(final_state) = installed.uninstall() {
+        if let Ok(final_state) = final_state.into_any().downcast::<S>() {
+            *state = *final_state;
+        }
+    }
+
+    result
+}
+
+pub(crate) fn missing<'a, S>(tail: &'a str) -> Error<'a> {
+    Error::expecting(format!("state of type `{}`", type_name::<S>()), tail)
+}
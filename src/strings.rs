@@ -0,0 +1,360 @@
+//! Quoted string literals.
+//!
+//! [`StringLiteral`] is configured with the quotes, the escape sequences and
+//! whether the literal can span multiple lines. [`json_string`],
+//! [`rust_string`] and [`raw_string`] are preconfigured.
+//!
+//! Since the [`Parser`] outputs can't borrow the input, [`Parser::p_arse`]
+//! outputs an owned [`String`]. [`StringLiteral::p_arse_cow`] borrows the
+//! content from the input unless the literal contains escape sequences.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::borrow::Cow;
+//!
+//! use p_arse::{strings::json_string, Parser};
+//!
+//! let string = json_string();
+//!
+//! let (plain, _) = string.p_arse_cow(r#""plain""#).unwrap();
+//! assert!(matches!(plain, Cow::Borrowed("plain")));
+//!
+//! let input = r#""tab\t, \u00e9, \ud83d\ude00""#;
+//! let (escaped, _) = string.p_arse_cow(input).unwrap();
+//! assert_eq!(escaped, "tab\t, é, 😀");
+//!
+//! assert!(string.p_arse(r#""lone \ud83d""#).is_err());
+//! assert!(string.p_arse("\"unterminated").is_err());
+//! ```
+
+use std::borrow::Cow;
+
+use crate::{
+    describe::GrammarNode,
+    search::FirstSet,
+    sequence::Leaf,
+    Error,
+    Parser,
+    Result,
+};
+
+/// Escape sequences denoting characters by their code.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum UnicodeEscape {
+    /// No such escapes.
+    None,
+    /// `\uXXXX`, where characters outside of the Basic Multilingual Plane are
+    /// encoded as UTF-16 surrogate pairs, e.g. `\ud83d\ude00`, like in JSON
+    /// and JavaScript.
+    Utf16,
+    /// `\u{X}` with 1 to 6 hexadecimal digits, like in Rust.
+    Braced,
+}
+
+/// A [`Parser`] matching a quoted string literal and outputting its content.
+#[derive(Copy, Clone)]
+pub struct StringLiteral<'s> {
+    prefix: &'s str,
+    quotes: &'s str,
+    escape: Option<char>,
+    escapes: &'s [(char, char)],
+    unicode: UnicodeEscape,
+    hex: bool,
+    multi_line: bool,
+    raw_hashes: bool,
+}
+
+const JSON_ESCAPES: &[(char, char)] = &[
+    ('"', '"'),
+    ('\\', '\\'),
+    ('/', '/'),
+    ('b', '\x08'),
+    ('f', '\x0C'),
+    ('n', '\n'),
+    ('r', '\r'),
+    ('t', '\t'),
+];
+
+const RUST_ESCAPES: &[(char, char)] = &[
+    ('"', '"'),
+    ('\'', '\''),
+    ('\\', '\\'),
+    ('0', '\0'),
+    ('n', '\n'),
+    ('r', '\r'),
+    ('t', '\t'),
+];
+
+/// A single-line literal in double quotes, with the escape sequences of
+/// JSON: `\"`, `\\`, `\/`, `\b`, `\f`, `\n`, `\r`, `\t` and `\uXXXX`.
+pub fn json_string() -> StringLiteral<'static> {
+    StringLiteral {
+        prefix: "",
+        quotes: "\"",
+        escape: Some('\\'),
+        escapes: JSON_ESCAPES,
+        unicode: UnicodeEscape::Utf16,
+        hex: false,
+        multi_line: false,
+        raw_hashes: false,
+    }
+}
+
+/// A literal in double quotes, with the escape sequences of Rust: `\"`,
+/// `\'`, `\\`, `\0`, `\n`, `\r`, `\t`, `\x7F` and `\u{7FFF}`. It can span
+/// multiple lines.
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::{strings::rust_string, Parser};
+///
+/// let string = rust_string();
+/// let (content, _) = string.p_arse(r#""\x41\u{1F980}\0""#).unwrap();
+/// assert_eq!(content, "A🦀\0");
+/// assert!(string.p_arse(r#""\x80""#).is_err());
+/// ```
+pub fn rust_string() -> StringLiteral<'static> {
+    StringLiteral {
+        escapes: RUST_ESCAPES,
+        unicode: UnicodeEscape::Braced,
+        hex: true,
+        multi_line: true,
+        ..json_string()
+    }
+}
+
+/// A raw literal of Rust, e.g. `r"C:\"` or `r#"say "hi""#`, without any
+/// escape sequences.
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::{strings::raw_string, Parser};
+///
+/// let string = raw_string();
+/// assert_eq!(string.p_arse(r#"r"C:\""#).unwrap().0, r"C:\");
+/// assert_eq!(
+///     string.p_arse(r###"r##"a "# b"##"###).unwrap().0,
+///     r##"a "# b"##,
+/// );
+/// ```
+pub fn raw_string() -> StringLiteral<'static> {
+    StringLiteral {
+        prefix: "r",
+        escape: None,
+        unicode: UnicodeEscape::None,
+        hex: false,
+        raw_hashes: true,
+        ..rust_string()
+    }
+}
+
+impl<'s> StringLiteral<'s> {
+    /// Sets the characters which can open a literal. A literal is closed by
+    /// the same character which opened it.
+    pub fn quotes(self, quotes: &'s str) -> Self {
+        StringLiteral { quotes, ..self }
+    }
+
+    /// Sets the text preceding the opening quote, e.g. `"r"`.
+    pub fn prefix(self, prefix: &'s str) -> Self {
+        StringLiteral { prefix, ..self }
+    }
+
+    /// Sets the character beginning the escape sequences, or disables them.
+    pub fn escape(self, escape: Option<char>) -> Self {
+        StringLiteral { escape, ..self }
+    }
+
+    /// Sets the escaped characters, e.g. `('n', '\n')` for `\n`.
+    pub fn escapes(self, escapes: &'s [(char, char)]) -> Self {
+        StringLiteral { escapes, ..self }
+    }
+
+    pub fn unicode(self, unicode: UnicodeEscape) -> Self {
+        StringLiteral { unicode, ..self }
+    }
+
+    /// Enables the `\xHH` escapes of ASCII characters.
+    pub fn hex(self, hex: bool) -> Self {
+        StringLiteral { hex, ..self }
+    }
+
+    /// Allows line breaks in the literal.
+    pub fn multi_line(self, multi_line: bool) -> Self {
+        StringLiteral { multi_line, ..self }
+    }
+
+    /// Like [`Parser::p_arse`], but borrows the content from the input, unless
+    /// it contains escape sequences.
+    pub fn p_arse_cow<'a>(&self, tail: &'a str) -> Result<'a, Cow<'a, str>> {
+        let start = tail;
+        let tail = tail.strip_prefix(self.prefix).ok_or_else(|| {
+            Error::expecting(format!("'{}'", self.prefix), start)
+        })?;
+
+        let hashes = if self.raw_hashes {
+            tail.len() - tail.trim_start_matches('#').len()
+        } else {
+            0
+        };
+        let tail = &tail[hashes..];
+
+        let mut chars = tail.chars();
+        let quote = chars
+            .next()
+            .filter(|&c| self.quotes.contains(c))
+            .ok_or_else(|| Error::expecting("opening quote", tail))?;
+        let content = chars.as_str();
+        let mut owned: Option<String> = None;
+        let mut rest = content;
+
+        loop {
+            let mut chars = rest.chars();
+            let c = chars
+                .next()
+                .ok_or_else(|| Error::expecting("closing quote", rest))?;
+
+            if c == quote {
+                let after = chars.as_str();
+
+                if let Some(after) = after
+                    .get(..hashes)
+                    .filter(|closing| closing.bytes().all(|b| b == b'#'))
+                    .map(|_| &after[hashes..])
+                {
+                    let output = match owned {
+                        Some(owned) => Cow::Owned(owned),
+                        None => Cow::Borrowed(
+                            &content[..content.len() - rest.len()],
+                        ),
+                    };
+
+                    return Ok((output, after));
+                }
+            }
+
+            if c == '\n' && !self.multi_line {
+                return Err(Error::expecting("closing quote", rest));
+            }
+
+            if Some(c) == self.escape {
+                let owned = owned.get_or_insert_with(|| {
+                    content[..content.len() - rest.len()].to_string()
+                });
+                let (escaped, after) = self.p_arse_escape(rest)?;
+                owned.push(escaped);
+                rest = after;
+            } else {
+                if let Some(owned) = &mut owned {
+                    owned.push(c);
+                }
+                rest = chars.as_str();
+            }
+        }
+    }
+
+    // Parses an escape sequence, including the escape character.
+    fn p_arse_escape<'a>(&self, sequence: &'a str) -> Result<'a, char> {
+        let error = || Error::expecting("escape sequence", sequence);
+        let mut chars = sequence.chars();
+        chars.next();
+        let c = chars.next().ok_or_else(error)?;
+        let tail = chars.as_str();
+
+        if let Some(&(_, escaped)) = self.escapes.iter().find(|(e, _)| *e == c)
+        {
+            return Ok((escaped, tail));
+        }
+
+        // The quotes and the escape character can always be escaped.
+        if self.quotes.contains(c) || Some(c) == self.escape {
+            return Ok((c, tail));
+        }
+
+        match c {
+            'x' if self.hex => {
+                let code = hex_digits(tail, 2, 2).filter(|&code| code < 0x80);
+                let code = code.ok_or_else(error)?;
+
+                Ok((char::from(code as u8), &tail[2..]))
+            }
+            'u' if self.unicode == UnicodeEscape::Utf16 => {
+                let high = hex_digits(tail, 4, 4).ok_or_else(error)?;
+                let tail = &tail[4..];
+
+                if !(0xD800..0xDC00).contains(&high) {
+                    let c = char::from_u32(high).ok_or_else(error)?;
+
+                    return Ok((c, tail));
+                }
+
+                let low = tail
+                    .strip_prefix("\\u")
+                    .and_then(|low| hex_digits(low, 4, 4))
+                    .filter(|low| (0xDC00..0xE000).contains(low))
+                    .ok_or_else(error)?;
+                let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+
+                Ok((char::from_u32(code).unwrap(), &tail[6..]))
+            }
+            'u' if self.unicode == UnicodeEscape::Braced => {
+                let digits = tail
+                    .strip_prefix('{')
+                    .and_then(|digits| digits.split_once('}'))
+                    .map(|(digits, _)| digits)
+                    .ok_or_else(error)?;
+                let c = hex_digits(digits, 1, 6)
+                    .filter(|_| digits.len() <= 6)
+                    .and_then(char::from_u32)
+                    .ok_or_else(error)?;
+
+                Ok((c, &tail[digits.len() + 2..]))
+            }
+            _ => Err(error()),
+        }
+    }
+}
+
+// The value of the hexadecimal digits prefixing the input, if there are
+// between `min` and `max` of them, or the input ends after `max` of them.
+fn hex_digits(tail: &str, min: usize, max: usize) -> Option<u32> {
+    let digits = tail
+        .bytes()
+        .take(max)
+        .take_while(u8::is_ascii_hexdigit)
+        .count();
+
+    if digits < min || (digits < max && digits < tail.len()) {
+        return None;
+    }
+
+    u32::from_str_radix(&tail[..digits], 16).ok()
+}
+
+impl Parser for StringLiteral<'_> {
+    type Output = String;
+
+    fn first_set(&self) -> Option<FirstSet> {
+        match self.prefix.chars().next() {
+            Some(first) => Some(FirstSet::char(first)),
+            None => {
+                Some(FirstSet::from_ranges(self.quotes.chars().map(|c| (c, c))))
+            }
+        }
+    }
+
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::Opaque("string literal".to_string())
+    }
+
+    fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        let (content, tail) = self.p_arse_cow(tail)?;
+
+        Ok((content.into_owned(), tail))
+    }
+}
+
+impl Leaf for StringLiteral<'_> {}
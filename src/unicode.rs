@@ -0,0 +1,259 @@
+//! Unicode properties: general categories, scripts, `XID_Start`,
+//! `XID_Continue` and `Pattern_White_Space`.
+//!
+//! Available with the `unicode` feature. The tables are generated from
+//! Unicode 17.0.0 by `tools/unicode_tables.py`.
+//!
+//! # Examples
+//!
+//! Rust- or Python-style identifiers:
+//!
+//! ```
+//! use p_arse::{unicode::{xid_continue, xid_start}, Parser};
+//!
+//! let ident = (xid_start().or('_'), xid_continue().zore())
+//!     .maps(|s| s.to_string());
+//!
+//! assert_eq!(ident.p_arse("źdźbło = 1").unwrap().0, "źdźbło");
+//! assert_eq!(ident.p_arse("_private").unwrap().0, "_private");
+//! assert!(ident.p_arse("1st").is_err());
+//! ```
+
+mod tables;
+
+use crate::{
+    class::CharClass,
+    describe::GrammarNode,
+    search::FirstSet,
+    sequence::Leaf,
+    Error,
+    Parser,
+    Result,
+};
+
+/// A [`Parser`] matching a single character with a Unicode property.
+///
+/// It can be converted into a [`CharClass`] to be combined with other
+/// classes.
+#[derive(Copy, Clone)]
+pub struct Property {
+    name: &'static str,
+    // The property is the union of the tables.
+    tables: &'static [tables::Ranges],
+}
+
+impl Property {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn contains(&self, c: char) -> bool {
+        self.tables.iter().any(|table| {
+            table
+                .binary_search_by(|&(from, to)| {
+                    if to < c {
+                        std::cmp::Ordering::Less
+                    } else if from > c {
+                        std::cmp::Ordering::Greater
+                    } else {
+                        std::cmp::Ordering::Equal
+                    }
+                })
+                .is_ok()
+        })
+    }
+
+    fn ranges(&self) -> impl Iterator<Item = (char, char)> {
+        self.tables.iter().flat_map(|table| table.iter().copied())
+    }
+}
+
+impl Parser for Property {
+    type Output = char;
+
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::Class(CharClass::from(*self).ranges())
+    }
+
+    fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        let mut chars = tail.chars();
+
+        match chars.next() {
+            Some(first) if self.contains(first) => Ok((first, chars.as_str())),
+            _ => Err(Error::expecting(format!("char {}", self.name), tail)),
+        }
+    }
+
+    fn first_set(&self) -> Option<FirstSet> {
+        Some(FirstSet::from_ranges(self.ranges()))
+    }
+}
+
+impl Leaf for Property {}
+
+impl From<Property> for CharClass {
+    fn from(property: Property) -> Self {
+        CharClass::from_ranges(property.ranges()).described(property.name)
+    }
+}
+
+/// Characters which can begin an identifier (`XID_Start`).
+pub fn xid_start() -> Property {
+    Property {
+        name: "XID_Start",
+        tables: &[tables::XID_START],
+    }
+}
+
+/// Characters which can continue an identifier (`XID_Continue`).
+pub fn xid_continue() -> Property {
+    Property {
+        name: "XID_Continue",
+        tables: &[tables::XID_CONTINUE],
+    }
+}
+
+/// Whitespace in patterns and source code (`Pattern_White_Space`).
+///
+/// Unlike [`char::is_whitespace`], it's stable across Unicode versions.
+pub fn pattern_white_space() -> Property {
+    Property {
+        name: "Pattern_White_Space",
+        tables: &[tables::PATTERN_WHITE_SPACE],
+    }
+}
+
+// Long names of the categories and the groups of categories.
+const CATEGORY_NAMES: &[(&str, &str)] = &[
+    ("Lu", "Uppercase_Letter"),
+    ("Ll", "Lowercase_Letter"),
+    ("Lt", "Titlecase_Letter"),
+    ("LC", "Cased_Letter"),
+    ("Lm", "Modifier_Letter"),
+    ("Lo", "Other_Letter"),
+    ("L", "Letter"),
+    ("Mn", "Nonspacing_Mark"),
+    ("Mc", "Spacing_Mark"),
+    ("Me", "Enclosing_Mark"),
+    ("M", "Mark"),
+    ("Nd", "Decimal_Number"),
+    ("Nl", "Letter_Number"),
+    ("No", "Other_Number"),
+    ("N", "Number"),
+    ("Pc", "Connector_Punctuation"),
+    ("Pd", "Dash_Punctuation"),
+    ("Ps", "Open_Punctuation"),
+    ("Pe", "Close_Punctuation"),
+    ("Pi", "Initial_Punctuation"),
+    ("Pf", "Final_Punctuation"),
+    ("Po", "Other_Punctuation"),
+    ("P", "Punctuation"),
+    ("Sm", "Math_Symbol"),
+    ("Sc", "Currency_Symbol"),
+    ("Sk", "Modifier_Symbol"),
+    ("So", "Other_Symbol"),
+    ("S", "Symbol"),
+    ("Zs", "Space_Separator"),
+    ("Zl", "Line_Separator"),
+    ("Zp", "Paragraph_Separator"),
+    ("Z", "Separator"),
+    ("Cc", "Control"),
+    ("Cf", "Format"),
+    ("Cs", "Surrogate"),
+    ("Co", "Private_Use"),
+    ("Cn", "Unassigned"),
+    ("C", "Other"),
+];
+
+macro_rules! category_tables {
+    ($($category:ident),*) => {
+        &[$(tables::$category),*]
+    };
+}
+
+fn category_tables(short_name: &str) -> &'static [tables::Ranges] {
+    match short_name {
+        "LC" => category_tables!(LU, LL, LT),
+        "L" => category_tables!(LU, LL, LT, LM, LO),
+        "M" => category_tables!(MN, MC, ME),
+        "N" => category_tables!(ND, NL, NO),
+        "P" => category_tables!(PC, PD, PS, PE, PI, PF, PO),
+        "S" => category_tables!(SM, SC, SK, SO),
+        "Z" => category_tables!(ZS, ZL, ZP),
+        "C" => category_tables!(CC, CF, CO, CN),
+        // Surrogates are not `char`s.
+        "Cs" => &[],
+        _ => tables::CATEGORIES
+            .iter()
+            .find(|(name, _)| *name == short_name)
+            .map(|(_, table)| std::slice::from_ref(table))
+            .unwrap_or(&[]),
+    }
+}
+
+/// Normalizes a property value name, so that case, whitespace, underscores
+/// and hyphens are ignored, as described in UAX #44 (UAX44-LM3).
+fn loose(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, ' ' | '_' | '-'))
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+/// Returns the characters of a general category, e.g. `"Lu"`,
+/// `"Uppercase_Letter"` or a group of categories, e.g. `"L"`.
+///
+/// The names are matched loosely, i.e. ignoring case, whitespace, underscores
+/// and hyphens.
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::{unicode::general_category, Parser};
+///
+/// let upper = general_category("Uppercase_Letter").unwrap();
+///
+/// assert!(upper.p_arse("Ż").is_ok());
+/// assert!(upper.p_arse("ż").is_err());
+/// assert!(general_category("Lx").is_none());
+/// ```
+pub fn general_category(name: &str) -> Option<Property> {
+    let loose_name = loose(name);
+    let &(short_name, long_name) =
+        CATEGORY_NAMES.iter().find(|(short_name, long_name)| {
+            loose(short_name) == loose_name || loose(long_name) == loose_name
+        })?;
+
+    Some(Property {
+        name: long_name,
+        tables: category_tables(short_name),
+    })
+}
+
+/// Returns the characters of a script, e.g. `"Latin"`, `"Old_Italic"` or
+/// `"Grek"`.
+///
+/// The names are matched loosely, i.e. ignoring case, whitespace, underscores
+/// and hyphens.
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::{unicode::script, Parser};
+///
+/// let greek = script("Greek").unwrap().more().maps(|s| s.to_string());
+///
+/// assert_eq!(greek.p_arse("λόγος logos").unwrap().0, "λόγος");
+/// assert!(script("Elvish").is_none());
+/// ```
+pub fn script(name: &str) -> Option<Property> {
+    let loose_name = loose(name);
+    let (names, table) = tables::SCRIPTS
+        .iter()
+        .find(|(names, _)| names.contains(&loose_name.as_str()))?;
+
+    Some(Property {
+        name: names[0],
+        tables: std::slice::from_ref(table),
+    })
+}
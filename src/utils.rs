@@ -2,7 +2,14 @@
 
 //! Basic utilities.
 
-use crate::{parser::Parser, Error, Result};
+use crate::{
+    describe::GrammarNode,
+    parser::Parser,
+    search::{self, FirstSet},
+    sequence::Leaf,
+    Error,
+    Result,
+};
 
 /// A [`Parser`] matching any single character.
 #[derive(Copy, Clone)]
@@ -11,6 +18,14 @@ pub struct any();
 impl Parser for any {
     type Output = char;
 
+    fn first_set(&self) -> Option<FirstSet> {
+        Some(FirstSet::range('\0', char::MAX))
+    }
+
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::Any
+    }
+
     fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
         let mut chars = tail.chars();
         let first =
@@ -21,6 +36,8 @@ impl Parser for any {
     }
 }
 
+impl Leaf for any {}
+
 /// A [`Parser`] matching the end of input. Used to ensure that the whole input
 /// has matched, i.e. when looking for zero or more repetitions.
 ///
@@ -41,6 +58,10 @@ pub struct eoi();
 impl Parser for eoi {
     type Output = ();
 
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::End
+    }
+
     fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
         if tail.is_empty() {
             Ok(((), tail))
@@ -49,3 +70,255 @@ impl Parser for eoi {
         }
     }
 }
+
+impl Leaf for eoi {}
+
+/// A [`Parser`] matching a single character satisfying the predicate,
+/// returned by [`satisfy`].
+#[derive(Copy, Clone)]
+pub struct Satisfy<F>
+where
+    F: Fn(char) -> bool + Copy,
+{
+    predicate: F,
+}
+
+/// Returns a [`Parser`] matching a single character satisfying the predicate.
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::{Parser, satisfy};
+///
+/// let digit = satisfy(|c| c.is_ascii_digit());
+///
+/// assert_eq!(digit.p_arse("1a").unwrap(), ('1', "a"));
+/// assert!(digit.p_arse("a1").is_err());
+/// ```
+pub fn satisfy<F>(predicate: F) -> Satisfy<F>
+where
+    F: Fn(char) -> bool + Copy,
+{
+    Satisfy { predicate }
+}
+
+impl<F> Parser for Satisfy<F>
+where
+    F: Fn(char) -> bool + Copy,
+{
+    type Output = char;
+
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::Opaque("char satisfying the predicate".to_string())
+    }
+
+    fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        let mut chars = tail.chars();
+
+        match chars.next() {
+            Some(first) if (self.predicate)(first) => {
+                Ok((first, chars.as_str()))
+            }
+            _ => Err(Error::expecting("char satisfying the predicate", tail)),
+        }
+    }
+}
+
+impl<F> Leaf for Satisfy<F> where F: Fn(char) -> bool + Copy {}
+
+/// A [`Parser`] matching characters while they satisfy the predicate,
+/// returned by [`take_while`], [`take_while1`] and [`take_till`].
+///
+/// Its output is `()`: the output of a [`Parser`] can't borrow from the
+/// input, since [`Parser::Output`] doesn't depend on its lifetime. The matched
+/// slice can be accessed with [`.maps()`](Parser::maps) without allocating
+/// anything per character.
+#[derive(Copy, Clone)]
+pub struct TakeWhile<F>
+where
+    F: Fn(char) -> bool + Copy,
+{
+    predicate: F,
+    at_least_one: bool,
+}
+
+/// Returns a [`Parser`] matching zero or more characters satisfying the
+/// predicate.
+///
+/// The effect is the same as `satisfy(predicate).zore().ignore()`, but it
+/// doesn't collect the characters into a [`Vec`].
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::{Parser, take_while};
+///
+/// let digits = take_while(|c| c.is_ascii_digit()).maps(|s| s.to_string());
+///
+/// assert_eq!(digits.p_arse("123abc").unwrap(), ("123".to_string(), "abc"));
+/// assert_eq!(digits.p_arse("abc").unwrap(), ("".to_string(), "abc"));
+/// ```
+pub fn take_while<F>(predicate: F) -> TakeWhile<F>
+where
+    F: Fn(char) -> bool + Copy,
+{
+    TakeWhile {
+        predicate,
+        at_least_one: false,
+    }
+}
+
+/// Returns a [`Parser`] matching one or more characters satisfying the
+/// predicate.
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::{Parser, take_while1};
+///
+/// let digits = take_while1(|c| c.is_ascii_digit());
+///
+/// assert_eq!(digits.p_arse("123abc").unwrap(), ((), "abc"));
+/// assert!(digits.p_arse("abc").is_err());
+/// ```
+pub fn take_while1<F>(predicate: F) -> TakeWhile<F>
+where
+    F: Fn(char) -> bool + Copy,
+{
+    TakeWhile {
+        predicate,
+        at_least_one: true,
+    }
+}
+
+impl<F> Parser for TakeWhile<F>
+where
+    F: Fn(char) -> bool + Copy,
+{
+    type Output = ();
+
+    fn describe(&self) -> GrammarNode {
+        let c =
+            GrammarNode::Opaque("char satisfying the predicate".to_string());
+
+        GrammarNode::repeat(c, self.at_least_one as usize, None)
+    }
+
+    fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        let end = tail.find(|c| !(self.predicate)(c)).unwrap_or(tail.len());
+
+        if self.at_least_one && end == 0 {
+            Err(Error::expecting("char satisfying the predicate", tail))
+        } else {
+            Ok(((), &tail[end..]))
+        }
+    }
+}
+
+impl<F> Leaf for TakeWhile<F> where F: Fn(char) -> bool + Copy {}
+
+/// Returns a [`Parser`] matching zero or more characters until one satisfies
+/// the predicate or the input ends. The character satisfying the predicate is
+/// not consumed.
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::{Parser, take_till};
+///
+/// let line = take_till(|c| c == '\n').maps(|s| s.to_string());
+///
+/// assert_eq!(line.p_arse("abc\ndef").unwrap(), ("abc".to_string(), "\ndef"));
+/// assert_eq!(line.p_arse("abc").unwrap(), ("abc".to_string(), ""));
+/// ```
+pub fn take_till<F>(predicate: F) -> TakeWhile<impl Fn(char) -> bool + Copy>
+where
+    F: Fn(char) -> bool + Copy,
+{
+    take_while(move |c| !predicate(c))
+}
+
+/// A [`Parser`] matching characters until the supplied parser matches,
+/// returned by [`take_until`].
+///
+/// Like [`TakeWhile`], its output is `()`.
+#[derive(Copy, Clone)]
+pub struct TakeUntil<P>
+where
+    P: Parser,
+{
+    parser: P,
+    // The first set of the parser if it's ASCII only, kept as a bitset since
+    // the parser is `Copy`.
+    first_set: Option<u128>,
+}
+
+/// Returns a [`Parser`] matching zero or more characters until the supplied
+/// parser matches or the input ends. The input matched by the supplied parser
+/// is not consumed.
+///
+/// The effect is the same as `(parser.not_ahead(), any()).zore().ignore()`,
+/// but it doesn't collect the characters into a [`Vec`]. If the first set of
+/// the parser (see [`Parser::first_set`]) contains only ASCII characters, the
+/// characters which can't begin a match are skipped without trying the parser.
+///
+/// # Examples
+///
+/// ```
+/// use p_arse::{Parser, take_until};
+///
+/// let comment = ("/*", take_until("*/"), "*/");
+///
+/// assert!(comment.p_arse("/* a * b */").is_ok());
+/// assert!(comment.p_arse("/* a * b").is_err());
+/// ```
+pub fn take_until<P>(parser: P) -> TakeUntil<P>
+where
+    P: Parser,
+{
+    TakeUntil {
+        parser,
+        first_set: parser.first_set().and_then(|set| set.ascii_only()),
+    }
+}
+
+impl<P> Parser for TakeUntil<P>
+where
+    P: Parser,
+{
+    type Output = ();
+
+    fn describe(&self) -> GrammarNode {
+        let c = GrammarNode::sequence(vec![
+            GrammarNode::lookahead(self.parser.describe(), false),
+            GrammarNode::Any,
+        ]);
+
+        GrammarNode::repeat(c, 0, None)
+    }
+
+    fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        let mut position = 0;
+
+        while position < tail.len() {
+            if let Some(first_set) = self.first_set {
+                match search::skip_ascii(first_set, &tail[position..]) {
+                    Some(skipped) => position += skipped,
+                    None => return Ok(((), &tail[tail.len()..])),
+                }
+            }
+
+            let rest = &tail[position..];
+
+            if self.parser.p_arse(rest).is_ok() {
+                return Ok(((), rest));
+            }
+
+            position += rest.chars().next().map_or(1, char::len_utf8);
+        }
+
+        Ok(((), &tail[position..]))
+    }
+}
+
+impl<P> Leaf for TakeUntil<P> where P: Parser {}
@@ -1,12 +1,33 @@
 use std::{fmt::Debug, marker::PhantomData};
 
-use crate::{parser::Parser, Error, Result};
+use crate::{
+    describe::GrammarNode,
+    export::peg,
+    limits,
+    parser::{Continuation, Parser},
+    search::{self, FirstSet},
+    sequence::Leaf,
+    state,
+    Error,
+    Result,
+};
+
+/// What a repetition does when the repeated parser matches without consuming
+/// anything, which it would otherwise do forever, e.g. `eoi().zore()`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EmptyMatch {
+    /// Stops the repetition, discarding the empty match. The default.
+    Stop,
+    /// Fails the repetition with an error describing the repeated parser.
+    Fail,
+}
 
 pub struct ZeroOrMore<P>
 where
     P: Parser,
 {
     pub(crate) parser: P,
+    pub(crate) empty_match: EmptyMatch,
 }
 
 impl<P> Clone for ZeroOrMore<P>
@@ -16,35 +37,100 @@ where
     fn clone(&self) -> Self {
         Self {
             parser: self.parser,
+            empty_match: self.empty_match,
         }
     }
 }
 
 impl<P> Copy for ZeroOrMore<P> where P: Parser {}
 
-impl<P> Parser for ZeroOrMore<P>
+impl<P> ZeroOrMore<P>
 where
     P: Parser,
 {
-    type Output = Vec<P::Output>;
+    /// Sets what happens when the parser matches without consuming anything.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use p_arse::{wrapper::EmptyMatch, Parser};
+    ///
+    /// let a = 'a'.opt().zore();
+    /// assert_eq!(a.p_arse("aab").unwrap(), (vec![Some('a'), Some('a')], "b"));
+    ///
+    /// let a = a.empty_match(EmptyMatch::Fail);
+    /// let error = a.p_arse("aab").unwrap_err();
+    /// assert_eq!(error.expectation(), "repetition of 'a'? consuming input");
+    /// assert_eq!(error.tail(), "b");
+    /// ```
+    pub fn empty_match(self, empty_match: EmptyMatch) -> Self {
+        Self {
+            empty_match,
+            ..self
+        }
+    }
 
-    fn p_arse<'a>(&self, mut tail: &'a str) -> Result<'a, Self::Output> {
-        let mut output = vec![];
+    // Continues the repetition after the matches in `output`.
+    fn p_arse_after<'a>(
+        &self,
+        mut output: Vec<P::Output>,
+        mut tail: &'a str,
+    ) -> Result<'a, Vec<P::Output>> {
+        let mut checkpoint = state::checkpoint();
 
         while let Ok((output_i, tail_i)) = self.parser.p_arse(tail) {
+            if tail_i.len() == tail.len() {
+                match self.empty_match {
+                    EmptyMatch::Stop => break,
+                    EmptyMatch::Fail => {
+                        checkpoint.restore();
+                        let repeated = peg(&self.parser.describe());
+
+                        return Err(Error::expecting(
+                            format!(
+                                "repetition of {} consuming input",
+                                repeated
+                            ),
+                            tail,
+                        ));
+                    }
+                }
+            }
+
+            limits::repetition(output.len() + 1, tail)?;
             tail = tail_i;
             output.push(output_i);
+            checkpoint.update();
         }
+        checkpoint.restore();
 
         Ok((output, tail))
     }
 }
 
+impl<P> Parser for ZeroOrMore<P>
+where
+    P: Parser,
+{
+    type Output = Vec<P::Output>;
+
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::repeat(self.parser.describe(), 0, None)
+    }
+
+    fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        self.p_arse_after(vec![], tail)
+    }
+}
+
+impl<P> Leaf for ZeroOrMore<P> where P: Parser {}
+
 pub struct OneOrMore<P>
 where
     P: Parser,
 {
     pub(crate) parser: P,
+    pub(crate) empty_match: EmptyMatch,
 }
 
 impl<P> Clone for OneOrMore<P>
@@ -54,32 +140,52 @@ where
     fn clone(&self) -> Self {
         Self {
             parser: self.parser,
+            empty_match: self.empty_match,
         }
     }
 }
 
 impl<P> Copy for OneOrMore<P> where P: Parser {}
 
+impl<P> OneOrMore<P>
+where
+    P: Parser,
+{
+    /// Sets what happens when the parser matches without consuming anything
+    /// after its first match, see [`ZeroOrMore::empty_match`].
+    pub fn empty_match(self, empty_match: EmptyMatch) -> Self {
+        Self {
+            empty_match,
+            ..self
+        }
+    }
+}
+
 impl<P> Parser for OneOrMore<P>
 where
     P: Parser,
 {
     type Output = Vec<P::Output>;
 
-    fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
-        let (first, tail) = self.parser.p_arse(tail)?;
+    fn first_set(&self) -> Option<FirstSet> {
+        self.parser.first_set()
+    }
 
-        match (&self.parser).zore().p_arse(tail) {
-            Ok((mut rest, tail)) => {
-                rest.insert(0, first);
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::repeat(self.parser.describe(), 1, None)
+    }
 
-                Ok((rest, tail))
-            }
-            Err(_) => Ok((vec![first], tail)),
-        }
+    fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        let (first, rest) = self.parser.p_arse(tail)?;
+        limits::repetition(1, tail)?;
+        let zore = self.parser.zore().empty_match(self.empty_match);
+
+        zore.p_arse_after(vec![first], rest)
     }
 }
 
+impl<P> Leaf for OneOrMore<P> where P: Parser {}
+
 pub struct Ignorant<P>
 where
     P: Parser,
@@ -106,11 +212,21 @@ where
 {
     type Output = ();
 
+    fn first_set(&self) -> Option<FirstSet> {
+        self.parser.first_set()
+    }
+
+    fn describe(&self) -> GrammarNode {
+        self.parser.describe()
+    }
+
     fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
         self.parser.p_arse(tail).map(|(_, tail)| ((), tail))
     }
 }
 
+impl<P> Leaf for Ignorant<P> where P: Parser {}
+
 pub struct Opt<P>
 where
     P: Parser,
@@ -137,15 +253,25 @@ where
 {
     type Output = Option<P::Output>;
 
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::repeat(self.parser.describe(), 0, Some(1))
+    }
+
     fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        let checkpoint = state::checkpoint();
+
         if let Ok((output, tail)) = self.parser.p_arse(tail) {
             Ok((Some(output), tail))
         } else {
+            checkpoint.restore();
+
             Ok((None, tail))
         }
     }
 }
 
+impl<P> Leaf for Opt<P> where P: Parser {}
+
 pub struct Map<P, F, U>
 where
     P: Parser,
@@ -184,6 +310,14 @@ where
 {
     type Output = U;
 
+    fn first_set(&self) -> Option<FirstSet> {
+        self.parser.first_set()
+    }
+
+    fn describe(&self) -> GrammarNode {
+        self.parser.describe()
+    }
+
     fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
         self.parser
             .p_arse(tail)
@@ -191,6 +325,13 @@ where
     }
 }
 
+impl<P, F, U> Leaf for Map<P, F, U>
+where
+    P: Parser,
+    F: Fn(P::Output) -> U + Copy,
+{
+}
+
 pub struct Or<P0, P1>
 where
     P0: Parser,
@@ -227,15 +368,114 @@ where
 {
     type Output = P0::Output;
 
+    fn first_set(&self) -> Option<FirstSet> {
+        search::union(self.parser_0.first_set(), self.parser_1.first_set())
+    }
+
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::choice(vec![
+            self.parser_0.describe(),
+            self.parser_1.describe(),
+        ])
+    }
+
     fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        limits::step(tail)?;
+        let checkpoint = state::checkpoint();
+
         if let Ok((output, tail)) = self.parser_0.p_arse(tail) {
             Ok((output, tail))
         } else {
+            checkpoint.restore();
+            limits::step(tail)?;
+
             self.parser_1.p_arse(tail)
         }
     }
 }
 
+impl<P0, P1> Leaf for Or<P0, P1>
+where
+    P0: Parser,
+    P1: Parser<Output = P0::Output>,
+{
+}
+
+/// One of two outputs, returned by [`Parser::or_either`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Either<A, B> {
+    Left(A),
+    Right(B),
+}
+
+pub struct OrEither<P0, P1>
+where
+    P0: Parser,
+    P1: Parser,
+{
+    pub(crate) parser_0: P0,
+    pub(crate) parser_1: P1,
+}
+
+impl<P0, P1> Clone for OrEither<P0, P1>
+where
+    P0: Parser,
+    P1: Parser,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P0, P1> Copy for OrEither<P0, P1>
+where
+    P0: Parser,
+    P1: Parser,
+{
+}
+
+impl<P0, P1> Parser for OrEither<P0, P1>
+where
+    P0: Parser,
+    P1: Parser,
+{
+    type Output = Either<P0::Output, P1::Output>;
+
+    fn first_set(&self) -> Option<FirstSet> {
+        search::union(self.parser_0.first_set(), self.parser_1.first_set())
+    }
+
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::choice(vec![
+            self.parser_0.describe(),
+            self.parser_1.describe(),
+        ])
+    }
+
+    fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        limits::step(tail)?;
+        let checkpoint = state::checkpoint();
+
+        if let Ok((output, tail)) = self.parser_0.p_arse(tail) {
+            Ok((Either::Left(output), tail))
+        } else {
+            checkpoint.restore();
+            limits::step(tail)?;
+
+            self.parser_1
+                .p_arse(tail)
+                .map(|(output, tail)| (Either::Right(output), tail))
+        }
+    }
+}
+
+impl<P0, P1> Leaf for OrEither<P0, P1>
+where
+    P0: Parser,
+    P1: Parser,
+{
+}
+
 pub struct NegativeLookahead<P>
 where
     P: Parser,
@@ -262,8 +502,18 @@ where
 {
     type Output = ();
 
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::lookahead(self.parser.describe(), false)
+    }
+
     fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
-        if self.parser.p_arse(tail).is_err() {
+        // A look-ahead doesn't consume the input, nor does it change the
+        // state.
+        let checkpoint = state::checkpoint();
+        let result = self.parser.p_arse(tail);
+        checkpoint.restore();
+
+        if result.is_err() {
             Ok(((), tail))
         } else {
             // TODO what to put here?
@@ -272,6 +522,8 @@ where
     }
 }
 
+impl<P> Leaf for NegativeLookahead<P> where P: Parser {}
+
 pub struct PositiveLookahead<P>
 where
     P: Parser,
@@ -298,8 +550,20 @@ where
 {
     type Output = ();
 
+    fn first_set(&self) -> Option<FirstSet> {
+        self.parser.first_set()
+    }
+
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::lookahead(self.parser.describe(), true)
+    }
+
     fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
-        if self.parser.p_arse(tail).is_ok() {
+        let checkpoint = state::checkpoint();
+        let result = self.parser.p_arse(tail);
+        checkpoint.restore();
+
+        if result.is_ok() {
             Ok(((), tail))
         } else {
             // TODO what to put here?
@@ -308,6 +572,8 @@ where
     }
 }
 
+impl<P> Leaf for PositiveLookahead<P> where P: Parser {}
+
 pub struct Named<P>
 where
     P: Parser,
@@ -336,11 +602,24 @@ where
 {
     type Output = P::Output;
 
+    fn first_set(&self) -> Option<FirstSet> {
+        self.parser.first_set()
+    }
+
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::Rule {
+            name: self.name.to_string(),
+            node: Box::new(self.parser.describe()),
+        }
+    }
+
     fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
         self.parser.p_arse(tail).map_err(|err| err.push(self.name))
     }
 }
 
+impl<P> Leaf for Named<P> where P: Parser {}
+
 pub struct MapStr<P, F, T>
 where
     P: Parser,
@@ -378,6 +657,14 @@ where
 {
     type Output = T;
 
+    fn first_set(&self) -> Option<FirstSet> {
+        self.parser.first_set()
+    }
+
+    fn describe(&self) -> GrammarNode {
+        self.parser.describe()
+    }
+
     fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
         match self.parser.p_arse(tail) {
             Ok((_, new_tail)) => {
@@ -392,6 +679,13 @@ where
     }
 }
 
+impl<P, F, T> Leaf for MapStr<P, F, T>
+where
+    P: Parser,
+    F: Fn(&str) -> T + Copy,
+{
+}
+
 #[derive(Copy, Clone)]
 pub struct Debugged<P>
 where
@@ -408,7 +702,219 @@ where
 {
     type Output = P::Output;
 
+    fn first_set(&self) -> Option<FirstSet> {
+        self.parser.first_set()
+    }
+
+    fn describe(&self) -> GrammarNode {
+        self.parser.describe()
+    }
+
     fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
         dbg!(self.parser.p_arse(tail))
     }
 }
+
+impl<P> Leaf for Debugged<P>
+where
+    P: Parser,
+    P::Output: Debug,
+{
+}
+
+pub struct Bind<P, F, C>
+where
+    P: Parser,
+    F: Fn(P::Output) -> C + Copy,
+    C: Continuation,
+{
+    pub(crate) parser: P,
+    pub(crate) f: F,
+    pub(crate) marker: PhantomData<C>,
+}
+
+impl<P, F, C> Clone for Bind<P, F, C>
+where
+    P: Parser,
+    F: Fn(P::Output) -> C + Copy,
+    C: Continuation,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P, F, C> Copy for Bind<P, F, C>
+where
+    P: Parser,
+    F: Fn(P::Output) -> C + Copy,
+    C: Continuation,
+{
+}
+
+impl<P, F, C> Parser for Bind<P, F, C>
+where
+    P: Parser,
+    F: Fn(P::Output) -> C + Copy,
+    C: Continuation,
+{
+    type Output = C::Output;
+
+    fn first_set(&self) -> Option<FirstSet> {
+        self.parser.first_set()
+    }
+
+    // The continuation is only known after parsing.
+    fn describe(&self) -> GrammarNode {
+        GrammarNode::sequence(vec![
+            self.parser.describe(),
+            GrammarNode::Opaque("continuation".to_string()),
+        ])
+    }
+
+    fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        let (output, tail) = self.parser.p_arse(tail)?;
+
+        (self.f)(output).p_arse_once(tail)
+    }
+}
+
+impl<P, F, C> Leaf for Bind<P, F, C>
+where
+    P: Parser,
+    F: Fn(P::Output) -> C + Copy,
+    C: Continuation,
+{
+}
+
+pub struct MapWithState<P, F, S, U>
+where
+    P: Parser,
+    F: Fn(P::Output, &mut S) -> U + Copy,
+    S: 'static,
+{
+    pub(crate) parser: P,
+    pub(crate) f: F,
+    pub(crate) marker: PhantomData<(S, U)>,
+}
+
+impl<P, F, S, U> Clone for MapWithState<P, F, S, U>
+where
+    P: Parser,
+    F: Fn(P::Output, &mut S) -> U + Copy,
+    S: 'static,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P, F, S, U> Copy for MapWithState<P, F, S, U>
+where
+    P: Parser,
+    F: Fn(P::Output, &mut S) -> U + Copy,
+    S: 'static,
+{
+}
+
+impl<P, F, S, U> Parser for MapWithState<P, F, S, U>
+where
+    P: Parser,
+    F: Fn(P::Output, &mut S) -> U + Copy,
+    S: 'static,
+{
+    type Output = U;
+
+    fn first_set(&self) -> Option<FirstSet> {
+        self.parser.first_set()
+    }
+
+    fn describe(&self) -> GrammarNode {
+        self.parser.describe()
+    }
+
+    fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        let (output, new_tail) = self.parser.p_arse(tail)?;
+        let output = state::with_state(|state| (self.f)(output, state))
+            .ok_or_else(|| state::missing::<S>(tail))?;
+
+        Ok((output, new_tail))
+    }
+}
+
+impl<P, F, S, U> Leaf for MapWithState<P, F, S, U>
+where
+    P: Parser,
+    F: Fn(P::Output, &mut S) -> U + Copy,
+    S: 'static,
+{
+}
+
+pub struct TryMapWithState<P, F, S, U, E>
+where
+    P: Parser,
+    F: Fn(P::Output, &mut S) -> std::result::Result<U, E> + Copy,
+    S: 'static,
+    E: Into<String>,
+{
+    pub(crate) parser: P,
+    pub(crate) f: F,
+    pub(crate) marker: PhantomData<(S, U, E)>,
+}
+
+impl<P, F, S, U, E> Clone for TryMapWithState<P, F, S, U, E>
+where
+    P: Parser,
+    F: Fn(P::Output, &mut S) -> std::result::Result<U, E> + Copy,
+    S: 'static,
+    E: Into<String>,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P, F, S, U, E> Copy for TryMapWithState<P, F, S, U, E>
+where
+    P: Parser,
+    F: Fn(P::Output, &mut S) -> std::result::Result<U, E> + Copy,
+    S: 'static,
+    E: Into<String>,
+{
+}
+
+impl<P, F, S, U, E> Parser for TryMapWithState<P, F, S, U, E>
+where
+    P: Parser,
+    F: Fn(P::Output, &mut S) -> std::result::Result<U, E> + Copy,
+    S: 'static,
+    E: Into<String>,
+{
+    type Output = U;
+
+    fn first_set(&self) -> Option<FirstSet> {
+        self.parser.first_set()
+    }
+
+    fn describe(&self) -> GrammarNode {
+        self.parser.describe()
+    }
+
+    fn p_arse<'a>(&self, tail: &'a str) -> Result<'a, Self::Output> {
+        let (output, new_tail) = self.parser.p_arse(tail)?;
+        let output = state::with_state(|state| (self.f)(output, state))
+            .ok_or_else(|| state::missing::<S>(tail))?
+            .map_err(|expectation| Error::expecting(expectation, tail))?;
+
+        Ok((output, new_tail))
+    }
+}
+
+impl<P, F, S, U, E> Leaf for TryMapWithState<P, F, S, U, E>
+where
+    P: Parser,
+    F: Fn(P::Output, &mut S) -> std::result::Result<U, E> + Copy,
+    S: 'static,
+    E: Into<String>,
+{
+}
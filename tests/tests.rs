@@ -145,3 +145,1362 @@ fn test_function() {
 
     assert!(a_string.p_arse("a").is_ok());
 }
+
+#[test]
+fn test_heterogeneous_choice() {
+    use p_arse::{alt_enum, Either};
+
+    let digit_or_word = '0'.to('9').or_either("word");
+    assert_eq!(digit_or_word.p_arse("1").unwrap().0, Either::Left('1'));
+    assert_eq!(
+        digit_or_word.p_arse("word").unwrap().0,
+        Either::Right("word")
+    );
+    assert!(digit_or_word.p_arse("x").is_err());
+
+    #[derive(Debug, PartialEq)]
+    enum Value {
+        Digit(u32),
+        Bool(bool),
+        Null,
+    }
+
+    let digit = '0'.to('9').map(|d: char| d.to_digit(10).unwrap());
+    let boolean = "true".map(|_| true).or("false".map(|_| false));
+    let value = alt_enum!(
+        digit => Value::Digit,
+        boolean => Value::Bool,
+        "null" => |_| Value::Null,
+    );
+
+    assert_eq!(value.p_arse("7").unwrap().0, Value::Digit(7));
+    assert_eq!(value.p_arse("false").unwrap().0, Value::Bool(false));
+    assert_eq!(value.p_arse("null").unwrap().0, Value::Null);
+    assert!(value.p_arse("nil").is_err());
+}
+
+#[test]
+fn test_seq() {
+    use p_arse::seq;
+
+    let ws = ' '.zore();
+    let word = 'a'.to('z').more().maps(|s| s.to_string());
+
+    let padded = seq!(~ws, word, ~ws);
+    let (word_0, tail) = padded.p_arse("  abc  def").unwrap();
+    assert_eq!(word_0, "abc");
+    assert_eq!(tail, "def");
+
+    let pair = seq!(~'(', ~ws, word, ~ws, ~',', ~ws, word, ~ws, ~')');
+    let ((a, b), _tail) = pair.p_arse("( abc , def )").unwrap();
+    assert_eq!((a.as_str(), b.as_str()), ("abc", "def"));
+
+    let nothing = seq!(~'a', ~'b');
+    assert_eq!(nothing.p_arse("ab").unwrap().0, ());
+
+    let captured = seq!(~ws, word, ~ws).maps(|s| s.to_string());
+    assert_eq!(captured.p_arse(" abc ").unwrap().0, " abc ");
+}
+
+#[test]
+fn test_flatten() {
+    let nl = '\n';
+    let header_content = 'a'.to('z').more().maps(|s| s.to_string());
+    let header = (">", header_content, nl);
+    let sequence = 'A'.to('Z').more().maps(|s| s.to_string());
+    let entry = (header, (sequence, nl)).flatten();
+
+    let ((tag, content, _, sequence, _), tail) =
+        entry.p_arse(">abc\nXYZ\nrest").unwrap();
+    assert_eq!(tag, ">");
+    assert_eq!(content, "abc");
+    assert_eq!(sequence, "XYZ");
+    assert_eq!(tail, "rest");
+
+    // Flattened sequences are spliced into the outer ones.
+    let ab = ('a', 'b').flatten();
+    let abcd = (ab, ('c', ('d',))).flatten();
+    assert_eq!(abcd.p_arse("abcd").unwrap().0, ('a', 'b', 'c', 'd'));
+
+    // Other parsers are not.
+    let ab = ('a', 'b').map(|ab| ab);
+    let ab_c = (ab, 'c').flatten();
+    assert_eq!(ab_c.p_arse("abc").unwrap().0, (('a', 'b'), 'c'));
+}
+
+#[test]
+fn test_bind() {
+    use p_arse::function::boxed;
+
+    // Pascal strings, the length is a single digit.
+    let length = '0'.to('9').map(|d: char| d.to_digit(10).unwrap() as usize);
+    let pascal = length
+        .bind(|n| vec![any(); n])
+        .map(|cs| cs.into_iter().collect::<String>());
+    assert_eq!(pascal.p_arse("3abcd").unwrap(), ("abc".to_string(), "d"));
+    assert!(pascal.p_arse("3ab").is_err());
+
+    // A terminator repeating the opening tag.
+    let tag = 'a'.to('z').more().maps(|s| s.to_string());
+    let tagged = (tag, ':').bind(|(tag, _)| tag);
+    assert!(tagged.p_arse("abc:abc").is_ok());
+    assert!(tagged.p_arse("abc:abd").is_err());
+
+    // A dynamically built closure.
+    let repeated = any().bind(|c| {
+        boxed(move |tail| {
+            let end = tail.find(|d| d != c).unwrap_or(tail.len());
+
+            Ok((end, &tail[end..]))
+        })
+    });
+    assert_eq!(repeated.p_arse("aaab").unwrap(), (2, "b"));
+}
+
+#[test]
+fn test_state() {
+    use std::{cell::Cell, collections::HashSet, rc::Rc};
+
+    use p_arse::state::with_state;
+
+    // Duplicate keys are rejected.
+    let key =
+        'a'.to('z')
+            .try_map_with_state(|key, seen: &mut HashSet<char>| {
+                if seen.insert(key) {
+                    Ok(key)
+                } else {
+                    Err("unique key")
+                }
+            });
+    let keys = (key, (',', key).zore(), p_arse::eoi());
+
+    let mut seen = HashSet::<char>::new();
+    assert!(keys.p_arse_with("a,b,c", &mut seen).is_ok());
+    assert_eq!(seen.len(), 3);
+
+    let mut seen = HashSet::<char>::new();
+    assert!(keys.p_arse_with("a,b,a", &mut seen).is_err());
+
+    // Without the state the parser fails.
+    assert!(key.p_arse("a").is_err());
+
+    // Changes made by failed alternatives are undone.
+    let counted = 'a'.map_with_state(|a, count: &mut usize| {
+        *count += 1;
+
+        a
+    });
+    let choice = (counted, counted, 'x').ignore().or((counted, 'y').ignore());
+    let mut count: usize = 0;
+    assert!(choice.p_arse_with("ay", &mut count).is_ok());
+    assert_eq!(count, 1);
+
+    let mut count: usize = 0;
+    assert!((counted, 'b').opt().p_arse_with("ac", &mut count).is_ok());
+    assert!((counted, 'b')
+        .not_ahead()
+        .p_arse_with("ab", &mut count)
+        .is_err());
+    assert_eq!(count, 0);
+
+    // Nested backtracking undoes the changes of the outer failed branch,
+    // including those kept by inner branches which succeeded.
+    let nested = ((counted, 'b').opt(), 'x').ignore().or(counted.ignore());
+    let mut count: usize = 0;
+    assert!(nested.p_arse_with("aby", &mut count).is_ok());
+    assert_eq!(count, 1);
+
+    let nested = (counted, (counted, 'b').opt(), 'c')
+        .ignore()
+        .or(counted.more().ignore());
+    let mut count: usize = 0;
+    assert_eq!(nested.p_arse_with("aac", &mut count).unwrap().1, "c");
+    assert_eq!(count, 2);
+
+    // Parsers run while the state is accessed don't get it.
+    let reentrant = 'a'.map_with_state(|_, count: &mut usize| {
+        *count += 1;
+
+        counted.p_arse("a").is_err()
+    });
+    let mut count: usize = 0;
+    assert!(reentrant.p_arse_with("a", &mut count).unwrap().0);
+    assert_eq!(count, 1);
+
+    // The state is copied only to undo its changes.
+    #[derive(Default)]
+    struct Copies(Rc<Cell<usize>>);
+
+    impl Clone for Copies {
+        fn clone(&self) -> Self {
+            self.0.set(self.0.get() + 1);
+
+            Copies(self.0.clone())
+        }
+    }
+
+    let unchanged = ('a'.or('b')).more().opt();
+    let mut copies = Copies::default();
+    assert!(unchanged.p_arse_with("abab", &mut copies).is_ok());
+    assert_eq!(copies.0.get(), 1);
+
+    // The state is available to recursive functions.
+    let nested = rec(&|tail, nested| {
+        let (_, tail) = '('.p_arse(tail)?;
+        with_state(|depth: &mut (usize, usize)| {
+            depth.0 += 1;
+            depth.1 = depth.1.max(depth.0);
+        });
+        let (_, tail) = nested.opt().p_arse(tail)?;
+        with_state(|depth: &mut (usize, usize)| depth.0 -= 1);
+        let (_, tail) = ')'.p_arse(tail)?;
+
+        Ok(((), tail))
+    });
+    let mut depth: (usize, usize) = (0, 0);
+    assert!(nested.p_arse_with("((()))", &mut depth).is_ok());
+    assert_eq!(depth, (0, 3));
+}
+
+#[test]
+fn test_pattern() {
+    let digit = '0'.to('9');
+    let timestamp = ('[', digit.more(), ':', digit.more(), "] ");
+
+    let log = "[12:00] started\n[12:01] running\n[12:02] stopped\n";
+    let entries = log
+        .split(timestamp.as_pattern())
+        .filter(|entry| !entry.is_empty())
+        .collect::<Vec<_>>();
+    assert_eq!(entries, vec!["started\n", "running\n", "stopped\n"]);
+
+    let numbers = "a1b22c333".matches(digit.more().as_pattern());
+    assert_eq!(numbers.collect::<Vec<_>>(), vec!["1", "22", "333"]);
+
+    assert_eq!(
+        "  \t text".trim_start_matches(' '.or('\t').as_pattern()),
+        "text"
+    );
+    assert!("abc".starts_with("ab".as_pattern()));
+    assert!(!"abc".contains(digit.as_pattern()));
+
+    // Parsers matching the empty string don't loop forever.
+    assert_eq!("ab".replace('x'.opt().as_pattern(), "-"), "-a-b-");
+}
+
+#[test]
+fn test_find() {
+    let digit = '0'.to('9');
+    let number = digit.more().maps(|s| s.parse::<u32>().unwrap());
+    let numbers = number.find_iter("a1 b22 ąę333").collect::<Vec<_>>();
+
+    assert_eq!(
+        numbers.iter().map(|m| m.output).collect::<Vec<_>>(),
+        vec![1, 22, 333]
+    );
+    assert_eq!(numbers[2].range(), 11..14);
+    assert_eq!(numbers[2].as_str(), "333");
+
+    assert!(number.find("abc").is_none());
+
+    // Parsers without a first set are tried at every position.
+    let word = ('x'.opt(), 'y');
+    assert!(word.first_set().is_none());
+    assert_eq!(word.find_iter("xy y xxy").count(), 3);
+
+    // Positive look-aheads match only where their parser does.
+    let before_digit = ('a'.to('z'), digit.ahead()).maps(|s| s.to_string());
+    let first_set = digit.ahead().first_set().unwrap();
+    assert_eq!(first_set.ranges(), vec![('0', '9')]);
+    assert_eq!(before_digit.find("ab1").unwrap().output, "b");
+
+    let first_set = 'a'.or('ą').or('b').first_set().unwrap();
+    assert_eq!(first_set.ranges(), vec![('a', 'b'), ('ą', 'ą')]);
+    assert_eq!('ą'.or('b').find("xyąa").unwrap().range(), 2..4);
+
+    // Parsers matching the empty string match between all characters.
+    assert_eq!('x'.zore().find_iter("ab").count(), 3);
+
+    // Tails which aren't suffixes of the input aren't matches.
+    let foreign = fun(&|_| Ok(((), "a longer foreign tail")));
+    let short = fun(&|_| Ok(((), "x")));
+    assert!(foreign.find("ab").is_none());
+    assert!(short.find("ab").is_none());
+    assert_eq!("ab".matches(foreign.as_pattern()).count(), 0);
+    assert_eq!("ab".matches(short.as_pattern()).count(), 0);
+    assert_eq!("ab".strip_prefix(foreign.as_pattern()), None);
+    assert!(!"ab".starts_with(foreign.as_pattern()));
+    assert!(!"ab".starts_with(short.as_pattern()));
+}
+
+#[test]
+fn test_replace() {
+    use std::borrow::Cow;
+
+    use p_arse::search::{replace_all, replacen};
+
+    let hex_d = ('0'.to('9')).or('a'.to('f')).or('A'.to('F'));
+    let hex_dd = (hex_d, hex_d).maps(|s| u8::from_str_radix(s, 16).unwrap());
+    let color = ('#', hex_dd, hex_dd, hex_dd);
+
+    let normalized =
+        replace_all("#DEFEC8, #c0ffee", color, |(_, r, g, b), _| {
+            format!("rgb({}, {}, {})", r, g, b)
+        });
+    assert_eq!(normalized, "rgb(222, 254, 200), rgb(192, 255, 238)");
+
+    let untouched = replace_all("no colors", color, |_, matched| matched);
+    assert!(matches!(untouched, Cow::Borrowed("no colors")));
+
+    let ident = ('a'.to('z')).more();
+    let redacted = replacen("a b c", ident, 2, |_, _| Cow::Borrowed("?"));
+    assert_eq!(redacted, "? ? c");
+    assert_eq!(replacen("a b c", ident, 0, |_, _| "?"), "a b c");
+}
+
+#[test]
+fn test_take() {
+    use p_arse::{satisfy, take_till, take_until, take_while, take_while1};
+
+    let vowel = satisfy(|c| "aeiou".contains(c));
+    assert_eq!(vowel.p_arse("abc").unwrap(), ('a', "bc"));
+    assert!(vowel.p_arse("bc").is_err());
+    assert!(vowel.p_arse("").is_err());
+
+    let digits = take_while(|c| c.is_ascii_digit()).maps(|s| s.to_string());
+    assert_eq!(digits.p_arse("12ab").unwrap(), ("12".to_string(), "ab"));
+    assert_eq!(digits.p_arse("").unwrap(), ("".to_string(), ""));
+
+    let digits_1 = take_while1(|c| c.is_ascii_digit());
+    assert!(digits_1.p_arse("ab").is_err());
+    assert!(digits_1.p_arse("1").is_ok());
+
+    let nl = '\n';
+    let header = (">", take_till(|c| c == nl).maps(|s| s.to_string()), nl);
+    let ((_, content, _), tail) = header.p_arse(">ąę header\nACGT").unwrap();
+    assert_eq!(content, "ąę header");
+    assert_eq!(tail, "ACGT");
+
+    let until_end = take_until("-->").maps(|s| s.len());
+    assert_eq!(until_end.p_arse("a -- b -->").unwrap(), (7, "-->"));
+    assert_eq!(until_end.p_arse("a -- b").unwrap(), (6, ""));
+    let until_empty = take_until(p_arse::eoi()).maps(|s| s.len());
+    assert_eq!(until_empty.p_arse("abc").unwrap(), (3, ""));
+    let until_arrow = take_until("→".or("->")).maps(|s| s.len());
+    assert_eq!(until_arrow.p_arse("ą → b").unwrap(), (3, "→ b"));
+}
+
+#[test]
+fn test_char_class() {
+    use p_arse::{none_of, one_of, CharClass};
+
+    let hex_d = '0'.to('9') | 'a'.to('f') | 'A'.to('F');
+    assert!(hex_d.contains('7') && hex_d.contains('c') && hex_d.contains('C'));
+    assert!(!hex_d.contains('g'));
+    assert_eq!(hex_d.ranges(), vec![('0', '9'), ('A', 'F'), ('a', 'f')]);
+
+    let sign = one_of("+-");
+    let number = ((&sign).opt(), (&hex_d).more());
+    assert!(number.p_arse("-ff").is_ok());
+    assert!(number.p_arse("*ff").is_err());
+
+    // Unicode ranges and set algebra.
+    let polish = one_of("ąćęłńóśźż");
+    let lower = CharClass::from('a'.to('z')) | polish.clone();
+    assert!(lower.contains('ł') && lower.contains('a'));
+    assert!(!lower.contains('Ł'));
+
+    let not_lower = !lower.clone();
+    assert!(not_lower.contains('Ł') && not_lower.contains('\u{10FFFF}'));
+    assert!(!not_lower.contains('ż') && !not_lower.contains('q'));
+    assert_eq!((!not_lower).ranges(), lower.ranges());
+
+    let vowels = one_of("aeiouóąę");
+    let consonants = lower & !vowels;
+    assert!(consonants.contains('b') && consonants.contains('ś'));
+    assert!(!consonants.contains('a') && !consonants.contains('ę'));
+
+    let unquoted = none_of("\"\\");
+    assert!(unquoted.contains('ą') && !unquoted.contains('"'));
+
+    // Surrogates are not characters, the gap is handled when negating.
+    let around_surrogates = !CharClass::from('\u{D7FF}'.to('\u{E000}'));
+    assert!(!around_surrogates.contains('\u{D7FF}'));
+    assert!(!around_surrogates.contains('\u{E000}'));
+    assert!(around_surrogates.contains('\u{D7FE}'));
+    assert!(around_surrogates.contains('\u{E001}'));
+
+    assert_eq!(sign.description(), "one of \"+-\"");
+    assert_eq!(
+        CharClass::from_ranges(vec![('a', 'c'), ('x', 'x')]).description(),
+        "['a'-'c' 'x']"
+    );
+}
+
+#[cfg(feature = "unicode")]
+#[test]
+fn test_unicode() {
+    use p_arse::{
+        unicode::{
+            general_category,
+            pattern_white_space,
+            script,
+            xid_continue,
+            xid_start,
+        },
+        CharClass,
+    };
+
+    let ident = (xid_start().or('_'), xid_continue().zore());
+    assert_eq!(ident.p_arse("Straße+1").unwrap().1, "+1");
+    assert_eq!(ident.p_arse("_x1 ").unwrap().1, " ");
+    assert!(ident.p_arse("٣x").is_err());
+
+    let ws = pattern_white_space().zore();
+    assert_eq!(ws.p_arse(" \t\u{200E}\n\u{2029}x").unwrap().1, "x");
+    assert!(pattern_white_space().p_arse("\u{A0}").is_err());
+
+    // Long and short names, groups and loose matching.
+    let digit = general_category("Nd").unwrap();
+    assert!(digit.p_arse("٣").is_ok() && digit.p_arse("x").is_err());
+    let letter = general_category("letter").unwrap();
+    assert_eq!(letter.name(), "Letter");
+    assert!(letter.contains('ß') && letter.contains('漢'));
+    assert!(!letter.contains('1'));
+    let cased = general_category("cased-letter").unwrap();
+    assert!(cased.contains('A') && cased.contains('ǅ'));
+    assert!(!cased.contains('漢'));
+    assert!(general_category("Cs").unwrap().p_arse("\u{D7FF}").is_err());
+
+    let cyrillic = script("cyrl").unwrap();
+    assert_eq!(cyrillic.name(), "CYRILLIC");
+    assert!(cyrillic.contains('ж') && !cyrillic.contains('z'));
+    let old_italic = script("Old Italic").unwrap();
+    assert!(old_italic.contains('\u{10300}'));
+
+    // Properties combine with character classes.
+    let class = CharClass::from(cyrillic) | CharClass::from('a'.to('z'));
+    assert!(class.contains('ж') && class.contains('z'));
+    assert!(!class.contains('Z'));
+    assert!(general_category("Lx").is_none() && script("Klingon").is_none());
+}
+
+#[test]
+fn test_no_case() {
+    use p_arse::{no_case, no_case_ascii};
+
+    let select = no_case("select");
+    assert_eq!(select.p_arse("SELECT a").unwrap(), ("SELECT".into(), " a"));
+    assert_eq!(select.p_arse("sElEcT").unwrap().0, "sElEcT");
+    assert!(select.p_arse("SELEC").is_err());
+
+    // The matched input may differ in length from the literal.
+    let kelvin = no_case("k");
+    assert_eq!(
+        kelvin.p_arse("\u{212A}!").unwrap(),
+        ("\u{212A}".into(), "!")
+    );
+    assert!(no_case_ascii("k").p_arse("\u{212A}").is_err());
+    assert!(no_case("straße").p_arse("STRASSE").is_err());
+    assert!(no_case("łódź").p_arse("ŁÓDŹ").is_ok());
+    assert!(no_case_ascii("łódź").p_arse("ŁÓDŹ").is_err());
+
+    assert_eq!(no_case('x').p_arse("Xy").unwrap(), ('X', "y"));
+    assert!(no_case('x').p_arse("y").is_err());
+
+    let hex = (no_case('0'.to('9')).or(no_case('a'.to('f')))).more();
+    assert!(hex.p_arse("DeadBeef").is_ok());
+    assert!(no_case_ascii('A'.to('Z')).p_arse("q").is_ok());
+    assert!(no_case_ascii('A'.to('Z')).p_arse("ą").is_err());
+
+    // Finding case-insensitive literals uses the first sets.
+    let found: Vec<_> = no_case("sql")
+        .find_iter("SQL, Sql or \u{17F}ql")
+        .map(|m| m.as_str())
+        .collect();
+    assert_eq!(found, vec!["SQL", "Sql", "\u{17F}ql"]);
+}
+
+#[test]
+fn test_literals() {
+    use p_arse::{literals, literals_map};
+
+    let operators = literals(&["=", "<", "<=", "<<", "<<=", ">", ">="]);
+    let operator = &operators;
+    assert_eq!(operator.p_arse("<<=1").unwrap(), ("<<=", "1"));
+    assert_eq!(operator.p_arse("<<1").unwrap(), ("<<", "1"));
+    assert_eq!(operator.p_arse("<=1").unwrap(), ("<=", "1"));
+    assert_eq!(operator.p_arse("< =1").unwrap(), ("<", " =1"));
+    assert!(operator.p_arse("!=").is_err());
+    assert!(operator.p_arse("").is_err());
+
+    // Falls back to the longest literal which has matched completely.
+    let words = literals(&["a", "abcd"]);
+    assert_eq!((&words).p_arse("abcx").unwrap(), ("a", "bcx"));
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum Token {
+        Let,
+        Loop,
+        Ident(String),
+    }
+
+    let keyword =
+        literals_map(vec![("let", Token::Let), ("loop", Token::Loop)]);
+    let ident = 'a'.to('z').more().maps(|s| Token::Ident(s.to_string()));
+    let token = (&keyword, 'a'.to('z').not_ahead()).r1().or(ident);
+    assert_eq!(token.p_arse("loop").unwrap().0, Token::Loop);
+    assert_eq!(
+        token.p_arse("looping").unwrap().0,
+        Token::Ident("looping".into())
+    );
+
+    // Repeated literals keep the last value, the empty literal always matches.
+    let repeated = literals_map(vec![("x", 1), ("x", 2), ("", 0)]);
+    assert_eq!((&repeated).p_arse("x").unwrap().0, 2);
+    assert_eq!((&repeated).p_arse("y").unwrap(), (0, "y"));
+
+    let found: Vec<_> = operator.find_iter("a <= b << c").collect();
+    assert_eq!(found.len(), 2);
+    assert_eq!(found[1].output, "<<");
+}
+
+#[test]
+fn test_lexeme() {
+    use p_arse::lexeme::{
+        block_comment,
+        keyword,
+        lexer,
+        line_comment,
+        nested_block_comment,
+        trivia,
+        ws,
+    };
+
+    let call = (ws(), "f".lexeme(), '('.lexeme(), "x".lexeme(), ')'.lexeme());
+    assert_eq!(call.p_arse(" f ( x\n) \t").unwrap().1, "");
+    assert!(call.p_arse("f(y)").is_err());
+
+    let while_ = keyword("while");
+    assert_eq!(while_.p_arse("while x").unwrap(), ("while", "x"));
+    assert_eq!(while_.p_arse("while").unwrap(), ("while", ""));
+    assert!(while_.p_arse("whilex").is_err());
+    assert!(while_.p_arse("while1").is_err());
+    assert!(while_.p_arse("whileż").is_err());
+
+    // Custom trivia and word characters.
+    let comment = line_comment("#")
+        .or(block_comment("(*", "*)"))
+        .or(nested_block_comment("{-", "-}"));
+    let lx = lexer(trivia(comment)).word_chars(|c| c.is_ascii_lowercase());
+    let stmt = (lx.trivia(), lx.keyword("print"), lx.lexeme('1'));
+    assert_eq!(stmt.p_arse("# x\n (* y *) print# z\n1 ").unwrap().1, "");
+    assert_eq!(stmt.p_arse("print1").unwrap().1, "");
+    assert_eq!(stmt.p_arse("{- {- -} -} print 1").unwrap().1, "");
+    assert!(stmt.p_arse("printx 1").is_err());
+
+    // Unterminated comments are left unparsed.
+    assert_eq!(stmt.p_arse("print 1 (* x").unwrap().1, "(* x");
+    assert!(block_comment("(*", "*)").p_arse("(* x").is_err());
+    assert_eq!(line_comment("--").p_arse("-- x\ny").unwrap().1, "\ny");
+}
+
+#[test]
+fn test_numbers() {
+    use p_arse::numbers::{binary, float, hex, int, octal, uint, FloatSyntax};
+
+    assert_eq!(uint::<u8>().p_arse("255x").unwrap(), (255, "x"));
+    assert!(uint::<u8>().p_arse("-1").is_err());
+    assert!(uint::<u32>().p_arse("x").is_err());
+    assert_eq!(int::<i8>().p_arse("-128").unwrap().0, -128);
+    assert_eq!(int::<i8>().p_arse("+127").unwrap().0, 127);
+    assert_eq!(int::<u8>().p_arse("-0").unwrap().0, 0);
+    assert_eq!(
+        int::<u128>().p_arse(&u128::MAX.to_string()).unwrap().0,
+        u128::MAX
+    );
+
+    // Overflow is reported at the beginning of the number.
+    let input = "x = 128";
+    let assignment = ("x = ", int::<i8>());
+    let error = assignment.p_arse(input).unwrap_err();
+    assert!(format!("{:?}", error).contains("\"128\""));
+    assert!(int::<i8>().p_arse("-129").is_err());
+    assert!(uint::<u64>().p_arse("18446744073709551616").is_err());
+
+    assert_eq!(hex::<u32>().p_arse("DeadBeefx").unwrap(), (0xdeadbeef, "x"));
+    assert_eq!(octal::<u16>().p_arse("0778").unwrap(), (0o77, "8"));
+    assert_eq!(binary::<u8>().p_arse("1010_2").unwrap(), (10, "_2"));
+    assert_eq!(uint::<u64>().radix(36).p_arse("zz").unwrap().0, 36 * 36 - 1);
+
+    let separated = uint::<u32>().separator('_');
+    assert_eq!(separated.p_arse("1_000_000").unwrap(), (1_000_000, ""));
+    assert_eq!(separated.p_arse("1_000__000").unwrap(), (1_000, "__000"));
+    assert!(separated.p_arse("_1").is_err());
+    // The separator is only consumed before a digit.
+    let signed = int::<i64>().separator('_');
+    assert_eq!(signed.p_arse("-1_").unwrap(), (-1, "_"));
+    assert_eq!(signed.p_arse("1__2").unwrap(), (1, "__2"));
+    assert_eq!(
+        hex::<u32>().separator('_').p_arse("f_fg").unwrap(),
+        (0xff, "g")
+    );
+    assert_eq!(
+        hex::<u32>().separator('_').p_arse("f_g").unwrap(),
+        (0xf, "_g")
+    );
+    let bytes = hex::<u8>().digits(2, 2);
+    assert_eq!(
+        (bytes, bytes).p_arse("c0ffee").unwrap(),
+        ((0xc0, 0xff), "ee")
+    );
+    assert!(bytes.p_arse("c").is_err());
+
+    let json = float::<f64>();
+    assert_eq!(json.p_arse("-0.25e+2,").unwrap(), (-25.0, ","));
+    assert_eq!(json.p_arse("12").unwrap().0, 12.0);
+    assert_eq!(json.p_arse("012").unwrap(), (0.0, "12"));
+    assert_eq!(json.p_arse("1.e5").unwrap(), (1.0, ".e5"));
+    assert_eq!(json.p_arse("1e").unwrap(), (1.0, "e"));
+    assert!(json.p_arse("+1").is_err() && json.p_arse(".5").is_err());
+    assert!(json.p_arse("1e309").is_err());
+
+    let rust = float::<f64>().syntax(FloatSyntax::Rust);
+    assert_eq!(rust.p_arse("1_0.2_5e1_0").unwrap().0, 10.25e10);
+    assert_eq!(rust.p_arse("1.;").unwrap(), (1.0, ";"));
+    assert_eq!(rust.p_arse("1.max(2)").unwrap(), (1.0, ".max(2)"));
+    assert_eq!(rust.p_arse("1..2").unwrap(), (1.0, "..2"));
+    assert!(rust.p_arse("_1").is_err());
+
+    let c = float::<f32>().syntax(FloatSyntax::C);
+    assert_eq!(c.p_arse("+.5").unwrap().0, 0.5);
+    assert_eq!(c.p_arse("5.").unwrap().0, 5.0);
+    assert_eq!(c.p_arse("1.5E-1f").unwrap(), (0.15, "f"));
+    assert!(c.p_arse(".").is_err() && c.p_arse("-e1").is_err());
+}
+
+#[test]
+fn test_strings() {
+    use std::borrow::Cow;
+
+    use p_arse::strings::{
+        json_string,
+        raw_string,
+        rust_string,
+        StringLiteral,
+        UnicodeEscape,
+    };
+
+    let json = json_string();
+    assert_eq!(
+        json.p_arse(r#""a\"b\\c\/" x"#).unwrap(),
+        (r#"a"b\c/"#.into(), " x")
+    );
+    assert_eq!(json.p_arse(r#""\u0041\u00DF""#).unwrap().0, "Aß");
+    assert_eq!(json.p_arse(r#""\uD834\uDD1E""#).unwrap().0, "𝄞");
+    assert!(json.p_arse(r#""\uD834x""#).is_err());
+    assert!(json.p_arse(r#""\uDD1E""#).is_err());
+    assert!(json.p_arse(r#""\u12""#).is_err());
+    assert!(json.p_arse(r#""\q""#).is_err());
+    assert!(json.p_arse("\"a\nb\"").is_err());
+    assert!(json.p_arse("'a'").is_err());
+
+    // Borrowed unless escaped.
+    let (content, tail) = json.p_arse_cow("\"zażółć\"!").unwrap();
+    assert!(matches!(content, Cow::Borrowed("zażółć")));
+    assert_eq!(tail, "!");
+    let (content, _) = json.p_arse_cow(r#""a\nb""#).unwrap();
+    assert!(matches!(content, Cow::Owned(_)));
+    assert_eq!(content, "a\nb");
+
+    let rust = rust_string();
+    assert_eq!(rust.p_arse("\"a\nb\"").unwrap().0, "a\nb");
+    assert_eq!(
+        rust.p_arse(r#""\u{48}\u{10FFFF}""#).unwrap().0,
+        "H\u{10FFFF}"
+    );
+    assert!(rust.p_arse(r#""\u{110000}""#).is_err());
+    assert!(rust.p_arse(r#""\u{D800}""#).is_err());
+    assert!(rust.p_arse(r#""\u{1234567}""#).is_err());
+    assert!(rust.p_arse(r#""\u{}""#).is_err());
+    assert!(rust.p_arse(r#""\u{12g}""#).is_err());
+
+    let raw = raw_string();
+    assert_eq!(raw.p_arse(r#"r"\n""#).unwrap().0, r"\n");
+    assert_eq!(raw.p_arse(r##"r#"a"b"#c"##).unwrap(), ("a\"b".into(), "c"));
+    assert!(raw.p_arse(r##"r#"a""##).is_err());
+    assert!(raw.p_arse(r#""a""#).is_err());
+
+    // Single or double quotes, with a custom escape table.
+    let sql: StringLiteral = json_string()
+        .quotes("'\"")
+        .escapes(&[('n', '\n')])
+        .unicode(UnicodeEscape::None)
+        .multi_line(true);
+    assert_eq!(sql.p_arse(r#"'it\'s "ok"'"#).unwrap().0, r#"it's "ok""#);
+    assert_eq!(sql.p_arse(r#""say 'hi'\n""#).unwrap().0, "say 'hi'\n");
+    assert!(sql.p_arse(r#"'\t'"#).is_err());
+    assert!(sql.p_arse(r#"'\u0041'"#).is_err());
+}
+
+#[test]
+fn test_grammar() {
+    use p_arse::grammar::{Expr, Grammar, GrammarError};
+
+    let text = r#"
+        # Arithmetic expressions.
+        Expr    <- Spacing Sum !.
+        Sum     <- Product (("+" / "-") Spacing Product)*
+        Product <- Value (('*' / '/') Spacing Value)*
+        Value   <- Number / '(' Spacing Sum ')' Spacing
+        Number  <- [0-9]+ ('.' [0-9]+)? Spacing
+        Spacing <- [ \t\n]*
+    "#;
+    let grammar: Grammar = text.parse().unwrap();
+    assert_eq!(grammar.rules().count(), 6);
+
+    let (tree, tail) = grammar.start().p_arse(" 1 + 2.5 * (3 - 4)\n").unwrap();
+    assert_eq!(tail, "");
+    assert_eq!(tree.rule, "Expr");
+    let sum = &tree.children[1];
+    assert_eq!(sum.rule, "Sum");
+    assert_eq!(sum.text, "1 + 2.5 * (3 - 4)\n");
+    let product = &sum.children[2];
+    assert_eq!(product.text, "2.5 * (3 - 4)\n");
+    assert_eq!(product.children[0].children[0].text, "2.5 ");
+    assert!(grammar.start().p_arse("1 + * 2").is_err());
+    assert!(grammar.start().p_arse("(1").is_err());
+
+    let number = grammar.rule("Number").unwrap();
+    assert_eq!(number.p_arse("42x").unwrap().1, "x");
+    assert!(grammar.rule("Nope").is_none());
+
+    // Lookaheads, any char, escapes and octal codes.
+    let grammar = Grammar::from_peg(
+        r#"
+        Line    <- (!'\n' .)* &'\n'
+        Quoted  <- '\'' [^\]\\\101-\103-]* "\""
+        Keyword <- 'if' ![a-z]
+        "#,
+    )
+    .unwrap();
+    let line = grammar.start();
+    assert_eq!(line.p_arse("abc\ndef").unwrap().1, "\ndef");
+    assert!(line.p_arse("abc").is_err());
+    let quoted = grammar.rule("Quoted").unwrap();
+    assert_eq!(quoted.p_arse("'^]\\ABC-\"").unwrap().1, "");
+    assert!(quoted.p_arse("'D\"").is_err());
+    let keyword = grammar.rule("Keyword").unwrap();
+    assert!(keyword.p_arse("if(").is_ok() && keyword.p_arse("iffy").is_err());
+
+    // Printing in the notation and parsing back.
+    let printed = grammar.to_string();
+    assert_eq!(
+        printed.lines().collect::<Vec<_>>(),
+        vec![
+            r"Line    <- (!'\n' .)* &'\n'",
+            r#"Quoted  <- '\'' [\055A-C\\-^]* '"'"#,
+            r"Keyword <- 'if' ![a-z]",
+        ]
+    );
+    let reparsed = Grammar::from_peg(&printed).unwrap();
+    assert!(grammar.rules().eq(reparsed.rules()));
+
+    let nested = Expr::Sequence(vec![
+        Expr::Not(Box::new(Expr::Choice(vec![Expr::Any, Expr::Any]))),
+        Expr::OneOrMore(Box::new(Expr::Sequence(vec![]))),
+        Expr::Optional(Box::new(Expr::And(Box::new(Expr::Any)))),
+    ]);
+    assert_eq!(nested.to_string(), "!(. / .) ''+ (&.)?");
+
+    // `i` right after a literal or a class makes it case-insensitive.
+    let text = "A <- 'select'i [a-c]i+ i\ni <- 'i'\n";
+    let grammar = Grammar::from_peg(text).unwrap();
+    assert_eq!(grammar.start().p_arse("SeLectbCAi").unwrap().1, "");
+    assert!(grammar.start().p_arse("selectdi").is_err());
+    assert_eq!(grammar.to_string(), text);
+
+    let error = |text| Grammar::from_peg(text).unwrap_err();
+    assert_eq!(
+        error("A <- 'a'\nB <- C"),
+        GrammarError {
+            message: "rule C is not defined".into(),
+            line: 2,
+            column: 1,
+        }
+    );
+    assert_eq!(
+        error("A <- 'a'\n  A <- 'b'").to_string(),
+        "2:3: rule A is already defined"
+    );
+    assert_eq!(error("A <- 'a' )").to_string(), "1:10: unexpected `)`");
+    assert_eq!(error("A <- (B\nB <- 'b'").to_string(), "2:1: expected `)`");
+    assert_eq!(error("# nothing\n").message, "no rules");
+    assert_eq!(error("A <- [a-").to_string(), "1:6: unterminated class");
+    assert_eq!(error("A <- 'a\n").to_string(), "1:6: unterminated literal");
+    assert_eq!(error("A <- '\\q'").to_string(), "1:7: invalid escape");
+    assert_eq!(error("A 'a'").to_string(), "1:3: expected `<-`");
+    assert_eq!(
+        error("A <- !").to_string(),
+        "1:7: expected an expression after `!`"
+    );
+    assert_eq!(
+        error("A <- 'a'\nB <- C? D\nC <- 'c'\nD <- B 'd'").to_string(),
+        "2:1: left recursion: B -> D -> B"
+    );
+}
+
+#[test]
+fn test_parse() {
+    use p_arse::{parse, parse::separated};
+
+    assert_eq!(parse::<bool>().p_arse("false!").unwrap(), (false, "!"));
+    assert_eq!(parse::<i8>().p_arse("-128").unwrap().0, -128);
+    assert!(parse::<u8>().p_arse("-1").is_err());
+    assert_eq!(parse::<f64>().p_arse("2.5e1").unwrap().0, 25.0);
+    assert_eq!(parse::<Option<char>>().p_arse("").unwrap().0, None);
+    assert_eq!(parse::<Box<u8>>().p_arse("7").unwrap().0, Box::new(7));
+
+    let (digits, tail) = parse::<Vec<u8>>().p_arse("1x").unwrap();
+    assert_eq!((digits, tail), (vec![1], "x"));
+    // Stops instead of looping on elements matching nothing.
+    assert_eq!(parse::<Vec<()>>().p_arse("x").unwrap(), (vec![()], "x"));
+
+    let list = separated::<u16, _>("::", false);
+    assert_eq!(list.p_arse("1::2:3").unwrap(), (vec![1, 2], ":3"));
+    assert_eq!(list.p_arse(":: 1").unwrap(), (vec![], ":: 1"));
+}
+
+#[test]
+fn test_describe() {
+    use p_arse::{
+        describe::GrammarNode::{self, *},
+        eoi,
+        function,
+        grammar::Grammar,
+        literals,
+        no_case,
+        rule,
+    };
+
+    let literal = |s: &str| Literal(s.to_string());
+
+    let value = (
+        "null".ignore().or(no_case("true").ignore()),
+        ','.not_ahead(),
+        eoi(),
+    )
+        .named("value");
+    assert_eq!(
+        value.describe(),
+        Rule {
+            name: "value".into(),
+            node: Box::new(Sequence(vec![
+                Choice(vec![
+                    literal("null"),
+                    NoCase(Box::new(literal("true"))),
+                ]),
+                GrammarNode::lookahead(literal(","), false),
+                End,
+            ])),
+        }
+    );
+
+    // Choices and sequences are flattened, maps are transparent.
+    let flat = ('a'.or('b').or('c'.map(|c| c)), ('d', ('e', any())).r0());
+    assert_eq!(
+        flat.describe(),
+        Sequence(vec![
+            Choice(vec![literal("a"), literal("b"), literal("c")]),
+            literal("d"),
+            literal("e"),
+            Any,
+        ])
+    );
+
+    // The longest literals are tried first.
+    let operators = literals(&["<", "<=", "="]);
+    assert_eq!(
+        (&operators).describe(),
+        Choice(vec![literal("<="), literal("<"), literal("=")])
+    );
+
+    fn x(tail: &str) -> p_arse::Result<'_, char> {
+        'x'.p_arse(tail)
+    }
+    assert_eq!(fun(&x).describe(), Opaque("function".into()));
+
+    // The recursion through a function can't be seen.
+    let nested =
+        rec(&|tail, nested| ('[', nested.zore(), ']').ignore().p_arse(tail));
+    assert_eq!(
+        nested.named("nested").describe(),
+        Rule {
+            name: "nested".into(),
+            node: Box::new(Opaque("function".into())),
+        }
+    );
+
+    // The recursion through rules can, each rule is described once.
+    const LIST: function::Rule<'static, ()> =
+        rule("list", &|_| ('(', ITEM.zore(), ')').ignore());
+    const ITEM: function::Rule<'static, ()> =
+        rule("item", &|_| LIST.or('a'.ignore()));
+    let list = Rule {
+        name: "list".into(),
+        node: Box::new(Sequence(vec![
+            literal("("),
+            GrammarNode::repeat(
+                Rule {
+                    name: "item".into(),
+                    node: Box::new(Choice(vec![
+                        Ref("list".into()),
+                        literal("a"),
+                    ])),
+                },
+                0,
+                None,
+            ),
+            literal(")"),
+        ])),
+    };
+    assert!(LIST.p_arse("(a(a)())").is_ok());
+    assert_eq!(LIST.describe(), list);
+    assert_eq!(LIST.describe(), list);
+
+    let grammar = Grammar::from_peg("A <- B? !'a'\nB <- [a-c]*").unwrap();
+    let rules = grammar.start().describe().rules();
+    assert_eq!(
+        rules,
+        vec![(
+            "A".to_string(),
+            Sequence(vec![
+                GrammarNode::repeat(Ref("B".into()), 0, Some(1)),
+                GrammarNode::lookahead(literal("a"), false),
+            ])
+        )]
+    );
+}
+
+#[test]
+fn test_export() {
+    use p_arse::{
+        eoi,
+        export::{export, Notation},
+        grammar::Grammar,
+        no_case,
+        none_of,
+    };
+
+    let quote = '"';
+    let not_quote = none_of("\"\n");
+    let string = (quote, (&not_quote).zore(), quote).named("string");
+    let keyword = no_case("null").ignore().or(" \t".ignore());
+    let line = (
+        keyword.or(string.ignore()),
+        ('a', ('b', 'c').not_ahead()).more(),
+        eoi(),
+    );
+
+    assert_eq!(
+        export(&line, Notation::Peg),
+        "\
+start  <- ('null'i / ' \\t' / string) ('a' !('b' 'c'))+ !.
+string <- '\"' [\\000-\\t\\013-!#-\\U0010ffff]* '\"'
+"
+        .replace("\\U0010ffff", "\u{10ffff}"),
+    );
+    assert_eq!(
+        export(&line, Notation::Ebnf),
+        "\
+start  ::= ([nN] [uU] [lL] [lL] | \" \" #x9 | string) (\"a\" /* not followed by \"b\" \"c\" */)+ /* end of input */
+string ::= #x22 [#x0-#x9#xB-!#-#x10FFFF]* #x22
+",
+    );
+    assert_eq!(
+        export(&line, Notation::Abnf),
+        "\
+start  = (\"null\" / \" \" %x9 / string) 1*(%s\"a\" <not followed by %s\"b\" %s\"c\">) <end of input>
+string = %x22 *(%x0-9 / %xB-21 / %x23-10FFFF) %x22
+",
+    );
+
+    // The parser itself is a fresh rule, and case-insensitive classes are
+    // described in the notations without them.
+    let start = 'x'.named("start");
+    let hex = (no_case('a'.to('f')), start);
+    assert_eq!(
+        export(&hex, Notation::Peg),
+        "start2 <- [a-f]i start\nstart  <- 'x'\n",
+    );
+    assert_eq!(
+        export(&hex, Notation::Ebnf),
+        "start2 ::= /* case-insensitive [a-f] */ start\nstart  ::= \"x\"\n",
+    );
+    assert_eq!(
+        export(&hex, Notation::Abnf),
+        "start2 = <case-insensitive %x61-66> start\nstart  = %s\"x\"\n",
+    );
+
+    // The rules of grammars refer to each other by name.
+    let text = "Expr <- Term ('+' Term)*\nTerm <- [0-9]+ / '(' Expr ')'\n";
+    let grammar = Grammar::from_peg(text).unwrap();
+    assert_eq!(
+        export(&grammar.start(), Notation::Peg),
+        "Expr <- Term ('+' Term)*\n",
+    );
+
+    // The PEGs can be read back, with the opaque parsers as comments.
+    let exported = export(&line, Notation::Peg);
+    assert_eq!(Grammar::from_peg(&exported).unwrap().to_string(), exported);
+
+    fn digit(tail: &str) -> p_arse::Result<'_, char> {
+        '0'.to('9').p_arse(tail)
+    }
+    let number = ('-', fun(&digit).more(), no_case('e'.to('f')));
+    let exported = export(&number, Notation::Peg);
+    assert_eq!(exported, "start <- '-' (# function\n         )+ [ef]i\n");
+    let grammar = Grammar::from_peg(&exported).unwrap();
+    assert_eq!(grammar.to_string(), "start <- '-' ''+ [ef]i\n");
+}
+
+#[test]
+fn test_diagram() {
+    use p_arse::{
+        describe::GrammarNode,
+        diagram::{railroad, svg},
+        eoi,
+        no_case,
+    };
+
+    let word = ('a'.to('z').more(), "'s".not_ahead()).named("word");
+    let words = (
+        word,
+        (" ".or(", "), word).zore(),
+        no_case("<end>").opt(),
+        eoi(),
+    );
+
+    let html = railroad(&words);
+    assert_eq!(html.matches("<svg").count(), 2);
+    assert_eq!(html.matches("</svg>").count(), 2);
+    assert!(html.find(r#"<h2 id="start">"#) < html.find(r#"<h2 id="word">"#));
+    assert_eq!(html.matches(r##"<a href="#word">"##).count(), 2);
+    assert!(html.contains(">not followed by</text>"));
+    assert!(html.contains(">ignoring case</text>"));
+    assert!(html.contains(">'&lt;end&gt;'</text>"));
+    assert!(html.contains(">end of input</text>"));
+
+    let repeated =
+        GrammarNode::repeat(GrammarNode::Literal("ab".into()), 2, None);
+    let diagram = svg(&repeated);
+    assert!(diagram.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(diagram.contains(">at least 2 times</text>"));
+    assert!(!diagram.contains("<h2"));
+
+    // Repetitions which can match nothing can be skipped.
+    let ab = GrammarNode::Literal("ab".into());
+    let at_most = svg(&GrammarNode::repeat(ab.clone(), 0, Some(3)));
+    let one_to = svg(&GrammarNode::repeat(ab, 1, Some(3)));
+    assert!(at_most.contains(">at most 3 times</text>"));
+    assert!(at_most.matches("<path").count() > one_to.matches("<path").count());
+}
+
+#[test]
+fn test_lint() {
+    use p_arse::{
+        any,
+        describe::GrammarNode,
+        eoi,
+        grammar::Grammar,
+        lint::{lint, Lint},
+        no_case,
+    };
+
+    let literal = |s: &str| GrammarNode::Literal(s.into());
+
+    // Shadowed alternatives.
+    let keyword = "for"
+        .ignore()
+        .or(no_case("IF").ignore())
+        .or("if".ignore())
+        .or("iffy".ignore())
+        .or("forward".ignore());
+    assert_eq!(
+        lint(&keyword),
+        vec![
+            Lint::ShadowedAlternative {
+                rule: "start".into(),
+                alternative: literal("if"),
+                by: GrammarNode::NoCase(Box::new(literal("IF"))),
+            },
+            Lint::ShadowedAlternative {
+                rule: "start".into(),
+                alternative: literal("iffy"),
+                by: GrammarNode::NoCase(Box::new(literal("IF"))),
+            },
+            Lint::ShadowedAlternative {
+                rule: "start".into(),
+                alternative: literal("forward"),
+                by: literal("for"),
+            },
+        ],
+    );
+    assert_eq!(lint(&"a".ignore().or(no_case("ab").ignore())), vec![]);
+    assert_eq!(
+        lint(&'a'.to('z').ignore().or(("if", 'x').ignore())).len(),
+        1
+    );
+    assert_eq!(lint(&"a".opt().ignore().or("b".ignore())).len(), 1);
+    assert_eq!(lint(&any().ignore().or("a".ignore()).or(eoi())).len(), 1);
+
+    // Nullable repetitions.
+    assert_eq!(lint(&"".zore()).len(), 1);
+    assert_eq!(lint(&eoi().more()).len(), 1);
+    assert_eq!(
+        lint(&"a".opt().more())[0].to_string(),
+        "rule start: ('a'?)+ repeats a parser which can match nothing",
+    );
+    assert_eq!(lint(&("a", "b".opt()).zore()), vec![]);
+    assert_eq!(lint(&"a".zore().opt()), vec![]);
+
+    // Left recursion.
+    let rule = |name: &str, node| GrammarNode::Rule {
+        name: name.into(),
+        node: Box::new(node),
+    };
+    let value = rule(
+        "Value",
+        GrammarNode::choice(vec![
+            rule(
+                "Sum",
+                GrammarNode::sequence(vec![
+                    GrammarNode::repeat(
+                        GrammarNode::Literal("-".into()),
+                        0,
+                        Some(1),
+                    ),
+                    GrammarNode::Ref("Value".into()),
+                    GrammarNode::Literal("+".into()),
+                ]),
+            ),
+            GrammarNode::Class(vec![('0', '9')]),
+        ]),
+    );
+    assert_eq!(
+        value.lint()[0].to_string(),
+        "left recursion: Value -> Sum -> Value",
+    );
+    let body = |sum| (sum, '+', 'n').ignore().or('n'.ignore());
+    let sum = p_arse::rule("sum", &body);
+    assert_eq!(
+        lint(&sum),
+        vec![Lint::LeftRecursion {
+            rules: vec!["sum".into()],
+        }],
+    );
+
+    // Unused rules.
+    let grammar = Grammar::from_peg(
+        "
+        Start  <- Value !.
+        Value  <- Sum / Number
+        Sum    <- Number '+' Value
+        Number <- [0-9]+ ('.' Number)?
+        Word   <- [a-z]+
+        ",
+    )
+    .unwrap();
+    let lints: Vec<_> = grammar.lint().iter().map(|l| l.to_string()).collect();
+    assert_eq!(lints, vec!["rule Word is not used"]);
+
+    // Rules with the same name, unless it's the same rule used twice.
+    let digit = '0'.to('9').named("digit");
+    assert_eq!(lint(&(digit, '.', digit)), vec![]);
+    let letter = 'a'.to('z').named("digit");
+    assert_eq!(
+        lint(&(digit, letter, digit, letter)),
+        vec![Lint::DuplicateRule {
+            rule: "digit".into(),
+        }],
+    );
+    assert_eq!(
+        lint(&letter.or(digit))[0].to_string(),
+        "rule digit is defined more than once",
+    );
+}
+
+#[test]
+fn test_empty_match() {
+    use p_arse::{eoi, wrapper::EmptyMatch};
+
+    // The repetitions stop instead of looping forever.
+    assert_eq!(eoi().zore().p_arse("").unwrap(), (vec![], ""));
+    assert_eq!(eoi().more().p_arse("").unwrap(), (vec![()], ""));
+    assert_eq!("".zore().p_arse("a").unwrap(), (vec![], "a"));
+    assert_eq!(
+        'a'.opt().more().p_arse("aab").unwrap(),
+        (vec![Some('a'), Some('a')], "b"),
+    );
+    assert_eq!('a'.opt().more().p_arse("b").unwrap(), (vec![None], "b"));
+
+    // Or fail, if configured to.
+    let a = 'a'.opt().more().empty_match(EmptyMatch::Fail);
+    let error = a.p_arse("aab").unwrap_err();
+    assert_eq!(error.expectation(), "repetition of 'a'? consuming input");
+    assert_eq!(error.tail(), "b");
+    assert!(eoi()
+        .zore()
+        .empty_match(EmptyMatch::Fail)
+        .p_arse("")
+        .is_err());
+    assert_eq!(
+        'a'.zore()
+            .empty_match(EmptyMatch::Fail)
+            .p_arse("aab")
+            .unwrap(),
+        (vec!['a', 'a'], "b"),
+    );
+
+    // The state changes of the empty match are undone.
+    let counted = "".map_with_state(|_, count: &mut usize| *count += 1);
+    let mut count = 0;
+    counted.zore().p_arse_with("", &mut count).unwrap();
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn test_limits() {
+    use p_arse::{
+        grammar::Grammar,
+        limits::{LimitExceeded, Limits},
+        parse::separated,
+    };
+
+    fn limit<T>(result: p_arse::Result<T>) -> Option<LimitExceeded> {
+        result.err().and_then(|error| error.limit_exceeded())
+    }
+
+    // A JSON-like array, e.g. `[1,[2,[]]]`.
+    let array = rec(&|tail, array| {
+        let value = array.or('0'.to('9').ignore());
+        let values = (value, (',', value).zore()).opt();
+
+        ('[', values, ']').ignore().p_arse(tail)
+    });
+    let deep = format!("{}{}", "[".repeat(10_000), "]".repeat(10_000));
+    let limits = Limits::default().max_depth(50);
+    assert_eq!(
+        limit(array.p_arse_limited(&deep, limits)),
+        Some(LimitExceeded::Depth)
+    );
+    assert!(array.p_arse_limited("[1,[2,[]],3]", limits).is_ok());
+
+    // The limit can't be recovered from, e.g. with `.opt()`.
+    let recovered = array.opt();
+    assert_eq!(
+        limit(recovered.p_arse_limited(&deep, limits)),
+        Some(LimitExceeded::Depth),
+    );
+    assert!(recovered.p_arse(&deep[9_990..]).is_ok());
+
+    // Backtracking.
+    let slow = rec(&|tail, slow| {
+        let a = 'a'.ignore();
+
+        (a, slow, 'c')
+            .ignore()
+            .or((a, slow, 'b').ignore())
+            .or(a)
+            .p_arse(tail)
+    });
+    let limits = Limits::default().max_steps(10_000);
+    let input = "a".repeat(30);
+    assert_eq!(
+        limit(slow.p_arse_limited(&input, limits)),
+        Some(LimitExceeded::Steps)
+    );
+    assert!(slow.p_arse_limited("aaaaa", limits).is_ok());
+
+    // Input length and repetitions.
+    let limits = Limits::default().max_input_len(4).max_repetitions(2);
+    let repetitions = Limits::default().max_repetitions(2);
+    let error = 'a'.zore().p_arse_limited("aaaaa", limits).unwrap_err();
+    assert_eq!(error.limit_exceeded(), Some(LimitExceeded::InputLength));
+    assert_eq!(error.expectation(), "input length of at most 4");
+    assert_eq!(
+        limit('a'.more().p_arse_limited("aaa", limits)),
+        Some(LimitExceeded::Repetitions),
+    );
+    assert_eq!(
+        limit(
+            separated::<u8, _>(',', false).p_arse_limited("1,2,3", repetitions)
+        ),
+        Some(LimitExceeded::Repetitions),
+    );
+    assert!('a'.more().p_arse_limited("aab", limits).is_ok());
+
+    // Grammars.
+    let grammar = Grammar::from_peg("List <- '(' List* ')'").unwrap();
+    let limits = Limits::default().max_depth(20);
+    let nested = format!("{}{}", "(".repeat(30), ")".repeat(30));
+    assert_eq!(
+        limit(grammar.start().p_arse_limited(&nested, limits)),
+        Some(LimitExceeded::Depth),
+    );
+    assert!(grammar
+        .start()
+        .p_arse_limited(&nested[11..49], limits)
+        .is_ok());
+    let grammar = Grammar::from_peg("Items <- ('a' / 'b')*").unwrap();
+    assert_eq!(
+        limit(grammar.start().p_arse_limited("aaa", repetitions)),
+        Some(LimitExceeded::Repetitions),
+    );
+    let limits = Limits::default().max_steps(10);
+    assert_eq!(
+        limit(grammar.start().p_arse_limited("bbbb", limits)),
+        Some(LimitExceeded::Steps),
+    );
+    assert!(grammar.start().p_arse_limited("b", limits).is_ok());
+
+    // The limits don't apply outside of `p_arse_limited`.
+    assert!(grammar.start().p_arse(&nested).is_ok());
+    assert!('a'.more().p_arse("aaa").is_ok());
+
+    // Derived recursive types.
+    #[cfg(feature = "derive")]
+    {
+        use p_arse::{parse, Parse};
+
+        #[derive(Parse, Debug, PartialEq)]
+        enum Expr {
+            #[p_arse(lit = "-")]
+            Neg(Box<Expr>),
+            Number(u32),
+        }
+
+        let limits = Limits::default().max_depth(100);
+        let deep = format!("{}1", "-".repeat(1_000_000));
+        assert_eq!(
+            limit(parse::<Expr>().p_arse_limited(&deep, limits)),
+            Some(LimitExceeded::Depth),
+        );
+        let (expr, _) = parse::<Expr>().p_arse_limited("--1", limits).unwrap();
+        let neg = |expr| Expr::Neg(Box::new(expr));
+        assert_eq!(expr, neg(neg(Expr::Number(1))));
+    }
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn test_derive_reexport() {
+    use p_arse::{parse, Parse};
+
+    #[derive(Parse, Debug, PartialEq)]
+    #[p_arse(lit = "#")]
+    struct Tag(u8);
+
+    assert_eq!(parse::<Tag>().p_arse("#7").unwrap().0, Tag(7));
+}
@@ -0,0 +1,146 @@
+#!/usr/bin/env python3
+"""Generates `src/unicode/tables.rs`.
+
+The tables are extracted from the Unicode database bundled with the `regex`
+package (`pip install regex`), so that they don't have to be downloaded. The
+output is formatted with `rustfmt`.
+
+    python3 tools/unicode_tables.py > src/unicode/tables.rs
+"""
+
+import os
+import subprocess
+import sys
+
+import regex
+import regex._regex as _regex
+
+CHARS = [chr(c) for c in range(0x110000) if not 0xD800 <= c <= 0xDFFF]
+
+# The categories which are not unions of other categories. Surrogates are not
+# `char`s.
+CATEGORIES = [
+    "Lu", "Ll", "Lt", "Lm", "Lo",
+    "Mn", "Mc", "Me",
+    "Nd", "Nl", "No",
+    "Pc", "Pd", "Ps", "Pe", "Pi", "Pf", "Po",
+    "Sm", "Sc", "Sk", "So",
+    "Zs", "Zl", "Zp",
+    "Cc", "Cf", "Co", "Cn",
+]
+
+
+# A character first assigned by each version of Unicode, from the oldest. The
+# `regex` package doesn't tell the version of its database, so it's the latest
+# version whose character is assigned.
+VERSIONS = [
+    ("13.0.0", 0x08BE),
+    ("14.0.0", 0x061D),
+    ("15.0.0", 0x0CF3),
+    ("15.1.0", 0x2FFC),
+    ("16.0.0", 0x0897),
+    ("17.0.0", 0x10940),
+]
+
+
+def unicode_version():
+    unassigned = regex.compile(r"\p{Cn}")
+    assigned = [
+        version
+        for version, code in VERSIONS
+        if not unassigned.match(chr(code))
+    ]
+
+    if not assigned:
+        sys.exit("Unicode database older than %s" % VERSIONS[0][0])
+
+    return assigned[-1]
+
+
+def ranges(prop):
+    pattern = regex.compile(r"\p{%s}" % prop)
+    result = []
+
+    for c in CHARS:
+        if pattern.match(c):
+            code = ord(c)
+            if result and result[-1][1] + 1 == code:
+                result[-1][1] = code
+            # Ranges may span the surrogates.
+            elif result and result[-1][1] == 0xD7FF and code == 0xE000:
+                result[-1][1] = code
+            else:
+                result.append([code, code])
+
+    return result
+
+
+def table(name, prop):
+    entries = ["('\\u{%x}', '\\u{%x}')," % (a, b) for a, b in ranges(prop)]
+
+    return "pub(crate) const %s: &[(char, char)] = &[\n%s\n];\n" % (
+        name,
+        "\n".join(entries),
+    )
+
+
+def scripts():
+    _, values = _regex.get_properties()["SCRIPT"]
+    names = {}
+
+    for name, value in values.items():
+        names.setdefault(value, []).append(name)
+
+    return sorted(names.values(), key=lambda names: names[0])
+
+
+def tables():
+    lines = []
+    emit = lines.append
+
+    emit("// Generated by `tools/unicode_tables.py`, do not edit.")
+    emit("// Unicode %s." % unicode_version())
+    emit("")
+    emit("pub(crate) type Ranges = &'static [(char, char)];")
+    emit("")
+    emit(table("XID_START", "XID_Start"))
+    emit(table("XID_CONTINUE", "XID_Continue"))
+    emit(table("PATTERN_WHITE_SPACE", "Pattern_White_Space"))
+
+    emit("pub(crate) const CATEGORIES: &[(&str, Ranges)] = &[")
+    for category in CATEGORIES:
+        emit('    ("%s", %s),' % (category, category.upper()))
+    emit("];")
+    emit("")
+    for category in CATEGORIES:
+        emit(table(category.upper(), "Gc=%s" % category))
+
+    emit("// Loosely matched names and aliases of the scripts.")
+    emit("pub(crate) const SCRIPTS: &[(&[&str], Ranges)] = &[")
+    for names in scripts():
+        emit(
+            "    (&[%s], SCRIPT_%s),"
+            % (", ".join('"%s"' % name for name in names), names[0])
+        )
+    emit("];")
+    emit("")
+    for names in scripts():
+        emit(table("SCRIPT_%s" % names[0], "Script=%s" % names[0]))
+
+    return "\n".join(lines) + "\n"
+
+
+def main():
+    root = os.path.dirname(os.path.dirname(os.path.abspath(__file__)))
+    rustfmt = ["rustfmt", "--edition", "2018", "--config-path", root]
+    formatted = subprocess.run(
+        rustfmt,
+        input=tables(),
+        stdout=subprocess.PIPE,
+        check=True,
+        universal_newlines=True,
+    )
+    sys.stdout.write(formatted.stdout)
+
+
+main()